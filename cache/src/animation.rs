@@ -0,0 +1,176 @@
+//! Animated values interpolating toward a target signal.
+//!
+//! [`tween`] returns a memo that moves smoothly toward the value of a target signal
+//! whenever the target changes. Animations advance during [`flush`](crate::flush),
+//! so an application typically flushes once per frame.
+
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::{Effect, EffectOptions, Memo, Signal, SignalSetter};
+
+/// Values that can be interpolated by [`tween`].
+///
+/// # Examples
+///
+/// ```
+/// use reactive_cache::animation::Lerp;
+///
+/// #[derive(Clone, PartialEq)]
+/// struct Point(f32, f32);
+///
+/// impl Lerp for Point {
+///     fn lerp(&self, to: &Self, t: f64) -> Self {
+///         Point(self.0.lerp(&to.0, t), self.1.lerp(&to.1, t))
+///     }
+/// }
+///
+/// assert_eq!(2.0_f64.lerp(&4.0, 0.5), 3.0);
+/// ```
+pub trait Lerp: Clone {
+    /// Returns the value at `t` between `self` (at 0) and `to` (at 1).
+    fn lerp(&self, to: &Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(&self, to: &Self, t: f64) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, to: &Self, t: f64) -> Self {
+        self + (to - self) * t as f32
+    }
+}
+
+/// Common easing functions, mapping linear progress in `[0, 1]` to eased progress.
+pub mod easing {
+    /// Constant speed.
+    pub fn linear(t: f64) -> f64 {
+        t
+    }
+
+    /// Starts slowly and accelerates.
+    pub fn ease_in_quad(t: f64) -> f64 {
+        t * t
+    }
+
+    /// Starts quickly and decelerates.
+    pub fn ease_out_quad(t: f64) -> f64 {
+        t * (2.0 - t)
+    }
+
+    /// Accelerates until halfway, then decelerates.
+    pub fn ease_in_out_quad(t: f64) -> f64 {
+        if t < 0.5 {
+            2.0 * t * t
+        } else {
+            -1.0 + (4.0 - 2.0 * t) * t
+        }
+    }
+
+    /// Starts quickly and decelerates more gently than [`ease_out_quad`].
+    pub fn ease_out_cubic(t: f64) -> f64 {
+        1.0 - (1.0 - t).powi(3)
+    }
+}
+
+/// The animation currently running.
+struct Animation<T> {
+    from: T,
+    to: T,
+    /// When the animation started, or `None` once it reached `to`.
+    start: Option<Instant>,
+}
+
+/// Returns a memo that animates toward the value of `target` whenever it changes.
+///
+/// Each animation starts from the currently displayed value, so retargeting
+/// mid-animation continues smoothly. It starts on the first [`flush`](crate::flush)
+/// after the change, advances on every following flush, and lands exactly on
+/// the target once `duration` has elapsed.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use reactive_cache::{animation::{easing, tween}, prelude::*};
+///
+/// let target = Signal::new(0.0);
+/// let opacity = tween(target.clone(), Duration::from_millis(20), easing::linear);
+/// assert_eq!(opacity.get(), 0.0);
+///
+/// target.set(1.0);
+/// reactive_cache::flush();
+/// let halfway = opacity.get();
+/// assert!((0.0..1.0).contains(&halfway));
+///
+/// std::thread::sleep(Duration::from_millis(30));
+/// reactive_cache::flush();
+/// assert_eq!(opacity.get(), 1.0);
+/// ```
+pub fn tween<T>(
+    target: Rc<Signal<T>>,
+    duration: Duration,
+    easing: impl Fn(f64) -> f64 + 'static,
+) -> Rc<Memo<T>>
+where
+    T: Lerp + PartialEq + 'static,
+{
+    let initial = target.clone_detached();
+    let output = Signal::new(initial.clone());
+    let animation = RefCell::new(Animation {
+        from: initial.clone(),
+        to: initial,
+        start: None,
+    });
+
+    // Setting `frame` while animating queues the effect for the next flush.
+    let frame = Signal::new(0_u64);
+
+    let effect = Effect::new_with_options(
+        {
+            let output = output.clone();
+            move || {
+                let to = target.get().clone();
+                let next_frame = *frame.get() + 1;
+
+                let mut animation = animation.borrow_mut();
+                let now = Instant::now();
+                if animation.to != to {
+                    *animation = Animation {
+                        from: output.clone_detached(),
+                        to,
+                        start: Some(now),
+                    };
+                }
+                let Some(start) = animation.start else { return };
+
+                let t = if duration.is_zero() {
+                    1.0
+                } else {
+                    (now - start).as_secs_f64() / duration.as_secs_f64()
+                };
+                if t >= 1.0 {
+                    animation.start = None;
+                    output.set(animation.to.clone());
+                } else {
+                    output.set(animation.from.lerp(&animation.to, easing(t)));
+                    frame.set(next_frame);
+                }
+            }
+        },
+        EffectOptions::new().deferred(),
+    );
+
+    Memo::new(move || {
+        // The memo owns the effect driving the animation.
+        let _ = &effect;
+
+        output.get().clone()
+    })
+}
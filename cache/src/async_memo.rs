@@ -0,0 +1,218 @@
+//! Memoized asynchronous computations.
+
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Wake, Waker},
+};
+
+use crate::{
+    MemoNode, NodeId, Observable, Tracker,
+    inspect::{self, DebugValue, Event, NodeKind},
+    memo::SameValue,
+    memo_stack,
+    revision::Revision,
+    runtime,
+};
+
+type Computation<T> = Pin<Box<dyn Future<Output = T>>>;
+
+/// A computation in flight, shared by every caller awaiting it.
+struct Pending<T> {
+    future: Computation<T>,
+    started: Revision,
+    wakers: Arc<Wakers>,
+}
+
+/// Wakes every task awaiting a shared computation.
+#[derive(Default)]
+pub(crate) struct Wakers(Mutex<Vec<Waker>>);
+
+impl Wakers {
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut wakers = self.0.lock().unwrap();
+        if !wakers.iter().any(|w| w.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+}
+
+impl Wake for Wakers {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        for waker in self.0.lock().unwrap().drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// A memoized asynchronous computation.
+///
+/// The first [`get`](AsyncMemo::get) runs the computation and caches its output;
+/// later calls return the cached value until one of the signals or memos read by the
+/// computation changes. Reads are tracked during every poll of the computation, including
+/// after an `await`. Callers awaiting while a computation is in flight share it instead
+/// of starting another one.
+///
+/// Unlike a [bounded](crate::Memo::set_bounded) memo, the value is never evicted from the
+/// memo. `AsyncMemo` does not depend on any executor.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, pin::pin, rc::Rc, task::{Context, Poll, Waker}};
+/// use reactive_cache::{AsyncMemo, prelude::*};
+///
+/// fn block_on<F: Future>(future: F) -> F::Output {
+///     let mut future = pin!(future);
+///     let mut cx = Context::from_waker(Waker::noop());
+///     loop {
+///         if let Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+///             return value;
+///         }
+///     }
+/// }
+///
+/// let user_id = Signal::new(1);
+/// let requests = Rc::new(Cell::new(0));
+/// let profile = AsyncMemo::new({
+///     let (user_id, requests) = (user_id.clone(), requests.clone());
+///     move || {
+///         let (user_id, requests) = (user_id.clone(), requests.clone());
+///         async move {
+///             requests.set(requests.get() + 1);
+///             format!("user #{}", *user_id.get())
+///         }
+///     }
+/// });
+///
+/// assert_eq!(block_on(profile.get()), "user #1");
+/// assert_eq!(block_on(profile.get()), "user #1");
+/// assert_eq!(requests.get(), 1);
+///
+/// user_id.set(2);
+/// assert_eq!(block_on(profile.get()), "user #2");
+/// assert_eq!(requests.get(), 2);
+/// ```
+pub struct AsyncMemo<T> {
+    f: Box<dyn Fn() -> Computation<T>>,
+    value: RefCell<Option<T>>,
+    pending: RefCell<Option<Pending<T>>>,
+    tracker: Tracker,
+    weak: Weak<AsyncMemo<T>>,
+}
+
+impl<T: Clone + 'static> AsyncMemo<T> {
+    /// Creates a memo computing its value with the future returned by `f`.
+    #[track_caller]
+    pub fn new<F>(f: impl Fn() -> F + 'static) -> Rc<Self>
+    where
+        F: Future<Output = T> + 'static,
+    {
+        runtime::increment(&runtime::MEMOS);
+        let tracker = Tracker::new();
+        crate::scope::owned(Rc::new_cyclic(|weak| AsyncMemo {
+            f: Box::new(move || Box::pin(f())),
+            value: RefCell::new(None),
+            pending: RefCell::new(None),
+            tracker,
+            weak: weak.clone(),
+        }))
+    }
+
+    /// Returns the unique id of this memo.
+    pub fn id(&self) -> NodeId {
+        self.tracker.id()
+    }
+
+    /// Returns the cached value, or awaits its computation if it is missing or outdated.
+    ///
+    /// The caller is tracked as a dependent when this is called, not when it is awaited.
+    pub fn get(&self) -> impl Future<Output = T> + '_ {
+        self.track();
+
+        std::future::poll_fn(move |cx| self.poll_value(cx))
+    }
+
+    /// Returns the cached value if it is up to date, without computing it or tracking the caller.
+    pub fn try_get(&self) -> Option<T> {
+        let idle = self.pending.try_borrow().is_ok_and(|p| p.is_none());
+        if idle && self.tracker.verify() {
+            self.value.borrow().clone()
+        } else {
+            None
+        }
+    }
+
+    fn poll_value(&self, cx: &mut Context<'_>) -> Poll<T> {
+        if let Some(value) = self.try_get() {
+            return Poll::Ready(value);
+        }
+
+        // Panics if the computation awaits this memo.
+        let _computing = memo_stack::push(self.weak.clone());
+        self.poll_pending(cx)
+    }
+
+    fn poll_pending(&self, cx: &mut Context<'_>) -> Poll<T> {
+        let mut slot = self.pending.borrow_mut();
+        let pending = slot.get_or_insert_with(|| {
+            let this: Rc<dyn MemoNode> = self.weak.upgrade().unwrap();
+            Pending {
+                future: (self.f)(),
+                started: self.tracker.begin_computation(&this),
+                wakers: Default::default(),
+            }
+        });
+        pending.wakers.register(cx.waker());
+
+        let waker = Waker::from(pending.wakers.clone());
+        let Poll::Ready(value) = pending
+            .future
+            .as_mut()
+            .poll(&mut Context::from_waker(&waker))
+        else {
+            return Poll::Pending;
+        };
+        let (started, wakers) = (pending.started, pending.wakers.clone());
+        *slot = None;
+        drop(slot);
+
+        let previous = self.value.replace(Some(value.clone()));
+        let changed = previous.is_none_or(|p| p.same_value(&value) != Some(true));
+        self.tracker.end_computation(started, changed);
+        inspect::emit(Event::Recomputed(self.id()));
+
+        // Other callers awaiting the computation pick the value up.
+        wakers.wake_by_ref();
+        Poll::Ready(value)
+    }
+}
+
+impl<T> Drop for AsyncMemo<T> {
+    fn drop(&mut self) {
+        runtime::decrement(&runtime::MEMOS, 1);
+    }
+}
+
+impl<T: 'static> Observable for AsyncMemo<T> {
+    fn tracker(&self) -> &Tracker {
+        &self.tracker
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Memo
+    }
+
+    fn debug_value(&self) -> Option<String> {
+        self.value.try_borrow().ok()?.as_ref()?.debug_string()
+    }
+}
+
+impl<T: 'static> MemoNode for AsyncMemo<T> {}
@@ -0,0 +1,274 @@
+//! Error boundaries catching panics of effects.
+//!
+//! An [`ErrorBoundary`] owns the effects created inside its [`scope`](ErrorBoundary::scope).
+//! When one of them panics, including through a memo it reads, the panic is caught
+//! and reported to the boundary instead of unwinding through the code that changed
+//! a signal. Other effects keep running, and the failing effect runs again on the
+//! next change of its dependencies.
+//!
+//! Memos returning a `Result` can forward their errors to the boundary of the
+//! effects reading them by unwrapping it there with [`Result::expect`].
+//!
+//! Panics of effects outside any boundary unwind through the code that changed a signal,
+//! unless the `panic-handler` feature is enabled and a global handler is set with
+//! `set_panic_handler`.
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    panic::resume_unwind,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    NodeId, Signal, SignalSetter,
+    effect_stack::{effect_peak, effect_suspended},
+};
+
+thread_local! {
+    static BOUNDARY_STACK: RefCell<Vec<Weak<ErrorBoundary>>> = const { RefCell::new(Vec::new()) };
+
+    /// Called with the panics of effects outside any boundary, see [`set_panic_handler`].
+    #[cfg(feature = "panic-handler")]
+    static PANIC_HANDLER: RefCell<Option<Rc<Handler>>> = const { RefCell::new(None) };
+}
+
+/// An error caught by an [`ErrorBoundary`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoundaryError {
+    /// The effect that panicked.
+    pub effect: NodeId,
+    /// The panic message.
+    pub message: String,
+}
+
+type Handler = Box<dyn Fn(&BoundaryError)>;
+
+/// The boundaries and the panic handler of a runtime that is not entered,
+/// see [`Runtime`](crate::Runtime).
+#[derive(Default)]
+pub(crate) struct State {
+    stack: Vec<Weak<ErrorBoundary>>,
+    #[cfg(feature = "panic-handler")]
+    handler: Option<Rc<Handler>>,
+}
+
+/// Exchanges the boundaries and the panic handler with those of the runtime being
+/// entered or left.
+pub(crate) fn swap(state: &mut State) {
+    BOUNDARY_STACK.with_borrow_mut(|stack| std::mem::swap(stack, &mut state.stack));
+    #[cfg(feature = "panic-handler")]
+    PANIC_HANDLER.with_borrow_mut(|handler| std::mem::swap(handler, &mut state.handler));
+}
+
+/// Catches panics of the effects created inside its scope.
+///
+/// # Examples
+///
+/// ```
+/// use reactive_cache::{boundary::ErrorBoundary, prelude::*};
+///
+/// let input = Signal::new("1".to_string());
+/// let parsed = Memo::new({
+///     let input = input.clone();
+///     move || input.get().parse::<i32>()
+/// });
+/// let other = Signal::new(0);
+///
+/// let boundary = ErrorBoundary::new(|e| println!("effect {} failed: {}", e.effect, e.message));
+/// let (effect, unrelated) = boundary.scope(|| {
+///     let effect = Effect::new({
+///         let parsed = parsed.clone();
+///         move || {
+///             let value = parsed.get().expect("invalid number");
+///             println!("value: {value}");
+///         }
+///     });
+///     let unrelated = Effect::new({
+///         let other = other.clone();
+///         move || println!("other: {}", other.get())
+///     });
+///     (effect, unrelated)
+/// });
+///
+/// // The panic is caught instead of unwinding out of `set`.
+/// input.set("one".to_string());
+/// assert!(boundary.error().unwrap().message.contains("invalid number"));
+///
+/// // The rest of the graph is unaffected.
+/// other.set(1);
+///
+/// // Fixing the input re-runs the effect, and resetting clears the error.
+/// input.set("2".to_string());
+/// boundary.reset();
+/// assert!(boundary.error().is_none());
+/// ```
+pub struct ErrorBoundary {
+    handler: Handler,
+    error: Rc<Signal<Option<BoundaryError>>>,
+}
+
+impl ErrorBoundary {
+    /// Creates a boundary calling `handler` with every caught error.
+    ///
+    /// Only a weak reference to the boundary is kept by the effects it owns:
+    /// once it is dropped, their panics are no longer caught.
+    pub fn new(handler: impl Fn(&BoundaryError) + 'static) -> Rc<Self> {
+        Rc::new(ErrorBoundary {
+            handler: Box::new(handler),
+            error: Signal::new(None),
+        })
+    }
+
+    /// Runs `f`, making this boundary own the effects created meanwhile.
+    ///
+    /// Effects created by those effects while they run are owned by this boundary as well,
+    /// unless they are created inside the scope of a nested boundary.
+    pub fn scope<R>(self: &Rc<Self>, f: impl FnOnce() -> R) -> R {
+        BOUNDARY_STACK.with_borrow_mut(|stack| stack.push(Rc::downgrade(self)));
+
+        // The boundary is popped even if `f` panics.
+        struct Pop;
+
+        impl Drop for Pop {
+            fn drop(&mut self) {
+                BOUNDARY_STACK.with_borrow_mut(Vec::pop);
+            }
+        }
+
+        let _pop = Pop;
+        f()
+    }
+
+    /// Returns the most recent error caught since the last [`reset`](Self::reset).
+    ///
+    /// Reading it from a memo or an effect is tracked, so fallback UI can react to failures.
+    pub fn error(&self) -> Option<BoundaryError> {
+        self.error.get().clone()
+    }
+
+    /// Clears the caught error.
+    pub fn reset(&self) {
+        self.error.set(None);
+    }
+}
+
+/// Sets the handler called with the panics of effects that no [`ErrorBoundary`] catches,
+/// replacing the previous one.
+///
+/// Without a handler, such a panic unwinds through the code that changed a signal, and
+/// the effects triggered after the failing one do not run. With a handler, the panic is
+/// caught and the other effects run. Unlike an effect of a boundary, the failing effect
+/// is then disabled: it is [disposed](Effect::dispose), [marked as failed](Effect::has_failed),
+/// and does not run again.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use reactive_cache::{boundary, prelude::*};
+///
+/// let errors = Rc::new(RefCell::new(Vec::new()));
+/// boundary::set_panic_handler({
+///     let errors = errors.clone();
+///     move |e| errors.borrow_mut().push(e.message.clone())
+/// });
+///
+/// let divisor = Signal::new(1);
+/// let quotient = Signal::new(0);
+/// let _divide = Effect::new({
+///     let (divisor, quotient) = (divisor.clone(), quotient.clone());
+///     move || {
+///         quotient.set(100 / *divisor.get());
+///     }
+/// });
+/// # let hook = std::panic::take_hook();
+/// # std::panic::set_hook(Box::new(|_| {}));
+/// divisor.set(0);
+/// # std::panic::set_hook(hook);
+/// assert_eq!(*errors.borrow(), ["attempt to divide by zero"]);
+/// assert!(_divide.has_failed());
+///
+/// divisor.set(4);
+/// assert_eq!(*quotient.get(), 100);
+/// boundary::clear_panic_handler();
+/// ```
+#[cfg(feature = "panic-handler")]
+#[cfg_attr(docsrs, doc(cfg(feature = "panic-handler")))]
+pub fn set_panic_handler(handler: impl Fn(&BoundaryError) + 'static) {
+    PANIC_HANDLER.set(Some(Rc::new(Box::new(handler))));
+}
+
+/// Removes the handler set with [`set_panic_handler`], so that panics of effects outside
+/// any boundary unwind again.
+#[cfg(feature = "panic-handler")]
+#[cfg_attr(docsrs, doc(cfg(feature = "panic-handler")))]
+pub fn clear_panic_handler() {
+    PANIC_HANDLER.set(None);
+}
+
+pub(crate) fn has_panic_handler() -> bool {
+    #[cfg(feature = "panic-handler")]
+    return PANIC_HANDLER.with_borrow(Option::is_some);
+    #[cfg(not(feature = "panic-handler"))]
+    return false;
+}
+
+/// Returns the boundary owning effects created now.
+pub(crate) fn current() -> Option<Weak<ErrorBoundary>> {
+    if let Some(boundary) = BOUNDARY_STACK.with_borrow(|stack| stack.last().cloned()) {
+        return Some(boundary);
+    }
+
+    effect_peak()
+        .and_then(|e| e.effect.upgrade())
+        .and_then(|e| e.boundary.clone())
+}
+
+/// Reports a panic of `effect` to `boundary`, or to the panic handler if there is no
+/// boundary or it was dropped. Resumes the panic if there is neither.
+///
+/// Returns `true` if the boundary caught the panic, `false` if the handler did.
+pub(crate) fn report(
+    boundary: Option<&Weak<ErrorBoundary>>,
+    effect: NodeId,
+    payload: Box<dyn Any + Send>,
+) -> bool {
+    let Some(boundary) = boundary.and_then(Weak::upgrade) else {
+        #[cfg(feature = "panic-handler")]
+        {
+            // The handler may replace itself, so it is called through a clone.
+            if let Some(handler) = PANIC_HANDLER.with_borrow(Clone::clone) {
+                let error = BoundaryError {
+                    effect,
+                    message: message(&*payload),
+                };
+                effect_suspended(|| handler(&error));
+                return false;
+            }
+        }
+        resume_unwind(payload)
+    };
+
+    let error = BoundaryError {
+        effect,
+        message: message(&*payload),
+    };
+
+    // The handler is not a dependency of the effect that failed.
+    effect_suspended(|| {
+        (boundary.handler)(&error);
+        boundary.error.set(Some(error));
+    });
+    true
+}
+
+fn message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
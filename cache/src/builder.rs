@@ -0,0 +1,206 @@
+//! Building graphs of signals and memos from data, without closures capturing their inputs.
+//!
+//! Code generators and visual editors know the nodes of a graph and the edges between
+//! them as data, not as Rust closures. A [`GraphBuilder`] creates signals and memos
+//! holding values of one type, identified by [`NodeRef`] handles: each memo declares the
+//! nodes it reads, and its compute function receives their values as parameters.
+//!
+//! Memos can only read nodes created before them, so the built [`Graph`] has no cycles.
+//! Graphs mixing several value types can use an enum as the value type.
+//!
+//! # Examples
+//!
+//! ```
+//! use reactive_cache::builder::GraphBuilder;
+//!
+//! let mut builder = GraphBuilder::new();
+//! let price = builder.signal(12);
+//! let quantity = builder.signal(3);
+//! let shipping = builder.signal(5);
+//! let subtotal = builder.memo([price, quantity], |inputs| inputs[0] * inputs[1]);
+//! let total = builder.memo([subtotal, shipping], |inputs| inputs[0] + inputs[1]);
+//! let graph = builder.build();
+//!
+//! assert_eq!(graph.get(total), 41);
+//! graph.set(quantity, 5);
+//! assert_eq!(graph.get(total), 65);
+//! ```
+
+use std::rc::Rc;
+
+use crate::{Memo, NodeId, Signal, SignalSetter};
+
+/// A node created by a [`GraphBuilder`], valid for that builder and the graph it builds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeRef(usize);
+
+impl NodeRef {
+    /// Returns the position of this node in creation order, starting at zero.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+enum Node<T> {
+    Signal(Rc<Signal<T>>),
+    Memo(Rc<Memo<T>>),
+}
+
+impl<T: Clone + 'static> Node<T> {
+    fn get(&self) -> T {
+        match self {
+            Node::Signal(s) => s.get().clone(),
+            Node::Memo(m) => m.get(),
+        }
+    }
+}
+
+impl<T> Clone for Node<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Node::Signal(s) => Node::Signal(s.clone()),
+            Node::Memo(m) => Node::Memo(m.clone()),
+        }
+    }
+}
+
+/// Creates the nodes of a [`Graph`]. See the [module docs](self).
+pub struct GraphBuilder<T> {
+    nodes: Vec<Node<T>>,
+}
+
+impl<T: Clone + 'static> GraphBuilder<T> {
+    /// Creates a builder without nodes.
+    pub fn new() -> Self {
+        GraphBuilder { nodes: Vec::new() }
+    }
+
+    /// Creates a signal holding `value`.
+    #[track_caller]
+    pub fn signal(&mut self, value: T) -> NodeRef {
+        self.push(Node::Signal(Signal::new(value)))
+    }
+
+    /// Creates a memo computing its value with `f` from the values of `inputs`,
+    /// passed in the same order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of `inputs` was not created by this builder.
+    #[track_caller]
+    pub fn memo(
+        &mut self,
+        inputs: impl IntoIterator<Item = NodeRef>,
+        f: impl Fn(&[T]) -> T + 'static,
+    ) -> NodeRef {
+        let inputs: Vec<_> = inputs
+            .into_iter()
+            .map(|input| {
+                let Some(node) = self.nodes.get(input.0) else {
+                    panic!("{input:?} was not created by this builder");
+                };
+                node.clone()
+            })
+            .collect();
+
+        let memo = Memo::new(move || {
+            let values: Vec<_> = inputs.iter().map(Node::get).collect();
+            f(&values)
+        });
+        self.push(Node::Memo(memo))
+    }
+
+    /// Returns the graph of the nodes created so far.
+    pub fn build(self) -> Graph<T> {
+        Graph { nodes: self.nodes }
+    }
+
+    fn push(&mut self, node: Node<T>) -> NodeRef {
+        self.nodes.push(node);
+        NodeRef(self.nodes.len() - 1)
+    }
+}
+
+impl<T: Clone + 'static> Default for GraphBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Signals and memos created by a [`GraphBuilder`], accessed through their [`NodeRef`]s.
+///
+/// Methods taking a `NodeRef` panic if it was not created by the builder of this graph.
+pub struct Graph<T> {
+    nodes: Vec<Node<T>>,
+}
+
+impl<T: Clone + 'static> Graph<T> {
+    /// Returns the current value of `node`, recomputing it if it is an outdated memo.
+    pub fn get(&self, node: NodeRef) -> T {
+        self.node(node).get()
+    }
+
+    /// Sets the value of the signal `node`, returning whether dependents were notified.
+    /// See [`SignalSetter::set`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is a memo.
+    pub fn set(&self, node: NodeRef, value: T) -> bool
+    where
+        Signal<T>: SignalSetter<T>,
+    {
+        let Some(signal) = self.signal(node) else {
+            panic!("{node:?} is a memo, only signals can be set");
+        };
+        signal.set(value)
+    }
+
+    /// Returns the id of `node`, as reported by [`inspect`](crate::inspect).
+    pub fn id(&self, node: NodeRef) -> NodeId {
+        match self.node(node) {
+            Node::Signal(s) => s.id(),
+            Node::Memo(m) => m.id(),
+        }
+    }
+
+    /// Returns the signal `node`, or `None` if it is a memo.
+    ///
+    /// Effects can read nodes of the graph through the signals and memos themselves.
+    pub fn signal(&self, node: NodeRef) -> Option<&Rc<Signal<T>>> {
+        match self.node(node) {
+            Node::Signal(s) => Some(s),
+            Node::Memo(_) => None,
+        }
+    }
+
+    /// Returns the memo `node`, or `None` if it is a signal.
+    pub fn memo(&self, node: NodeRef) -> Option<&Rc<Memo<T>>> {
+        match self.node(node) {
+            Node::Memo(m) => Some(m),
+            Node::Signal(_) => None,
+        }
+    }
+
+    /// Returns the nodes of this graph, in creation order.
+    pub fn nodes(&self) -> impl Iterator<Item = NodeRef> + use<T> {
+        (0..self.nodes.len()).map(NodeRef)
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the graph has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn node(&self, node: NodeRef) -> &Node<T> {
+        let Some(n) = self.nodes.get(node.0) else {
+            panic!("{node:?} was not created by the builder of this graph");
+        };
+        n
+    }
+}
@@ -1,43 +1,236 @@
-#![allow(static_mut_refs)]
-
 use lru::LruCache;
-use once_cell::unsync::Lazy;
-use std::{any::Any, num::NonZeroUsize, rc::Rc};
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    CacheSlot, MemoNode,
+    inspect::{self, Event},
+    memo::{Evict, EvictionPolicy},
+};
+
+const DEFAULT_BUDGET: usize = 128;
+
+/// How many of the least recently used entries are considered for eviction.
+const EVICTION_WINDOW: usize = 8;
+
+/// A cached value, held by its [`CacheSlot`].
+struct Entry {
+    /// The node owning the slot, notified when the value is evicted.
+    node: Weak<dyn MemoNode>,
+    /// Keeps the allocation of the slot, so that its address is not reused as another key.
+    slot: Weak<dyn Evict>,
+    /// The weight of the value against the budget, see [`MemoNode::weigh`].
+    weight: usize,
+    /// How long the value took to compute.
+    cost: Duration,
+    /// When the value was stored.
+    stored: Instant,
+    /// How many times the value was read from the cache.
+    reads: u64,
+}
+
+/// The cached values of a runtime. Swapped with the cache of a runtime being entered or
+/// left, see [`Runtime`](crate::Runtime).
+pub(crate) struct State {
+    /// The cached values, by the address of their slot, in order of recency.
+    index: LruCache<*const (), Entry>,
+    budget: usize,
+    /// The total weight of the cached values.
+    weight: usize,
+    /// How many values were evicted since the runtime was created.
+    evictions: usize,
+    policy: EvictionPolicy,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            index: LruCache::unbounded(),
+            budget: DEFAULT_BUDGET,
+            weight: 0,
+            evictions: 0,
+            policy: EvictionPolicy::default(),
+        }
+    }
+}
 
-use crate::IMemo;
+thread_local! {
+    static CACHE: RefCell<State> = RefCell::new(State::default());
+}
 
-const CACHE_CAP: usize = 128;
+/// Exchanges the cache with the one of the runtime being entered or left.
+pub(crate) fn swap(state: &mut State) {
+    CACHE.with_borrow_mut(|cache| std::mem::swap(cache, state))
+}
 
-static mut CACHE: Lazy<LruCache<*const dyn IMemo, Rc<dyn Any>>> =
-    Lazy::new(|| LruCache::new(NonZeroUsize::new(CACHE_CAP).unwrap()));
+pub(crate) fn set_budget(budget: usize) {
+    CACHE.with_borrow_mut(|cache| cache.budget = budget);
+    evict();
+}
 
-pub(crate) fn touch<T>(key: &Rc<dyn IMemo>) -> Option<Rc<T>>
+pub(crate) fn set_policy(policy: EvictionPolicy) {
+    CACHE.with_borrow_mut(|cache| cache.policy = policy);
+    evict();
+}
+
+/// Returns the cached value of `slot`, marking it as used, unless it expired, in which
+/// case it is evicted.
+pub(crate) fn touch<T>(slot: &CacheSlot<T>) -> Option<Rc<T>>
 where
     T: 'static,
 {
-    // When the Effect performs dependency calculations for the first time,
-    // it must ignore the relevant cache,
-    // otherwise the underlying Signal will not remember the Effect.
-    if crate::effect_stack::effect_peak().is_some_and(|e| e.collecting) {
-        remove_from_cache(key);
-        return None;
+    let value = slot.peek()?;
+    let expired = CACHE.with_borrow_mut(|cache| {
+        let policy = cache.policy;
+        let key = slot.key();
+        let entry = cache.index.get_mut(&key)?;
+        entry.reads += 1;
+        if !matches!(policy, EvictionPolicy::Ttl(ttl) if entry.stored.elapsed() >= ttl) {
+            return None;
+        }
+        let entry = pop(cache, &key)?;
+        cache.evictions += 1;
+        Some(entry)
+    });
+    match expired {
+        Some(entry) => {
+            drop(value);
+            release(entry);
+            None
+        }
+        None => Some(value),
     }
-
-    unsafe { CACHE.get(&Rc::as_ptr(key)) }
-        .map(Rc::clone)
-        .filter(|rc| rc.is::<T>())
-        .map(|rc| unsafe { Rc::from_raw(Rc::into_raw(rc) as *const T) })
 }
 
-pub(crate) fn store_in_cache<T>(key: &Rc<dyn IMemo>, val: T) -> Rc<T>
+/// Caches `val` in `slot`, computed by `node` in `cost`. Values of bounded slots are
+/// indexed, then values are evicted until the cache fits its budget.
+pub(crate) fn store_in_cache<T>(
+    node: &Rc<dyn MemoNode>,
+    slot: &CacheSlot<T>,
+    val: T,
+    cost: Duration,
+) -> Rc<T>
 where
     T: 'static,
 {
-    let rc = Rc::new(val);
-    unsafe { CACHE.put(Rc::as_ptr(key), Rc::clone(&rc) as _) };
+    if !slot.is_bounded() {
+        return slot.replace(val);
+    }
+
+    let weight = node.weigh(&val);
+    let rc = slot.replace(val);
+
+    let key = slot.key();
+    CACHE.with_borrow_mut(|cache| {
+        if let Some(entry) = cache.index.get_mut(&key) {
+            cache.weight = cache.weight - entry.weight + weight;
+            (entry.weight, entry.cost, entry.stored) = (weight, cost, Instant::now());
+        } else {
+            let entry = Entry {
+                node: Rc::downgrade(node),
+                slot: slot.downgrade(),
+                weight,
+                cost,
+                stored: Instant::now(),
+                reads: 0,
+            };
+            cache.weight += weight;
+            cache.index.put(key, entry);
+        }
+    });
+
+    evict();
     rc
 }
 
-pub(crate) fn remove_from_cache(key: &Rc<dyn IMemo>) -> bool {
-    unsafe { CACHE.pop(&Rc::as_ptr(key)) }.is_some()
+/// Evicts values until their total weight fits the budget, notifying their nodes.
+/// Pinned values are never evicted. Which value is evicted first depends on the
+/// [policy](EvictionPolicy):
+///
+/// - [`Lru`](EvictionPolicy::Lru) and [`Ttl`](EvictionPolicy::Ttl): among the least
+///   recently used values, the one that took the least time to compute per unit of
+///   weight, expired values first.
+/// - [`Lfu`](EvictionPolicy::Lfu): the value read the least times, the least recently
+///   used one among equals.
+/// - [`NoEviction`](EvictionPolicy::NoEviction): none.
+fn evict() {
+    loop {
+        let entry = CACHE.with_borrow_mut(|cache| {
+            if cache.weight <= cache.budget {
+                return None;
+            }
+            let candidates = cache
+                .index
+                .iter()
+                .rev()
+                .filter(|(_, e)| !e.slot.upgrade().is_some_and(|slot| slot.is_pinned()));
+            let victim = match cache.policy {
+                EvictionPolicy::Lru => candidates
+                    .take(EVICTION_WINDOW)
+                    .min_by_key(|(_, e)| e.cost.as_nanos() / e.weight.max(1) as u128),
+                EvictionPolicy::Ttl(ttl) => candidates
+                    .clone()
+                    .find(|(_, e)| e.stored.elapsed() >= ttl)
+                    .or_else(|| {
+                        candidates
+                            .take(EVICTION_WINDOW)
+                            .min_by_key(|(_, e)| e.cost.as_nanos() / e.weight.max(1) as u128)
+                    }),
+                // `min_by_key` keeps the first minimum, the least recently used one.
+                EvictionPolicy::Lfu => candidates.min_by_key(|(_, e)| e.reads),
+                EvictionPolicy::NoEviction => None,
+            }
+            .map(|(key, _)| *key)?;
+            let entry = pop(cache, &victim)?;
+            cache.evictions += 1;
+            Some(entry)
+        });
+        let Some(entry) = entry else {
+            break;
+        };
+        release(entry);
+    }
+}
+
+/// Empties the slot of an evicted entry and notifies its node.
+fn release(entry: Entry) {
+    // The cache is no longer borrowed, so the value may drop nodes, and the node may
+    // read memos or pin its slot.
+    if let Some(slot) = entry.slot.upgrade() {
+        slot.clear();
+    }
+    if let Some(node) = entry.node.upgrade() {
+        node.on_evict();
+        inspect::emit(Event::Evicted(node.id()));
+    }
+}
+
+/// Removes the entry of `slot` from the index, keeping its value.
+pub(crate) fn forget<T>(slot: &CacheSlot<T>) {
+    // Memos held by thread-locals may be dropped after the cache, as the thread exits.
+    let _ = CACHE.try_with(|cache| {
+        pop(&mut cache.borrow_mut(), &slot.key());
+    });
+}
+
+/// Removes the entry at `key` from the index. Its slot is emptied by the caller.
+fn pop(cache: &mut State, key: &*const ()) -> Option<Entry> {
+    let entry = cache.index.pop(key)?;
+    cache.weight -= entry.weight;
+    Some(entry)
+}
+
+pub(crate) fn len() -> usize {
+    CACHE.with_borrow(|cache| cache.index.len())
+}
+
+pub(crate) fn weight() -> usize {
+    CACHE.with_borrow(|cache| cache.weight)
+}
+
+pub(crate) fn evictions() -> usize {
+    CACHE.with_borrow(|cache| cache.evictions)
 }
@@ -0,0 +1,264 @@
+use std::{cell::RefCell, collections::HashMap, hash::Hash, rc::Rc};
+
+use crate::{Effect, EffectOptions, Memo, Observable, Signal};
+
+/// Flattens a memo that selects a signal into a memo of the selected signal's value.
+///
+/// The returned memo tracks both the outer selection and the currently selected
+/// inner signal. When the selection changes, the subscription to the previously
+/// selected signal is dropped and the newly selected one is followed instead,
+/// so effects reading the returned memo always react to the right signal.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{combinators::flatten, prelude::*};
+///
+/// let items = [Signal::new(1), Signal::new(2)];
+/// let selected = Signal::new(0);
+///
+/// let current = flatten(Memo::new({
+///     let (items, selected) = (items.clone(), selected.clone());
+///     move || items[*selected.get()].clone()
+/// }));
+///
+/// let seen = Rc::new(Cell::new(0));
+/// let effect = Effect::new({
+///     let (current, seen) = (current.clone(), seen.clone());
+///     move || seen.set(current.get())
+/// });
+///
+/// assert_eq!(seen.get(), 1);
+///
+/// selected.set(1);
+/// assert_eq!(seen.get(), 2);
+///
+/// // Only the currently selected signal is followed.
+/// items[1].set(20);
+/// assert_eq!(seen.get(), 20);
+/// items[0].set(10);
+/// assert_eq!(seen.get(), 20);
+/// ```
+pub fn flatten<T>(outer: Rc<Memo<Rc<Signal<T>>>>) -> Rc<Memo<T>>
+where
+    T: Clone + 'static,
+{
+    follow(move || {
+        let source = outer.get();
+        move || source.get().clone()
+    })
+}
+
+/// Flattens a signal holding a signal into a memo of the inner signal's value.
+///
+/// This is the `Signal`-of-`Signal` counterpart of [`flatten`].
+///
+/// # Examples
+///
+/// ```
+/// use reactive_cache::{combinators::flatten_signal, prelude::*};
+///
+/// let a = Signal::new("a");
+/// let b = Signal::new("b");
+/// let selected = Signal::new(a.clone());
+///
+/// let current = flatten_signal(selected.clone());
+/// assert_eq!(current.get(), "a");
+///
+/// selected.set_always(b.clone());
+/// assert_eq!(current.get(), "b");
+///
+/// b.set("B");
+/// assert_eq!(current.get(), "B");
+/// ```
+pub fn flatten_signal<T>(outer: Rc<Signal<Rc<Signal<T>>>>) -> Rc<Memo<T>>
+where
+    T: Clone + 'static,
+{
+    follow(move || {
+        let source = outer.get().clone();
+        move || source.get().clone()
+    })
+}
+
+/// Forwards the value of the arm chosen by `selector`.
+///
+/// Only the currently active arm is subscribed to: changes in inactive arms
+/// neither recompute the returned memo nor re-run effects reading it.
+/// When `selector` changes, the subscription switches to the newly chosen arm.
+///
+/// # Panics
+///
+/// Panics if `arms` has no entry for the current value of `selector`.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, collections::HashMap, rc::Rc};
+/// use reactive_cache::{combinators::select, prelude::*};
+///
+/// #[derive(PartialEq, Eq, Hash)]
+/// enum Tab {
+///     Inbox,
+///     Sent,
+/// }
+///
+/// let inbox = Signal::new(3);
+/// let sent = Signal::new(5);
+/// let tab = Signal::new(Tab::Inbox);
+///
+/// let count = select(
+///     tab.clone(),
+///     HashMap::from([
+///         (Tab::Inbox, Memo::new({
+///             let inbox = inbox.clone();
+///             move || *inbox.get()
+///         })),
+///         (Tab::Sent, Memo::new({
+///             let sent = sent.clone();
+///             move || *sent.get()
+///         })),
+///     ]),
+/// );
+///
+/// let runs = Rc::new(Cell::new(0));
+/// let effect = Effect::new({
+///     let (count, runs) = (count.clone(), runs.clone());
+///     move || {
+///         let _ = count.get();
+///         runs.set(runs.get() + 1);
+///     }
+/// });
+///
+/// // The inactive arm is not subscribed to.
+/// sent.set(6);
+/// assert_eq!(runs.get(), 1);
+///
+/// tab.set(Tab::Sent);
+/// assert_eq!(count.get(), 6);
+/// assert_eq!(runs.get(), 2);
+///
+/// inbox.set(4);
+/// assert_eq!(runs.get(), 2);
+/// ```
+pub fn select<K, T>(selector: Rc<Signal<K>>, arms: HashMap<K, Rc<Memo<T>>>) -> Rc<Memo<T>>
+where
+    K: Eq + Hash + 'static,
+    T: Clone + 'static,
+{
+    follow(move || {
+        let arm = arms
+            .get(&*selector.get())
+            .cloned()
+            .expect("`select` requires an arm for every selector value.");
+        move || arm.get()
+    })
+}
+
+/// Calls `callback` with the value computed by `source` and the previous one, now and
+/// whenever it changes.
+///
+/// Only the signals and memos read by `source` are tracked, not those read by
+/// `callback`. The first call passes no previous value. A recomputed value equal to the
+/// previous one, compared with `PartialEq`, does not call `callback`. Dropping the
+/// returned effect stops watching.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use reactive_cache::{combinators::watch, prelude::*};
+///
+/// let temperature = Signal::new(18);
+/// let log = Rc::new(RefCell::new(Vec::new()));
+/// let _watcher = watch(
+///     {
+///         let temperature = temperature.clone();
+///         move || *temperature.get() >= 20
+///     },
+///     {
+///         let log = log.clone();
+///         move |warm: &bool, was_warm: Option<&bool>| {
+///             log.borrow_mut().push((*warm, was_warm.copied()))
+///         }
+///     },
+/// );
+///
+/// temperature.set(19);
+/// temperature.set(22);
+/// assert_eq!(*log.borrow(), [(false, None), (true, Some(false))]);
+/// ```
+#[track_caller]
+pub fn watch<T: PartialEq + 'static>(
+    source: impl Fn() -> T + 'static,
+    callback: impl Fn(&T, Option<&T>) + 'static,
+) -> Rc<Effect> {
+    let source = Memo::new_with_eq(source, T::eq);
+    let previous: RefCell<Option<Rc<T>>> = RefCell::new(None);
+    Effect::new(move || {
+        source.track();
+        let value = source.value();
+        let previous = previous.replace(Some(value.clone()));
+        crate::effect_stack::effect_suspended(|| callback(&value, previous.as_deref()));
+    })
+}
+
+/// Mirrors the value read by the source returned from `select` into a memo,
+/// re-subscribing whenever `select` picks a different source.
+fn follow<T, F, G>(select: F) -> Rc<Memo<T>>
+where
+    T: Clone + 'static,
+    F: Fn() -> G + 'static,
+    G: Fn() -> T + 'static,
+{
+    let output = Signal::new(None);
+
+    // The inner effect is owned by the current run of the outer one,
+    // so a new selection drops the subscription to the previous source.
+    let outer = Effect::new({
+        let output = output.clone();
+        move || {
+            let source = select();
+            let output = output.clone();
+            let _ = Effect::new(move || {
+                output.set_value(Some(source()));
+            });
+        }
+    });
+
+    Memo::new(move || {
+        // The memo owns the effect that keeps `output` up to date.
+        let _ = &outer;
+
+        output
+            .get()
+            .clone()
+            .expect("The inner effect runs on creation and always sets a value.")
+    })
+}
+
+/// Returns a memo following `source` through an effect scheduled with `options`.
+pub(crate) fn rate_limited<T: Clone + 'static>(
+    source: Rc<Signal<T>>,
+    options: EffectOptions,
+) -> Rc<Memo<T>> {
+    let output = Signal::new(source.clone_detached());
+
+    let effect = Effect::new_with_options(
+        {
+            let output = output.clone();
+            move || {
+                output.set_value(source.get().clone());
+            }
+        },
+        options,
+    );
+
+    Memo::new(move || {
+        // The memo owns the effect that keeps `output` up to date.
+        let _ = &effect;
+
+        output.get().clone()
+    })
+}
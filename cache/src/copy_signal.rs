@@ -0,0 +1,184 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::{NodeId, Observable, SignalSetter, Tracker, inspect::DebugValue, runtime};
+
+/// A reactive signal for `Copy` values, stored in a `Cell`.
+///
+/// `CopySignal<T>` tracks dependencies and triggers effects exactly like a
+/// [`Signal`](crate::Signal), but [`get`](CopySignal::get) returns the value by copy
+/// instead of a `Ref`. Nothing is ever borrowed, so reading and writing it can never
+/// panic with a borrow conflict, and no borrow flag is checked on each access.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{CopySignal, prelude::*};
+///
+/// let count = CopySignal::new(1);
+/// let double = Memo::new({
+///     let count = count.clone();
+///     move || count.get() * 2
+/// });
+///
+/// let seen = Rc::new(Cell::new(0));
+/// let effect = Effect::new({
+///     let (count, seen) = (count.clone(), seen.clone());
+///     move || seen.set(count.get())
+/// });
+///
+/// // The value can be read while it is being set.
+/// count.set(count.get() + 1);
+/// assert_eq!(double.get(), 4);
+/// assert_eq!(seen.get(), 2);
+/// ```
+pub struct CopySignal<T: Copy> {
+    value: Cell<T>,
+    tracker: Tracker,
+}
+
+impl<T: Copy> CopySignal<T> {
+    /// Creates a new `CopySignal` with the given initial value.
+    ///
+    /// Like [`Signal::new`](crate::Signal::new), this requires `T: 'static`, so that a
+    /// [`Scope`](crate::Scope) can own the signal.
+    #[track_caller]
+    pub fn new(value: T) -> Rc<Self>
+    where
+        T: 'static,
+    {
+        runtime::increment(&runtime::SIGNALS);
+        crate::scope::owned(Rc::new(CopySignal {
+            value: Cell::new(value),
+            tracker: Tracker::new(),
+        }))
+    }
+
+    /// Returns the unique id of this signal.
+    pub fn id(&self) -> NodeId {
+        self.tracker.id()
+    }
+
+    /// Returns the current value, tracking dependencies and effects if inside a reactive context.
+    pub fn get(&self) -> T {
+        self.track();
+
+        self.value.get()
+    }
+
+    /// Returns the current value without tracking.
+    pub fn get_untracked(&self) -> T {
+        self.value.get()
+    }
+
+    /// Sets the value without comparing it with the current one, so that dependent
+    /// memos are always invalidated and dependent effects triggered.
+    pub fn set_always(&self, value: T) {
+        self.replace(value);
+    }
+
+    fn replace(&self, value: T) {
+        self.notify_changing();
+
+        if runtime::recording() {
+            runtime::record_change(
+                &self.tracker,
+                self.value.get().debug_string(),
+                value.debug_string(),
+            );
+        }
+        self.value.set(value);
+
+        self.notify_changed();
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T: Copy> SignalSetter<T> for CopySignal<T> {
+    specializable! {
+        /// Sets the value of the signal.
+        ///
+        /// Values that cannot be compared are treated as always changing,
+        /// so `true` is always returned.
+        fn set(&self, value: T) -> bool {
+            self.replace(value);
+
+            true
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T: Copy + Eq> SignalSetter<T> for CopySignal<T> {
+    /// Sets the value of the signal.
+    ///
+    /// Returns `true` if the value changed, all dependent memos are
+    /// invalidated and dependent effects were triggered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{CopySignal, prelude::*};
+    ///
+    /// let signal = CopySignal::new(5);
+    /// assert!(signal.set(10));
+    /// assert!(!signal.set(10));
+    /// assert_eq!(signal.get(), 10);
+    /// ```
+    fn set(&self, value: T) -> bool {
+        if self.value.get() == value {
+            return false;
+        }
+
+        self.replace(value);
+
+        true
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T: Copy + PartialEq> SignalSetter<T> for CopySignal<T> {
+    /// Sets the value of the signal.
+    ///
+    /// Returns `true` if the value changed, all dependent memos are
+    /// invalidated and dependent effects were triggered.
+    ///
+    /// Without the `nightly` feature, only values implementing `PartialEq` can be set
+    /// this way; other values are set with [`CopySignal::set_always`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{CopySignal, prelude::*};
+    ///
+    /// let signal = CopySignal::new(5);
+    /// assert!(signal.set(10));
+    /// assert!(!signal.set(10));
+    /// assert_eq!(signal.get(), 10);
+    /// ```
+    fn set(&self, value: T) -> bool {
+        if self.value.get() == value {
+            return false;
+        }
+
+        self.replace(value);
+
+        true
+    }
+}
+
+impl<T: Copy> Drop for CopySignal<T> {
+    fn drop(&mut self) {
+        runtime::decrement(&runtime::SIGNALS, 1);
+    }
+}
+
+impl<T: Copy> Observable for CopySignal<T> {
+    fn tracker(&self) -> &Tracker {
+        &self.tracker
+    }
+
+    fn debug_value(&self) -> Option<String> {
+        self.value.get().debug_string()
+    }
+}
@@ -0,0 +1,246 @@
+//! A WebSocket server streaming the reactive graph to external inspectors.
+//!
+//! Enabled with the `devtools` feature. A [`DevtoolsServer`] accepts WebSocket
+//! connections on a background thread. Since reactive nodes are confined to the
+//! thread that created them, nothing is sent until the owning thread calls
+//! [`DevtoolsServer::flush`], typically once per frame or event-loop iteration.
+//!
+//! The server reports the nodes registered with [`inspect::register`] and every
+//! memo and effect reachable from them. Name the nodes you want to see.
+//!
+//! # Protocol
+//!
+//! The server only sends messages; anything received from a client is ignored.
+//! Every message is a WebSocket text frame holding one JSON object with a `type` field.
+//!
+//! An `event` message is sent for every [`Event`] since the previous flush, in order:
+//!
+//! ```json
+//! {"type": "event", "event": "changed", "node": 3}
+//! ```
+//!
+//! `event` is one of `"changed"` (a signal changed), `"invalidated"` (a memo's cached
+//! value was dropped), `"recomputed"` (a memo computed a new value), `"evicted"` (a memo's
+//! cached value was evicted from the runtime cache) or `"effect_run"`.
+//! `node` is the id of the node concerned.
+//!
+//! A `graph` message follows the events whenever something happened since the previous
+//! flush, and is sent to every newly connected client on its first flush:
+//!
+//! ```json
+//! {
+//!   "type": "graph",
+//!   "nodes": [
+//!     {"id": 3, "kind": "signal", "name": "hp", "location": "src/main.rs:4:14",
+//!      "value": "100", "dependents": [4]},
+//!     {"id": 4, "kind": "memo", "name": null, "location": "src/main.rs:5:15",
+//!      "value": "false", "dependents": []}
+//!   ]
+//! }
+//! ```
+//!
+//! `kind` is `"signal"`, `"memo"` or `"effect"`. `location` is where the node was created,
+//! as `file:line:column`. `value` is the `Debug` rendering of the
+//! current value, or `null` if it is unavailable (see [`NodeInfo::value`](inspect::NodeInfo::value)).
+//! `dependents` lists the ids of the memos and effects notified when the node changes,
+//! so the edges of the graph point from sources to their dependents.
+//!
+//! # Examples
+//!
+//! ```
+//! use reactive_cache::{devtools::DevtoolsServer, inspect, prelude::*};
+//!
+//! let hp = Signal::new(100);
+//! inspect::register("hp", &hp);
+//!
+//! let server = DevtoolsServer::bind("127.0.0.1:0").unwrap();
+//! let (mut client, _) =
+//!     tungstenite::connect(format!("ws://{}", server.local_addr())).unwrap();
+//! while server.client_count() == 0 {
+//!     std::thread::yield_now();
+//! }
+//!
+//! hp.set(50);
+//! server.flush();
+//!
+//! let event = client.read().unwrap().into_text().unwrap();
+//! assert!(event.contains(r#""event":"changed""#));
+//! let graph = client.read().unwrap().into_text().unwrap();
+//! assert!(graph.contains(r#""name":"hp","value":"50""#));
+//! ```
+
+use std::{
+    cell::RefCell,
+    io,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    rc::Rc,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+use serde_json::{Value, json};
+use tungstenite::{Message, WebSocket};
+
+use crate::inspect::{self, Event, HookId, NodeInfo, NodeKind};
+
+struct Client {
+    socket: WebSocket<TcpStream>,
+    /// Whether the client has not received a graph yet.
+    fresh: bool,
+}
+
+/// A WebSocket server streaming the reactive graph. See the [module docs](self).
+///
+/// Dropping the server closes all connections and stops the background thread.
+pub struct DevtoolsServer {
+    addr: SocketAddr,
+    clients: Arc<Mutex<Vec<Client>>>,
+    shutdown: Arc<AtomicBool>,
+    events: Rc<RefCell<Vec<Event>>>,
+    hook: HookId,
+}
+
+impl DevtoolsServer {
+    /// Starts listening on `addr` and accepting clients on a background thread.
+    ///
+    /// Events are recorded from this point on and sent on the next [`flush`](Self::flush).
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        thread::spawn({
+            let (clients, shutdown) = (clients.clone(), shutdown.clone());
+            move || accept(listener, clients, shutdown)
+        });
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let hook = inspect::add_hook({
+            let events = events.clone();
+            move |e: &Event| events.borrow_mut().push(e.clone())
+        });
+
+        Ok(DevtoolsServer {
+            addr,
+            clients,
+            shutdown,
+            events,
+            hook,
+        })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the number of connected clients.
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// Sends the events recorded since the previous flush to every client,
+    /// followed by the current graph if anything changed or a client is new.
+    ///
+    /// Clients whose connection failed are dropped.
+    pub fn flush(&self) {
+        let events = std::mem::take(&mut *self.events.borrow_mut());
+        let mut clients = self.clients.lock().unwrap();
+        if clients.is_empty() {
+            return;
+        }
+
+        let events: Vec<String> = events
+            .iter()
+            .map(|e| event_message(e).to_string())
+            .collect();
+        let graph = (!events.is_empty() || clients.iter().any(|c| c.fresh))
+            .then(|| graph_message(&inspect::graph()).to_string());
+
+        clients.retain_mut(|client| {
+            let sent_graph = graph
+                .as_ref()
+                .filter(|_| !events.is_empty() || client.fresh);
+            client.fresh = false;
+
+            events
+                .iter()
+                .chain(sent_graph)
+                .try_for_each(|m| client.socket.send(Message::text(m.as_str())))
+                .is_ok()
+        });
+    }
+}
+
+impl Drop for DevtoolsServer {
+    fn drop(&mut self) {
+        inspect::remove_hook(self.hook);
+
+        // Wake the accept loop up so that it notices the shutdown.
+        self.shutdown.store(true, Ordering::Relaxed);
+        let _ = TcpStream::connect(self.addr);
+
+        for mut client in self.clients.lock().unwrap().drain(..) {
+            let _ = client.socket.close(None);
+            let _ = client.socket.flush();
+        }
+    }
+}
+
+fn accept(listener: TcpListener, clients: Arc<Mutex<Vec<Client>>>, shutdown: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if shutdown.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(stream) = stream else { continue };
+
+        // Handshake on a separate thread, so a stalled client cannot block others.
+        let (clients, shutdown) = (clients.clone(), shutdown.clone());
+        thread::spawn(move || {
+            if let Ok(socket) = tungstenite::accept(stream)
+                && !shutdown.load(Ordering::Relaxed)
+            {
+                clients.lock().unwrap().push(Client {
+                    socket,
+                    fresh: true,
+                });
+            }
+        });
+    }
+}
+
+fn event_message(event: &Event) -> Value {
+    let (name, node) = match event {
+        Event::Changed(id) => ("changed", id),
+        Event::Invalidated(id) => ("invalidated", id),
+        Event::Recomputed(id) => ("recomputed", id),
+        Event::Evicted(id) => ("evicted", id),
+        Event::EffectRun(id) => ("effect_run", id),
+    };
+    json!({ "type": "event", "event": name, "node": node.get() })
+}
+
+fn graph_message(nodes: &[NodeInfo]) -> Value {
+    let nodes: Vec<Value> = nodes
+        .iter()
+        .map(|n| {
+            json!({
+                "id": n.id.get(),
+                "kind": match n.kind {
+                    NodeKind::Signal => "signal",
+                    NodeKind::Memo => "memo",
+                    NodeKind::Effect => "effect",
+                },
+                "name": n.name,
+                "location": n.location.to_string(),
+                "value": n.value,
+                "dependents": n.dependents.iter().map(|d| d.get()).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    json!({ "type": "graph", "nodes": nodes })
+}
@@ -0,0 +1,159 @@
+//! Memos reporting what changed instead of the whole new value.
+//!
+//! A [`DiffMemo`] computes a value like a [`Memo`], and compares it with the value each
+//! consumer saw last. Renderers and network sync layers read patches through their own
+//! [`DiffReader`], so they apply deltas instead of rebuilding from full snapshots.
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{Memo, NodeId};
+
+/// An operation turning one list into another, produced by [`list_diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListOp<T> {
+    /// A value was inserted at an index.
+    Insert(usize, T),
+    /// The value at an index was removed.
+    Remove(usize),
+    /// The value at an index was replaced.
+    Update(usize, T),
+}
+
+/// Returns the operations turning `old` into `new`, to be applied in order.
+///
+/// Items shared at the start and at the end of both lists are skipped; the items
+/// in between are updated in place, then the remaining ones removed or inserted.
+///
+/// # Examples
+///
+/// ```
+/// use reactive_cache::diff_memo::{ListOp, list_diff};
+///
+/// assert_eq!(
+///     list_diff(&[1, 2, 3, 4], &[1, 5, 3, 4, 7]),
+///     [ListOp::Update(1, 5), ListOp::Insert(4, 7)],
+/// );
+/// assert_eq!(list_diff(&[1, 2, 3], &[1, 3]), [ListOp::Remove(1)]);
+/// ```
+pub fn list_diff<T: PartialEq + Clone>(old: &[T], new: &[T]) -> Vec<ListOp<T>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+
+    let common = old.len().min(new.len());
+    let mut ops: Vec<_> = (0..common)
+        .filter(|&i| old[i] != new[i])
+        .map(|i| ListOp::Update(prefix + i, new[i].clone()))
+        .collect();
+    // Removing from the same index shifts the next extra item into it.
+    ops.extend((common..old.len()).map(|_| ListOp::Remove(prefix + common)));
+    ops.extend((common..new.len()).map(|i| ListOp::Insert(prefix + i, new[i].clone())));
+    ops
+}
+
+type DiffFn<T, P> = Rc<dyn Fn(&T, &T) -> P>;
+
+/// A memo whose consumers receive patches describing what changed since they last read it.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use reactive_cache::{diff_memo::{DiffMemo, ListOp}, prelude::*};
+///
+/// let todos = Signal::new(vec!["write docs", "review"]);
+/// let visible = DiffMemo::list({
+///     let todos = todos.clone();
+///     move || todos.get().iter().filter(|t| !t.starts_with('#')).copied().collect()
+/// });
+///
+/// let reader = visible.reader();
+/// let patches = Rc::new(RefCell::new(vec![]));
+/// let effect = Effect::new({
+///     let patches = patches.clone();
+///     move || {
+///         if let Some(patch) = reader.patch() {
+///             patches.borrow_mut().push(patch);
+///         }
+///     }
+/// });
+///
+/// todos.set(vec!["write docs", "#hidden", "release"]);
+/// assert_eq!(*patches.borrow(), [vec![ListOp::Update(1, "release")]]);
+/// ```
+pub struct DiffMemo<T, P> {
+    memo: Rc<Memo<Rc<T>>>,
+    diff: DiffFn<T, P>,
+}
+
+impl<T: 'static, P: 'static> DiffMemo<T, P> {
+    /// Creates a memo computing its value with `f`, and patches with `diff(old, new)`.
+    #[track_caller]
+    pub fn new(f: impl Fn() -> T + 'static, diff: impl Fn(&T, &T) -> P + 'static) -> Rc<Self> {
+        crate::scope::owned(Rc::new(DiffMemo {
+            memo: Memo::new(move || Rc::new(f())),
+            diff: Rc::new(diff),
+        }))
+    }
+
+    /// Returns the unique id of the underlying memo.
+    pub fn id(&self) -> NodeId {
+        self.memo.id()
+    }
+
+    /// Returns the current value, tracking dependencies and effects if inside a reactive context.
+    pub fn get(&self) -> Rc<T> {
+        self.memo.get()
+    }
+
+    /// Returns a reader whose first patch describes the changes made after this call.
+    ///
+    /// Each consumer should own its reader, so that it receives every change exactly once.
+    pub fn reader(&self) -> DiffReader<T, P> {
+        DiffReader {
+            memo: self.memo.clone(),
+            diff: self.diff.clone(),
+            last: RefCell::new(self.memo.snapshot_value()),
+        }
+    }
+}
+
+impl<T: PartialEq + Clone + 'static> DiffMemo<Vec<T>, Vec<ListOp<T>>> {
+    /// Creates a memo computing a list with `f`, with patches produced by [`list_diff`].
+    #[track_caller]
+    pub fn list(f: impl Fn() -> Vec<T> + 'static) -> Rc<Self> {
+        Self::new(f, |old, new| list_diff(old, new))
+    }
+}
+
+/// A consumer of a [`DiffMemo`], remembering the value it saw last.
+pub struct DiffReader<T, P> {
+    memo: Rc<Memo<Rc<T>>>,
+    diff: DiffFn<T, P>,
+    last: RefCell<Rc<T>>,
+}
+
+impl<T: PartialEq + 'static, P> DiffReader<T, P> {
+    /// Returns the patch from the value seen last to the current one,
+    /// or `None` if the value is unchanged.
+    ///
+    /// Reading is tracked, so an effect calling it re-runs whenever a new patch is available.
+    pub fn patch(&self) -> Option<P> {
+        let current = self.memo.get();
+        let last = self.last.replace(current.clone());
+        (!Rc::ptr_eq(&last, &current) && last != current).then(|| (self.diff)(&last, &current))
+    }
+}
+
+impl<T, P> DiffReader<T, P> {
+    /// Returns the value seen last, without reading the memo.
+    pub fn last(&self) -> Rc<T> {
+        self.last.borrow().clone()
+    }
+}
@@ -1,6 +1,19 @@
-use std::rc::Rc;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    mem::ManuallyDrop,
+    panic::{AssertUnwindSafe, Location, catch_unwind},
+    rc::{Rc, Weak},
+    time::{Duration, Instant},
+};
 
-use crate::effect_stack::{effect_peak, effect_pop, effect_push};
+use crate::{
+    NodeId,
+    boundary::ErrorBoundary,
+    effect_stack::{effect_depth, effect_peak, effect_pop, effect_push, effect_truncate},
+    observable::DropHook,
+    revision::{self, Revision, Revisions},
+};
 
 /// A reactive effect that runs a closure whenever its dependencies change.
 ///
@@ -75,13 +88,351 @@ use crate::effect_stack::{effect_peak, effect_pop, effect_push};
 /// assert_eq!(run_count.get(), 2);
 /// ```
 pub struct Effect {
-    f: Box<dyn Fn()>,
+    id: NodeId,
+
+    /// Where the effect was created.
+    location: &'static Location<'static>,
+    f: Box<dyn Fn() -> Option<CleanupFn>>,
+
+    /// Returned by the last run or registered during it with
+    /// [`on_cleanup`](crate::on_cleanup), called before the next one. See [`Cleanup`].
+    cleanups: RefCell<Vec<CleanupFn>>,
+
+    /// The signals this effect depends on, with the memos the dependency was reached through.
+    sources: RefCell<Vec<Trigger>>,
+
+    /// The nodes this effect is registered with, so that disposing it unregisters it.
+    subscriptions: RefCell<Vec<Weak<Revisions>>>,
+
+    /// Whether the effect was disposed, see [`Effect::dispose`].
+    disposed: Cell<bool>,
+
+    /// Whether the effect was disabled by a panic, see [`Effect::has_failed`].
+    #[cfg(feature = "panic-handler")]
+    failed: Cell<bool>,
+
+    /// The signal whose change caused the most recent triggered run.
+    last_trigger: Cell<Option<NodeId>>,
+
+    /// The signals and memos read by the effect itself, rather than by the memos it read.
+    reads: RefCell<Vec<Weak<Revisions>>>,
+
+    /// The revision the effect last ran in.
+    ran_at: Cell<Revision>,
+
+    /// Effects created while this effect was running.
+    /// They are owned by that run and dropped before the next one.
+    children: RefCell<Vec<Rc<Effect>>>,
+
+    /// How triggered runs are scheduled.
+    pub(crate) options: EffectOptions,
+
+    /// When the effect last ran, for throttled scheduling.
+    pub(crate) last_run: Cell<Option<Instant>>,
+
+    /// The boundary catching panics of this effect.
+    pub(crate) boundary: Option<Weak<ErrorBoundary>>,
+
+    /// Callbacks run when the effect is dropped.
+    on_drop: RefCell<Vec<DropHook>>,
+}
+
+thread_local! {
+    /// The effects kept alive by [`Effect::keep_alive`], by id.
+    ///
+    /// Never dropped, like a `static`: the effects would run their cleanups while the
+    /// other thread-locals of the runtime are being destroyed.
+    static KEPT_ALIVE: ManuallyDrop<RefCell<HashMap<NodeId, Rc<Effect>>>> =
+        ManuallyDrop::new(RefCell::new(HashMap::new()));
+}
+
+/// The effects kept alive in a runtime that is not entered, see [`Runtime`](crate::Runtime).
+#[derive(Default)]
+pub(crate) struct State(HashMap<NodeId, Rc<Effect>>);
+
+/// Exchanges the effects kept alive with those of the runtime being entered or left.
+pub(crate) fn swap(state: &mut State) {
+    KEPT_ALIVE.with(|kept| std::mem::swap(&mut *kept.borrow_mut(), &mut state.0))
+}
+
+/// A handle to an effect kept alive by [`Effect::keep_alive`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EffectHandle {
+    id: NodeId,
+}
+
+impl EffectHandle {
+    /// Returns the unique id of the effect.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Returns whether the effect is still kept alive.
+    pub fn is_alive(&self) -> bool {
+        KEPT_ALIVE.with(|kept| kept.borrow().contains_key(&self.id))
+    }
+
+    /// [Disposes](Effect::dispose) the effect and releases it.
+    ///
+    /// Returns whether it was still kept alive.
+    pub fn dispose(self) -> bool {
+        let Some(effect) = KEPT_ALIVE.with(|kept| kept.borrow_mut().remove(&self.id)) else {
+            return false;
+        };
+        effect.dispose();
+        true
+    }
+}
+
+/// Scheduling options of an [`Effect`].
+///
+/// The options only affect runs triggered by a change:
+/// the first run, at creation, always happens immediately.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{effect::EffectOptions, prelude::*};
+///
+/// let width = Signal::new(100);
+/// let order = Rc::new(std::cell::RefCell::new(Vec::new()));
+///
+/// let paint = Effect::new_with_options(
+///     {
+///         let (width, order) = (width.clone(), order.clone());
+///         move || order.borrow_mut().push(("paint", *width.get()))
+///     },
+///     EffectOptions::new().deferred(),
+/// );
+/// let layout = Effect::new_with_options(
+///     {
+///         let (width, order) = (width.clone(), order.clone());
+///         move || order.borrow_mut().push(("layout", *width.get()))
+///     },
+///     EffectOptions::new().deferred().priority(1),
+/// );
+/// order.borrow_mut().clear();
+///
+/// // Deferred effects wait for `flush()`, so both writes are seen at once.
+/// width.set(200);
+/// width.set(300);
+/// assert!(order.borrow().is_empty());
+///
+/// assert_eq!(reactive_cache::flush(), 2);
+/// assert_eq!(*order.borrow(), [("layout", 300), ("paint", 300)]);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EffectOptions {
+    pub(crate) deferred: bool,
+    pub(crate) priority: i32,
+    pub(crate) debounce: Option<Duration>,
+    pub(crate) throttle: Option<Duration>,
+    pub(crate) dynamic: bool,
+    pub(crate) once: bool,
+}
+
+impl EffectOptions {
+    /// Returns the default options: immediate runs with priority 0.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues triggered runs until the next [`flush`](crate::flush),
+    /// instead of running them inside the `set()` that caused them.
+    ///
+    /// A queued effect runs once per flush, however often it was triggered.
+    pub fn deferred(mut self) -> Self {
+        self.deferred = true;
+        self
+    }
+
+    /// Sets the priority of the effect. The default is 0.
+    ///
+    /// Effects triggered by the same change, or flushed together,
+    /// run in descending priority; equal priorities keep subscription order.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Defers triggered runs until `delay` has passed without another trigger.
+    ///
+    /// This implies [`EffectOptions::deferred`]: the run happens during the first
+    /// [`flush`](crate::flush) after the delay has elapsed.
+    pub fn debounce(mut self, delay: Duration) -> Self {
+        self.deferred = true;
+        self.debounce = Some(delay);
+        self
+    }
+
+    /// Defers triggered runs so that they are at least `interval` apart.
+    ///
+    /// This implies [`EffectOptions::deferred`]: the run happens during the first
+    /// [`flush`](crate::flush) once `interval` has elapsed since the previous run.
+    /// Unlike [`EffectOptions::debounce`], further triggers do not postpone it.
+    pub fn throttle(mut self, interval: Duration) -> Self {
+        self.deferred = true;
+        self.throttle = Some(interval);
+        self
+    }
+
+    /// Collects the dependencies of the effect again on every triggered run, instead of
+    /// only at creation. See [`Effect::new_dynamic`].
+    ///
+    /// Effects created with [`Effect::new_with_deps`] keep the dependencies of their
+    /// initializer.
+    pub fn dynamic(mut self) -> Self {
+        self.dynamic = true;
+        self
+    }
+
+    /// [Disposes](Effect::dispose) the effect after its first triggered run.
+    /// See [`Effect::once`].
+    pub fn once(mut self) -> Self {
+        self.once = true;
+        self
+    }
+}
+
+/// What an effect closure returns: nothing, or a cleanup closure.
+///
+/// A cleanup returned by a run of an [`Effect`] is called before the next run, and when
+/// the effect is [disposed](Effect::dispose) or dropped, to tear down what the run set up,
+/// such as timers, tasks or callbacks. Cleanups do not track the signals they read.
+///
+/// It is implemented for `()`, for closures, and for `Option`s of closures, so that a
+/// run may set up nothing.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use reactive_cache::prelude::*;
+///
+/// let room = Signal::new("lobby");
+/// let joined = Rc::new(RefCell::new(Vec::new()));
+/// let effect = Effect::new({
+///     let (room, joined) = (room.clone(), joined.clone());
+///     move || {
+///         let room = *room.get();
+///         joined.borrow_mut().push(room);
+///         let joined = joined.clone();
+///         move || joined.borrow_mut().retain(|r| *r != room)
+///     }
+/// });
+///
+/// room.set("kitchen");
+/// assert_eq!(*joined.borrow(), ["kitchen"]);
+///
+/// drop(effect);
+/// assert!(joined.borrow().is_empty());
+/// ```
+pub trait Cleanup: 'static {
+    /// Returns the cleanup closure, if any.
+    fn into_cleanup(self) -> Option<CleanupFn>;
+}
+
+/// A cleanup returned by an effect run, see [`Cleanup`].
+pub type CleanupFn = Box<dyn FnOnce()>;
+
+impl Cleanup for () {
+    fn into_cleanup(self) -> Option<CleanupFn> {
+        None
+    }
+}
+
+impl<F: FnOnce() + 'static> Cleanup for F {
+    fn into_cleanup(self) -> Option<CleanupFn> {
+        Some(Box::new(self))
+    }
+}
+
+impl<F: FnOnce() + 'static> Cleanup for Option<F> {
+    fn into_cleanup(self) -> Option<CleanupFn> {
+        self.map(|f| Box::new(f) as _)
+    }
+}
+
+/// Parses a duration literal such as `100ms` for the [`effect!`](crate::effect) macro.
+///
+/// Supported units are `ns`, `us`, `ms` and `s`.
+#[doc(hidden)]
+pub const fn parse_duration(s: &str) -> Duration {
+    let bytes = s.as_bytes();
+    let mut value: u64 = 0;
+    let mut i = 0;
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        value = value * 10 + (bytes[i] - b'0') as u64;
+        i += 1;
+    }
+    assert!(i > 0, "A duration must start with a number, as in `100ms`.");
+
+    let unit = bytes.split_at(i).1;
+    let nanos = match unit {
+        b"ns" => 1,
+        b"us" => 1_000,
+        b"ms" => 1_000_000,
+        b"s" => 1_000_000_000,
+        _ => panic!("Unknown duration unit: expected `ns`, `us`, `ms` or `s`."),
+    };
+    Duration::from_nanos(value * nanos)
+}
+
+/// Describes which change caused an [`Effect`] to run.
+///
+/// See [`Effect::last_trigger`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trigger {
+    /// The signal whose change triggered the run.
+    pub source: NodeId,
+
+    /// The memos through which the change propagated to the effect,
+    /// starting with the one closest to `source`.
+    ///
+    /// Empty if the effect read `source` directly.
+    pub path: Vec<NodeId>,
 }
 
 impl Effect {
+    #[track_caller]
+    fn new_inner<C: Cleanup>(f: impl Fn() -> C + 'static, options: EffectOptions) -> Rc<Effect> {
+        crate::runtime::increment(&crate::runtime::EFFECTS);
+        let e = Rc::new(Effect {
+            id: NodeId::next(),
+            location: Location::caller(),
+            f: Box::new(move || f().into_cleanup()),
+            cleanups: Default::default(),
+            sources: Default::default(),
+            subscriptions: Default::default(),
+            disposed: Default::default(),
+            #[cfg(feature = "panic-handler")]
+            failed: Default::default(),
+            last_trigger: Default::default(),
+            reads: Default::default(),
+            ran_at: Default::default(),
+            children: Default::default(),
+            options,
+            last_run: Default::default(),
+            boundary: crate::boundary::current(),
+            on_drop: Default::default(),
+        });
+
+        // An effect created inside a running effect is owned by the current run of its parent.
+        if let Some(parent) = effect_peak().and_then(|p| p.effect.upgrade()) {
+            parent.children.borrow_mut().push(Rc::clone(&e));
+        }
+        crate::scope::adopt_effect(&e);
+
+        e
+    }
+
     /// Creates a new `Effect`, wrapping the provided closure
     /// and running it immediately for dependency tracking.
     ///
+    /// The closure may return a [cleanup](Cleanup), called before its next run and when
+    /// the effect is dropped.
+    ///
     /// Returns an `Rc<Effect>` so the effect can be stored and shared
     /// as a non-generic type.
     ///
@@ -103,6 +454,40 @@ impl Effect {
     /// assert_eq!(counter.get(), 1);
     /// ```
     ///
+    /// ## Nested effects
+    ///
+    /// An effect created while another effect is running is owned by that run:
+    /// the outer effect drops it before running again, so re-runs do not accumulate
+    /// inner effects.
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let outer = Signal::new(0);
+    /// let inner = Signal::new(0);
+    /// let inner_runs = Rc::new(Cell::new(0));
+    ///
+    /// let effect = Effect::new({
+    ///     let (outer, inner, inner_runs) = (outer.clone(), inner.clone(), inner_runs.clone());
+    ///     move || {
+    ///         let _ = outer.get();
+    ///         let (inner, inner_runs) = (inner.clone(), inner_runs.clone());
+    ///         let _ = Effect::new(move || {
+    ///             let _ = inner.get();
+    ///             inner_runs.set(inner_runs.get() + 1);
+    ///         });
+    ///     }
+    /// });
+    /// assert_eq!(inner_runs.get(), 1);
+    ///
+    /// // The outer re-run replaces the inner effect instead of adding another one.
+    /// outer.set(1);
+    /// assert_eq!(inner_runs.get(), 2);
+    ///
+    /// inner.set(1);
+    /// assert_eq!(inner_runs.get(), 3);
+    /// ```
+    ///
     /// ## Using inside a struct
     /// ```
     /// use std::rc::Rc;
@@ -133,8 +518,139 @@ impl Effect {
     /// assert_eq!(double.get(), 6);
     /// ```
     #[allow(clippy::new_ret_no_self)]
-    pub fn new(f: impl Fn() + 'static) -> Rc<Effect> {
-        let e: Rc<Effect> = Rc::new(Effect { f: Box::new(f) });
+    #[track_caller]
+    pub fn new<C: Cleanup>(f: impl Fn() -> C + 'static) -> Rc<Effect> {
+        Effect::new_with_options(f, EffectOptions::default())
+    }
+
+    /// Creates a new `Effect` like [`Effect::new`], whose dependencies are collected again
+    /// on every triggered run.
+    ///
+    /// A run stops depending on the signals and memos it no longer read, and starts
+    /// depending on the ones it read for the first time, such as those of a branch that
+    /// was not taken before. This costs recomputing the memos the effect reads, when
+    /// it runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::RefCell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let logged_in = Signal::new(false);
+    /// let name = Signal::new("guest");
+    /// let greetings = Rc::new(RefCell::new(Vec::new()));
+    /// let effect = Effect::new_dynamic({
+    ///     let (logged_in, name, greetings) =
+    ///         (logged_in.clone(), name.clone(), greetings.clone());
+    ///     move || {
+    ///         let greeting = if *logged_in.get() {
+    ///             format!("hello {}", name.get())
+    ///         } else {
+    ///             "please log in".to_string()
+    ///         };
+    ///         greetings.borrow_mut().push(greeting);
+    ///     }
+    /// });
+    ///
+    /// // `name` was not read yet.
+    /// name.set("ada");
+    /// logged_in.set(true);
+    /// name.set("grace");
+    /// // Once logged out, `name` is no longer read.
+    /// logged_in.set(false);
+    /// name.set("alan");
+    /// assert_eq!(
+    ///     *greetings.borrow(),
+    ///     ["please log in", "hello ada", "hello grace", "please log in"]
+    /// );
+    /// ```
+    #[track_caller]
+    pub fn new_dynamic<C: Cleanup>(f: impl Fn() -> C + 'static) -> Rc<Effect> {
+        Effect::new_with_options(f, EffectOptions::new().dynamic())
+    }
+
+    /// Creates a new `Effect` like [`Effect::new`], which runs again only the first time
+    /// one of its dependencies changes, then disposes itself.
+    ///
+    /// Like any effect, it runs once at creation to collect its dependencies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let loaded = Signal::new(false);
+    /// let notifications = Rc::new(Cell::new(0));
+    /// let effect = Effect::once({
+    ///     let (loaded, notifications) = (loaded.clone(), notifications.clone());
+    ///     move || {
+    ///         if *loaded.get() {
+    ///             notifications.set(notifications.get() + 1);
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// loaded.set(true);
+    /// loaded.set(false);
+    /// loaded.set(true);
+    /// assert_eq!(notifications.get(), 1);
+    /// assert!(effect.is_disposed());
+    /// ```
+    #[track_caller]
+    pub fn once<C: Cleanup>(f: impl Fn() -> C + 'static) -> Rc<Effect> {
+        Effect::new_with_options(f, EffectOptions::new().once())
+    }
+
+    /// Creates a new `Effect` like [`Effect::new`], with the given
+    /// [priority](EffectOptions::priority).
+    ///
+    /// Effects triggered by the same change run in descending priority, so that for
+    /// example layout effects run before the paint effects reading their result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::RefCell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let width = Signal::new(100);
+    /// let order = Rc::new(RefCell::new(Vec::new()));
+    /// let _paint = Effect::new_with_priority(
+    ///     {
+    ///         let (width, order) = (width.clone(), order.clone());
+    ///         move || order.borrow_mut().push(("paint", *width.get()))
+    ///     },
+    ///     0,
+    /// );
+    /// let _layout = Effect::new_with_priority(
+    ///     {
+    ///         let (width, order) = (width.clone(), order.clone());
+    ///         move || order.borrow_mut().push(("layout", *width.get()))
+    ///     },
+    ///     10,
+    /// );
+    /// order.borrow_mut().clear();
+    ///
+    /// width.set(200);
+    /// assert_eq!(*order.borrow(), [("layout", 200), ("paint", 200)]);
+    /// ```
+    #[track_caller]
+    pub fn new_with_priority<C: Cleanup>(f: impl Fn() -> C + 'static, priority: i32) -> Rc<Effect> {
+        Effect::new_with_options(f, EffectOptions::new().priority(priority))
+    }
+
+    /// Creates a new `Effect` like [`Effect::new`], scheduling its triggered runs
+    /// according to `options`.
+    ///
+    /// See [`EffectOptions`] for an example.
+    #[track_caller]
+    pub fn new_with_options<C: Cleanup>(
+        f: impl Fn() -> C + 'static,
+        options: EffectOptions,
+    ) -> Rc<Effect> {
+        let e = Effect::new_inner(f, options);
         let w = Rc::downgrade(&e);
 
         // Dependency collection only at creation time
@@ -202,8 +718,24 @@ impl Effect {
     /// COUNTER().set(20);
     /// assert_eq!(result.get(), 20);
     /// ```
-    pub fn new_with_deps(f: impl Fn() + 'static, deps: impl Fn()) -> Rc<Effect> {
-        let e: Rc<Effect> = Rc::new(Effect { f: Box::new(f) });
+    #[track_caller]
+    pub fn new_with_deps<C: Cleanup>(f: impl Fn() -> C + 'static, deps: impl Fn()) -> Rc<Effect> {
+        Effect::new_with_deps_and_options(f, deps, EffectOptions::default())
+    }
+
+    /// Creates a new `Effect` like [`Effect::new_with_deps`], scheduling its
+    /// triggered runs according to `options`.
+    #[track_caller]
+    pub fn new_with_deps_and_options<C: Cleanup>(
+        f: impl Fn() -> C + 'static,
+        deps: impl Fn(),
+        options: EffectOptions,
+    ) -> Rc<Effect> {
+        let options = EffectOptions {
+            dynamic: false,
+            ..options
+        };
+        let e = Effect::new_inner(f, options);
         let w = Rc::downgrade(&e);
 
         // Dependency collection only at creation time
@@ -219,6 +751,258 @@ impl Effect {
         e
     }
 
+    /// Returns the unique id of this effect.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Returns where this effect was created.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Registers `f` to be called when this effect is dropped.
+    ///
+    /// Callbacks run in registration order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let subscribed = Rc::new(Cell::new(true));
+    /// let effect = Effect::new(|| {});
+    /// effect.on_drop({
+    ///     let subscribed = subscribed.clone();
+    ///     move || subscribed.set(false)
+    /// });
+    ///
+    /// drop(effect);
+    /// assert!(!subscribed.get());
+    /// ```
+    pub fn on_drop(&self, f: impl FnOnce() + 'static) {
+        self.on_drop.borrow_mut().push(Box::new(f));
+    }
+
+    /// Returns what caused the most recent run of this effect.
+    ///
+    /// Returns `None` if the effect has only run once, at creation.
+    /// Otherwise the returned [`Trigger`] names the signal whose change re-ran the effect,
+    /// together with the memos the change propagated through.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let a = Signal::new(1);
+    /// let b = Signal::new(2);
+    /// let sum = Memo::new({
+    ///     let (a, b) = (a.clone(), b.clone());
+    ///     move || *a.get() + *b.get()
+    /// });
+    ///
+    /// let effect = Effect::new({
+    ///     let (a, sum) = (a.clone(), sum.clone());
+    ///     move || {
+    ///         let _ = *a.get() + sum.get();
+    ///     }
+    /// });
+    /// assert!(effect.last_trigger().is_none());
+    ///
+    /// b.set(3);
+    /// let trigger = effect.last_trigger().unwrap();
+    /// assert_eq!(trigger.source, b.id());
+    /// assert_eq!(trigger.path, [sum.id()]);
+    ///
+    /// // `a` is read directly by the effect.
+    /// a.set(4);
+    /// let trigger = effect.last_trigger().unwrap();
+    /// assert_eq!(trigger.source, a.id());
+    /// assert!(trigger.path.is_empty());
+    /// ```
+    pub fn last_trigger(&self) -> Option<Trigger> {
+        let source = self.last_trigger.get()?;
+        let trigger = self
+            .sources
+            .borrow()
+            .iter()
+            .find(|t| t.source == source)
+            .cloned()
+            .unwrap_or(Trigger {
+                source,
+                path: vec![],
+            });
+        Some(trigger)
+    }
+
+    /// Records that this effect depends on `source`, whose revisions are `revisions`,
+    /// through the memos in `path`.
+    pub(crate) fn record_source(
+        &self,
+        source: NodeId,
+        revisions: Weak<Revisions>,
+        path: Vec<NodeId>,
+    ) {
+        self.sources.borrow_mut().push(Trigger { source, path });
+        self.subscriptions.borrow_mut().push(revisions);
+    }
+
+    /// Stops the effect: it is unregistered from the signals and memos it depends on,
+    /// and runs no more, even if it was already triggered or queued. The effects it
+    /// created are dropped, and the [cleanup](Cleanup) of its last run is called.
+    ///
+    /// Returns whether the effect was still active. A disposed effect stays valid, so
+    /// handles to it can be kept, and it is otherwise released when dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let count = Signal::new(0);
+    /// let runs = Rc::new(Cell::new(0));
+    /// let effect = Effect::new({
+    ///     let (count, runs) = (count.clone(), runs.clone());
+    ///     move || {
+    ///         let _ = count.get();
+    ///         runs.set(runs.get() + 1);
+    ///     }
+    /// });
+    ///
+    /// assert!(effect.dispose());
+    /// count.set(1);
+    /// assert_eq!(runs.get(), 1);
+    /// assert!(!effect.dispose());
+    /// ```
+    pub fn dispose(&self) -> bool {
+        if self.disposed.replace(true) {
+            return false;
+        }
+        self.unsubscribe();
+        let children = std::mem::take(&mut *self.children.borrow_mut());
+        drop(children);
+        self.run_cleanup();
+        true
+    }
+
+    /// Returns whether the effect was [disposed](Effect::dispose).
+    pub fn is_disposed(&self) -> bool {
+        self.disposed.get()
+    }
+
+    /// Returns whether the effect was disabled because it panicked outside any error
+    /// boundary, and the panic was caught by the
+    /// [panic handler](crate::boundary::set_panic_handler). A failed effect is disposed.
+    #[cfg(feature = "panic-handler")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "panic-handler")))]
+    pub fn has_failed(&self) -> bool {
+        self.failed.get()
+    }
+
+    /// Keeps the effect alive until it is disposed through the returned handle, without
+    /// holding on to the `Rc`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let count = Signal::new(0);
+    /// let runs = Rc::new(Cell::new(0));
+    /// let handle = Effect::new({
+    ///     let (count, runs) = (count.clone(), runs.clone());
+    ///     move || {
+    ///         let _ = count.get();
+    ///         runs.set(runs.get() + 1);
+    ///     }
+    /// })
+    /// .keep_alive();
+    ///
+    /// count.set(1);
+    /// assert_eq!(runs.get(), 2);
+    ///
+    /// assert!(handle.dispose());
+    /// count.set(2);
+    /// assert_eq!(runs.get(), 2);
+    /// ```
+    pub fn keep_alive(self: Rc<Self>) -> EffectHandle {
+        let id = self.id;
+        KEPT_ALIVE.with(|kept| kept.borrow_mut().insert(id, self));
+        EffectHandle { id }
+    }
+
+    /// Forgets the dependencies of the effect, before a run collects them again.
+    fn forget_dependencies(&self) {
+        self.unsubscribe();
+        self.sources.borrow_mut().clear();
+        self.reads.borrow_mut().clear();
+    }
+
+    /// Removes this effect from the nodes it is registered with.
+    fn unsubscribe(&self) {
+        for source in self.subscriptions.take() {
+            let Some(source) = source.upgrade() else {
+                continue;
+            };
+            source.effects.borrow_mut().retain(|w| {
+                let this = std::ptr::eq(w.as_ptr(), self);
+                if this {
+                    crate::runtime::decrement(&crate::runtime::EDGES, 1);
+                }
+                !this
+            });
+        }
+    }
+
+    /// Records that the effect itself read the node with `revisions`.
+    pub(crate) fn record_read(&self, revisions: Weak<Revisions>) {
+        let mut reads = self.reads.borrow_mut();
+        if !reads.iter().any(|r| Weak::ptr_eq(r, &revisions)) {
+            reads.push(revisions);
+        }
+    }
+
+    /// Returns whether a node read by the effect itself changed since it last ran,
+    /// bringing the memos it read up to date first.
+    ///
+    /// A change of a signal triggers the effects reading it through memos as well,
+    /// which only need to run again if one of those memos computed a different value.
+    pub(crate) fn is_stale(&self) -> bool {
+        let ran_at = self.ran_at.get();
+        // Refreshing a memo may run effects, so the reads are not borrowed across it.
+        for i in 0.. {
+            let Some(read) = self.reads.borrow().get(i).cloned() else {
+                // An effect that read nothing itself always runs.
+                return i == 0;
+            };
+            let Some(read) = read.upgrade() else {
+                return true;
+            };
+            let node = read.node.borrow().clone();
+            if let Some(node) = node {
+                let Some(node) = node.upgrade() else {
+                    return true;
+                };
+                if !node.refresh() {
+                    return true;
+                }
+            }
+            if read.changed_at.get() > ran_at {
+                return true;
+            }
+        }
+        unreachable!()
+    }
+
+    /// Records that the next run is triggered by a change of `source`.
+    pub(crate) fn record_trigger(&self, source: NodeId) {
+        self.last_trigger.set(Some(source));
+    }
+
     /// Runs the effect closure.
     ///
     /// Typically called by the reactive system when dependencies change.
@@ -244,8 +1028,100 @@ impl Effect {
             "`Effect` is not pushed onto the stack before being called."
         );
 
-        (self.f)()
+        // Effects created by the previous run are disposed before running again.
+        let children = std::mem::take(&mut *self.children.borrow_mut());
+        drop(children);
+
+        crate::inspect::emit(crate::inspect::Event::EffectRun(self.id));
+        self.ran_at.set(revision::current());
+        if self.options.throttle.is_some() {
+            self.last_run.set(Some(Instant::now()));
+        }
+
+        self.run_cleanup();
+        if let Some(cleanup) = self.guarded(|| (self.f)()).flatten() {
+            self.cleanups.borrow_mut().push(cleanup);
+        }
+    }
+
+    /// Registers `cleanup` to be called before the next run, or when the effect is
+    /// disposed or dropped.
+    pub(crate) fn on_cleanup(&self, cleanup: CleanupFn) {
+        self.cleanups.borrow_mut().push(cleanup);
+    }
+
+    /// Calls the cleanups of the last run, in the order they were registered,
+    /// without tracking.
+    fn run_cleanup(&self) {
+        for cleanup in self.cleanups.take() {
+            crate::effect_stack::effect_suspended(|| self.guarded(cleanup));
+        }
+    }
+
+    /// Calls `f`, reporting its panic to the boundary of this effect if there is one,
+    /// or else to the panic handler if one is set, in which case the effect is disabled.
+    ///
+    /// Returns `None` if `f` panicked.
+    fn guarded<R>(&self, f: impl FnOnce() -> R) -> Option<R> {
+        if self.boundary.is_none() && !crate::boundary::has_panic_handler() {
+            return Some(f());
+        }
+
+        let depth = effect_depth();
+        match catch_unwind(AssertUnwindSafe(f)) {
+            Ok(result) => Some(result),
+            Err(payload) => {
+                // Unwinding skipped the pops of the effects that were running.
+                effect_truncate(depth);
+                if !crate::boundary::report(self.boundary.as_ref(), self.id, payload) {
+                    // Caught by the panic handler: the effect is disabled.
+                    #[cfg(feature = "panic-handler")]
+                    self.failed.set(true);
+                    self.dispose();
+                }
+                None
+            }
+        }
+    }
+}
+
+impl Drop for Effect {
+    fn drop(&mut self) {
+        crate::runtime::decrement(&crate::runtime::EFFECTS, 1);
+        crate::inspect::forget(self.id);
+        self.unsubscribe();
+        self.run_cleanup();
+
+        for f in self.on_drop.get_mut().drain(..) {
+            f();
+        }
+    }
+}
+
+/// Runs an effect triggered by a change, unless the memos it read kept their values.
+///
+/// Returns whether the effect ran.
+pub(crate) fn run_triggered(e: &Rc<Effect>) -> bool {
+    if e.is_disposed() {
+        return false;
+    }
+    let w = Rc::downgrade(e);
+    let collecting = e.options.dynamic;
+
+    effect_push(w.clone(), collecting);
+    // Memos computed to check the effect panic within its boundary.
+    let stale = e.guarded(|| e.is_stale()) == Some(true);
+    if stale {
+        if collecting {
+            e.forget_dependencies();
+        }
+        e.run();
+        if e.options.once {
+            e.dispose();
+        }
     }
+    effect_pop(w, collecting);
+    stale
 }
 
 pub(crate) fn run_untracked(e: &Rc<Effect>) {
@@ -1,28 +1,37 @@
-#![allow(static_mut_refs)]
-
-use std::rc::Weak;
-
-use once_cell::unsync::Lazy;
+use std::{cell::RefCell, rc::Weak};
 
 use crate::Effect;
 
+#[derive(Clone)]
 pub(crate) struct EffectStackEntry {
     pub(crate) effect: Weak<Effect>,
     pub(crate) collecting: bool,
 }
 
-static mut EFFECT_STACK: Lazy<Vec<EffectStackEntry>> = Lazy::new(Vec::new);
+thread_local! {
+    static EFFECT_STACK: RefCell<Vec<EffectStackEntry>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The effect stack of a runtime that is not entered, see [`Runtime`](crate::Runtime).
+#[derive(Default)]
+pub(crate) struct State(Vec<EffectStackEntry>);
+
+/// Exchanges the effect stack with the one of the runtime being entered or left.
+pub(crate) fn swap(state: &mut State) {
+    EFFECT_STACK.with_borrow_mut(|stack| std::mem::swap(stack, &mut state.0))
+}
 
 pub(crate) fn effect_push(effect: Weak<Effect>, collecting: bool) {
-    unsafe { EFFECT_STACK.push(EffectStackEntry { effect, collecting }) }
+    EFFECT_STACK.with_borrow_mut(|stack| stack.push(EffectStackEntry { effect, collecting }))
 }
 
-pub(crate) fn effect_peak() -> Option<&'static EffectStackEntry> {
-    unsafe { EFFECT_STACK.last() }
+pub(crate) fn effect_peak() -> Option<EffectStackEntry> {
+    EFFECT_STACK.with_borrow(|stack| stack.last().cloned())
 }
 
 pub(crate) fn effect_pop(effect: Weak<Effect>, collecting: bool) {
-    let e = unsafe { EFFECT_STACK.pop() }
+    let e = EFFECT_STACK
+        .with_borrow_mut(Vec::pop)
         .expect("`effect_push` and `effect_pop` are called in pairs and should not be empty.");
 
     assert!(
@@ -34,3 +43,33 @@ pub(crate) fn effect_pop(effect: Weak<Effect>, collecting: bool) {
         "`effect_push` and `effect_pop` are called in pairs and should have the same collecting flag."
     );
 }
+
+/// The number of entries on the stack.
+pub(crate) fn effect_depth() -> usize {
+    EFFECT_STACK.with_borrow(Vec::len)
+}
+
+/// Drops the entries above `depth`, left behind by effects that panicked.
+pub(crate) fn effect_truncate(depth: usize) {
+    EFFECT_STACK.with_borrow_mut(|stack| stack.truncate(depth))
+}
+
+/// Runs `f` with effect dependency collection suspended.
+///
+/// A placeholder entry that is not collecting is pushed onto the stack,
+/// so signals read inside `f` do not register the currently collecting `Effect`.
+pub(crate) fn effect_suspended<R>(f: impl FnOnce() -> R) -> R {
+    // The placeholder is popped even if `f` panics, along with the entries of the
+    // effects it left behind.
+    struct Resume(usize);
+
+    impl Drop for Resume {
+        fn drop(&mut self) {
+            effect_truncate(self.0);
+        }
+    }
+
+    let _resume = Resume(effect_depth());
+    effect_push(Weak::new(), false);
+    f()
+}
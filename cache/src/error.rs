@@ -0,0 +1,24 @@
+use std::{error::Error, fmt};
+
+use crate::NodeId;
+
+/// An error returned by the fallible accessors of reactive nodes, such as
+/// [`Signal::try_get`](crate::Signal::try_get) and [`Signal::try_set`](crate::Signal::try_set).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ReactiveError {
+    /// The value of the node is borrowed in a way that conflicts with the access,
+    /// for example a signal set while a `Ref` to its value is alive, or read while it
+    /// is being updated in place.
+    Borrowed(NodeId),
+}
+
+impl fmt::Display for ReactiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReactiveError::Borrowed(id) => write!(f, "the value of node {id} is already borrowed"),
+        }
+    }
+}
+
+impl Error for ReactiveError {}
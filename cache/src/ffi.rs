@@ -0,0 +1,138 @@
+//! A handle-based C API.
+//!
+//! Enabled with the `ffi` feature. It lets a C/C++ host application own reactive
+//! state while plugins written in other languages read and write it through
+//! opaque handles:
+//!
+//! ```c
+//! typedef struct RcSignalI64 RcSignalI64;
+//! typedef struct RcEffect RcEffect;
+//!
+//! RcSignalI64 *rc_signal_new_i64(int64_t value);
+//! int64_t rc_signal_get_i64(const RcSignalI64 *signal);
+//! bool rc_signal_set_i64(const RcSignalI64 *signal, int64_t value);
+//! void rc_signal_free_i64(RcSignalI64 *signal);
+//!
+//! RcEffect *rc_effect_new(void (*callback)(void *), void *userdata);
+//! void rc_effect_free(RcEffect *effect);
+//! ```
+//!
+//! Every handle returned by a `*_new` function must be released exactly once
+//! with the matching `*_free` function.
+//!
+//! # SAFETY
+//!
+//! Like the rest of the crate, this API is **not thread-safe**. All calls must
+//! be made from the same thread.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{cell::Cell, ffi::c_void};
+//! use reactive_cache::ffi::*;
+//!
+//! extern "C" fn on_change(userdata: *mut c_void) {
+//!     let (signal, seen) = unsafe { &*(userdata as *const (*mut RcSignalI64, Cell<i64>)) };
+//!     seen.set(unsafe { rc_signal_get_i64(*signal) });
+//! }
+//!
+//! let signal = rc_signal_new_i64(1);
+//! let state = (signal, Cell::new(0));
+//! let effect = unsafe { rc_effect_new(on_change, &state as *const _ as *mut c_void) };
+//! assert_eq!(state.1.get(), 1);
+//!
+//! assert!(unsafe { rc_signal_set_i64(signal, 2) });
+//! assert_eq!(state.1.get(), 2);
+//!
+//! unsafe {
+//!     rc_effect_free(effect);
+//!     rc_signal_free_i64(signal);
+//! }
+//! ```
+
+use std::{ffi::c_void, rc::Rc};
+
+use crate::{Effect, Signal, SignalSetter};
+
+/// Opaque handle to a `Signal<i64>`.
+pub struct RcSignalI64(Rc<Signal<i64>>);
+
+/// Opaque handle to an `Effect`.
+pub struct RcEffect(#[allow(dead_code)] Rc<Effect>);
+
+/// Creates a signal holding `value`.
+///
+/// The returned handle must be released with [`rc_signal_free_i64`].
+#[unsafe(no_mangle)]
+pub extern "C" fn rc_signal_new_i64(value: i64) -> *mut RcSignalI64 {
+    Box::into_raw(Box::new(RcSignalI64(Signal::new(value))))
+}
+
+/// Returns the current value of the signal, tracking it as a dependency
+/// when called from inside an effect callback.
+///
+/// # Safety
+///
+/// `signal` must be a live handle returned by [`rc_signal_new_i64`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rc_signal_get_i64(signal: *const RcSignalI64) -> i64 {
+    let signal = unsafe { &*signal };
+    *signal.0.get()
+}
+
+/// Sets the value of the signal.
+///
+/// Returns `true` if the value changed and dependent effects were run.
+///
+/// # Safety
+///
+/// `signal` must be a live handle returned by [`rc_signal_new_i64`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rc_signal_set_i64(signal: *const RcSignalI64, value: i64) -> bool {
+    let signal = unsafe { &*signal };
+    signal.0.set(value)
+}
+
+/// Releases a signal handle.
+///
+/// # Safety
+///
+/// `signal` must be a handle returned by [`rc_signal_new_i64`] that was not released yet,
+/// or null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rc_signal_free_i64(signal: *mut RcSignalI64) {
+    if !signal.is_null() {
+        drop(unsafe { Box::from_raw(signal) });
+    }
+}
+
+/// Creates an effect that calls `callback(userdata)` immediately
+/// and again whenever a signal read by the callback changes.
+///
+/// The returned handle must be released with [`rc_effect_free`],
+/// which also stops the effect.
+///
+/// # Safety
+///
+/// `userdata` must remain valid for as long as the effect is alive.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rc_effect_new(
+    callback: extern "C" fn(*mut c_void),
+    userdata: *mut c_void,
+) -> *mut RcEffect {
+    let effect = Effect::new(move || callback(userdata));
+    Box::into_raw(Box::new(RcEffect(effect)))
+}
+
+/// Releases an effect handle, stopping the effect.
+///
+/// # Safety
+///
+/// `effect` must be a handle returned by [`rc_effect_new`] that was not released yet,
+/// or null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rc_effect_free(effect: *mut RcEffect) {
+    if !effect.is_null() {
+        drop(unsafe { Box::from_raw(effect) });
+    }
+}
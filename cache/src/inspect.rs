@@ -0,0 +1,439 @@
+//! Runtime introspection of the reactive graph.
+//!
+//! This module is the foundation for debugging tools such as the
+//! `devtools` inspector server. It offers two views of the runtime:
+//!
+//! - **Structure**: nodes registered with [`register`] are the roots of the graph
+//!   returned by [`graph`], which also contains every memo and effect reachable
+//!   from them through dependency edges. [`snapshot`] captures that structure in a form
+//!   that [`diff`] compares, for regression tests.
+//! - **Activity**: closures installed with [`add_hook`] receive an [`Event`]
+//!   every time a signal changes, a memo is invalidated or recomputed, or an effect runs.
+//!
+//! Both are opt-in: a program that registers nothing and installs no hook
+//! pays only for an emptiness check on each event.
+//!
+//! # Examples
+//!
+#![cfg_attr(feature = "nightly", doc = "```")]
+#![cfg_attr(not(feature = "nightly"), doc = "```ignore")]
+//! use std::{cell::RefCell, rc::Rc};
+//! use reactive_cache::{inspect::{self, Event, NodeKind}, prelude::*};
+//!
+//! let hp = Signal::new(100);
+//! let low = Memo::new({
+//!     let hp = hp.clone();
+//!     move || *hp.get() < 20
+//! });
+//! inspect::register("hp", &hp);
+//! inspect::register("low", &low);
+//!
+//! let events = Rc::new(RefCell::new(Vec::new()));
+//! let hook = inspect::add_hook({
+//!     let events = events.clone();
+//!     move |e: &Event| events.borrow_mut().push(e.clone())
+//! });
+//!
+//! assert!(!low.get());
+//! hp.set(10);
+//! assert!(low.get());
+//!
+//! assert_eq!(
+//!     *events.borrow(),
+//!     [
+//!         Event::Recomputed(low.id()),
+//!         Event::Invalidated(low.id()),
+//!         Event::Changed(hp.id()),
+//!         Event::Recomputed(low.id()),
+//!     ]
+//! );
+//!
+//! let graph = inspect::graph();
+//! assert_eq!(graph[0].kind, NodeKind::Signal);
+//! assert_eq!(graph[0].name.as_deref(), Some("hp"));
+//! assert_eq!(graph[0].value.as_deref(), Some("10"));
+//! assert_eq!(graph[0].dependents, [low.id()]);
+//! assert_eq!(graph[1].value.as_deref(), Some("true"));
+//!
+//! inspect::remove_hook(hook);
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    fmt::{self, Debug},
+    panic::Location,
+    rc::{Rc, Weak},
+};
+
+use crate::{NodeId, Observable};
+
+/// The kind of a reactive node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    /// A source of values, such as a [`Signal`](crate::Signal).
+    Signal,
+    /// A cached computation, such as a [`Memo`](crate::Memo).
+    Memo,
+    /// A side effect, such as an [`Effect`](crate::Effect).
+    Effect,
+}
+
+/// A description of one node of the reactive graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeInfo {
+    /// The id of the node, never reused by another node.
+    pub id: NodeId,
+    /// Whether the node is a signal, a memo or an effect.
+    pub kind: NodeKind,
+    /// The name given with [`register`] or [`set_name`], if any.
+    pub name: Option<String>,
+    /// Where the node was created.
+    pub location: &'static Location<'static>,
+    /// The `Debug` rendering of the current value, if the value type implements `Debug`.
+    /// Memos only report a value while it is cached; effects never do.
+    pub value: Option<String>,
+    /// The memos and effects notified when this node changes.
+    pub dependents: Vec<NodeId>,
+}
+
+/// Something that happened in the reactive runtime.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// An observable changed and is about to re-run its effects.
+    Changed(NodeId),
+    /// A memo's cached value was dropped because a dependency changed.
+    Invalidated(NodeId),
+    /// A memo computed a new value.
+    Recomputed(NodeId),
+    /// A memo's cached value was evicted to keep the runtime cache within its budget.
+    Evicted(NodeId),
+    /// An effect ran.
+    EffectRun(NodeId),
+}
+
+/// Identifies a hook installed with [`add_hook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HookId(usize);
+
+type Hook = Rc<dyn Fn(&Event)>;
+
+thread_local! {
+    static HOOKS: RefCell<Vec<(HookId, Hook)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_HOOK: Cell<usize> = const { Cell::new(0) };
+
+    static ROOTS: RefCell<Vec<Weak<dyn Observable>>> = const { RefCell::new(Vec::new()) };
+    static NAMES: RefCell<HashMap<NodeId, String>> = RefCell::new(HashMap::new());
+}
+
+/// The hooks, roots and names of a runtime that is not entered,
+/// see [`Runtime`](crate::Runtime).
+#[derive(Default)]
+pub(crate) struct State {
+    hooks: Vec<(HookId, Hook)>,
+    next_hook: usize,
+    roots: Vec<Weak<dyn Observable>>,
+    names: HashMap<NodeId, String>,
+}
+
+/// Exchanges the hooks, roots and names with those of the runtime being entered or left.
+pub(crate) fn swap(state: &mut State) {
+    HOOKS.with_borrow_mut(|hooks| std::mem::swap(hooks, &mut state.hooks));
+    state.next_hook = NEXT_HOOK.replace(state.next_hook);
+    ROOTS.with_borrow_mut(|roots| std::mem::swap(roots, &mut state.roots));
+    NAMES.with_borrow_mut(|names| std::mem::swap(names, &mut state.names));
+}
+
+/// Installs `hook`, which is called with every subsequent [`Event`].
+///
+/// Hooks run synchronously, in the middle of the operation that caused the event.
+/// They must not read or write reactive nodes.
+pub fn add_hook(hook: impl Fn(&Event) + 'static) -> HookId {
+    let id = HookId(NEXT_HOOK.get());
+    NEXT_HOOK.set(id.0 + 1);
+    HOOKS.with_borrow_mut(|hooks| hooks.push((id, Rc::new(hook))));
+    id
+}
+
+/// Removes a hook installed with [`add_hook`].
+///
+/// Returns `false` if the hook was already removed.
+pub fn remove_hook(id: HookId) -> bool {
+    HOOKS.with_borrow_mut(|hooks| {
+        let len = hooks.len();
+        hooks.retain(|(h, _)| *h != id);
+        hooks.len() != len
+    })
+}
+
+pub(crate) fn emit(event: Event) {
+    if HOOKS.with_borrow(Vec::is_empty) {
+        return;
+    }
+
+    // Hooks may install or remove hooks, so iterate over a snapshot.
+    let hooks: Vec<_> = HOOKS.with_borrow(|hooks| hooks.iter().map(|(_, h)| h.clone()).collect());
+    for hook in hooks {
+        hook(&event);
+    }
+}
+
+/// Gives a node a human-readable name, shown by debugging tools.
+pub fn set_name(id: NodeId, name: impl Into<String>) {
+    NAMES.with_borrow_mut(|names| names.insert(id, name.into()));
+}
+
+/// Returns the name given to a node with [`register`] or [`set_name`].
+pub fn name(id: NodeId) -> Option<String> {
+    NAMES.with_borrow(|names| names.get(&id).cloned())
+}
+
+pub(crate) fn forget(id: NodeId) {
+    // Nodes held by thread-locals may be dropped after the names, as the thread exits.
+    let _ = NAMES.try_with(|names| {
+        let mut names = names.borrow_mut();
+        if !names.is_empty() {
+            names.remove(&id);
+        }
+    });
+}
+
+/// Names `node` and adds it to the roots of [`graph`].
+///
+/// Only a weak reference is kept: registering a node never keeps it alive.
+pub fn register<N: Observable + 'static>(name: impl Into<String>, node: &Rc<N>) {
+    set_name(node.id(), name);
+
+    let weak: Weak<dyn Observable> = Rc::downgrade(node) as _;
+    ROOTS.with_borrow_mut(|roots| {
+        roots.retain(|r| r.strong_count() > 0);
+        if !roots.iter().any(|r| Weak::ptr_eq(r, &weak)) {
+            roots.push(weak);
+        }
+    });
+}
+
+/// Returns the live registered nodes and every node reachable from them,
+/// in breadth-first order starting with the roots in registration order.
+pub fn graph() -> Vec<NodeInfo> {
+    let mut seen = HashSet::new();
+    let mut queue: Vec<Rc<dyn Observable>> =
+        ROOTS.with_borrow(|roots| roots.iter().filter_map(Weak::upgrade).collect());
+    let mut nodes = Vec::new();
+
+    let mut i = 0;
+    while i < queue.len() {
+        let node = queue[i].clone();
+        i += 1;
+        if !seen.insert(node.id()) {
+            continue;
+        }
+
+        let tracker = node.tracker();
+        let memos = tracker.dependent_memos();
+        let effects = tracker.dependent_effects();
+
+        nodes.push(NodeInfo {
+            id: node.id(),
+            kind: node.kind(),
+            name: name(node.id()),
+            location: tracker.location(),
+            value: node.debug_value(),
+            dependents: memos
+                .iter()
+                .map(|m| m.id())
+                .chain(effects.iter().map(|e| e.id()))
+                .collect(),
+        });
+
+        for effect in effects {
+            if seen.insert(effect.id()) {
+                nodes.push(NodeInfo {
+                    id: effect.id(),
+                    kind: NodeKind::Effect,
+                    name: name(effect.id()),
+                    location: effect.location(),
+                    value: None,
+                    dependents: Vec::new(),
+                });
+            }
+        }
+        queue.extend(memos.into_iter().map(|m| m as Rc<dyn Observable>));
+    }
+
+    nodes
+}
+
+/// The structure of the graph returned by [`graph`]: its nodes and edges, without ids or values.
+///
+/// Nodes are labelled by their kind and name, or by their kind and creation site if they
+/// have no name, so snapshots taken by different runs of the same code compare equal.
+/// See [`snapshot`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Snapshot {
+    /// The labels of the nodes, sorted.
+    pub nodes: Vec<String>,
+    /// The edges from each node to its dependents, as labels, sorted.
+    pub edges: Vec<(String, String)>,
+}
+
+/// The differences between two [`Snapshot`]s, returned by [`diff`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SnapshotDiff {
+    /// The labels of the nodes only in the new snapshot, sorted.
+    pub added_nodes: Vec<String>,
+    /// The labels of the nodes only in the old snapshot, sorted.
+    pub removed_nodes: Vec<String>,
+    /// The edges only in the new snapshot, as labels, sorted.
+    pub added_edges: Vec<(String, String)>,
+    /// The edges only in the old snapshot, as labels, sorted.
+    pub removed_edges: Vec<(String, String)>,
+}
+
+impl SnapshotDiff {
+    /// Returns whether both snapshots have the same structure.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+/// Lists the differences one per line, prefixed with `+` if added and `-` if removed.
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for node in &self.added_nodes {
+            writeln!(f, "+ {node}")?;
+        }
+        for node in &self.removed_nodes {
+            writeln!(f, "- {node}")?;
+        }
+        for (source, dependent) in &self.added_edges {
+            writeln!(f, "+ {source} -> {dependent}")?;
+        }
+        for (source, dependent) in &self.removed_edges {
+            writeln!(f, "- {source} -> {dependent}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Captures the structure of the graph returned by [`graph`].
+///
+/// Comparing snapshots with [`diff`] lets tests assert that a change did not add
+/// unexpected dependencies, such as an effect subscribing to more signals than it needs.
+///
+/// # Examples
+///
+/// ```
+/// use reactive_cache::{inspect, prelude::*};
+///
+/// let hp = Signal::new(100);
+/// let mana = Signal::new(10);
+/// inspect::register("hp", &hp);
+/// inspect::register("mana", &mana);
+///
+/// let low = Memo::new({
+///     let hp = hp.clone();
+///     move || *hp.get() < 20
+/// });
+/// inspect::set_name(low.id(), "low");
+/// low.get();
+/// let before = inspect::snapshot();
+/// assert_eq!(before.edges, [("signal hp".to_string(), "memo low".to_string())]);
+///
+/// // An effect that reads more than it needs.
+/// let alert = Effect::new({
+///     let (low, mana) = (low.clone(), mana.clone());
+///     move || {
+///         let _ = (low.get(), mana.get());
+///     }
+/// });
+/// inspect::set_name(alert.id(), "alert");
+///
+/// let diff = inspect::diff(&before, &inspect::snapshot());
+/// assert_eq!(diff.added_nodes, ["effect alert"]);
+/// assert_eq!(
+///     diff.to_string(),
+///     "+ effect alert\n\
+///      + memo low -> effect alert\n\
+///      + signal hp -> effect alert\n\
+///      + signal mana -> effect alert\n"
+/// );
+/// ```
+pub fn snapshot() -> Snapshot {
+    let graph = graph();
+    let labels: HashMap<NodeId, String> = graph.iter().map(|n| (n.id, label(n))).collect();
+
+    let mut nodes: Vec<String> = labels.values().cloned().collect();
+    let mut edges: Vec<(String, String)> = graph
+        .iter()
+        .flat_map(|n| {
+            let source = &labels[&n.id];
+            n.dependents
+                .iter()
+                .filter_map(|d| Some((source.clone(), labels.get(d)?.clone())))
+        })
+        .collect();
+    nodes.sort();
+    edges.sort();
+    Snapshot { nodes, edges }
+}
+
+/// Returns what `new` added to and removed from `old`.
+///
+/// Nodes with the same label are told apart by their number only.
+pub fn diff(old: &Snapshot, new: &Snapshot) -> SnapshotDiff {
+    SnapshotDiff {
+        added_nodes: difference(&new.nodes, &old.nodes),
+        removed_nodes: difference(&old.nodes, &new.nodes),
+        added_edges: difference(&new.edges, &old.edges),
+        removed_edges: difference(&old.edges, &new.edges),
+    }
+}
+
+fn label(node: &NodeInfo) -> String {
+    let kind = match node.kind {
+        NodeKind::Signal => "signal",
+        NodeKind::Memo => "memo",
+        NodeKind::Effect => "effect",
+    };
+    match &node.name {
+        Some(name) => format!("{kind} {name}"),
+        None => format!("{kind} at {}", node.location),
+    }
+}
+
+/// Returns the items of the sorted `a` missing from the sorted `b`, counting duplicates.
+fn difference<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut b = b.iter().peekable();
+    let mut missing = Vec::new();
+    for item in a {
+        while b.next_if(|other| *other < item).is_some() {}
+        if b.next_if(|other| *other == item).is_none() {
+            missing.push(item.clone());
+        }
+    }
+    missing
+}
+
+/// Renders values whose type implements `Debug`.
+pub(crate) trait DebugValue {
+    fn debug_string(&self) -> Option<String>;
+}
+
+impl<T: ?Sized> DebugValue for T {
+    specializable! {
+        fn debug_string(&self) -> Option<String> {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T: Debug + ?Sized> DebugValue for T {
+    fn debug_string(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+}
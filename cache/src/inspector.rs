@@ -0,0 +1,169 @@
+//! An interactive terminal inspector of the reactive graph.
+//!
+//! Enabled with the `inspector` feature. [`run`] takes over the terminal and shows:
+//!
+//! - the nodes reported by [`inspect::graph`], with their current values and
+//!   the number of memos and effects subscribed to each of them,
+//! - a live log of signal changes, memo invalidations and recomputations, and effect runs.
+//!
+//! Unlike the `devtools` server, it needs nothing but a terminal,
+//! so it also works over SSH or inside containers.
+//!
+//! Keys: `↑`/`↓` select a node, `q` or `Esc` quits.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use reactive_cache::{inspect, inspector, prelude::*};
+//!
+//! let frame = Signal::new(0_u64);
+//! inspect::register("frame", &frame);
+//!
+//! // Keep the application running while inspecting it.
+//! inspector::run_with(|| {
+//!     frame.set(*frame.get() + 1);
+//! })
+//! .unwrap();
+//! ```
+
+use std::{cell::RefCell, collections::VecDeque, io, rc::Rc, time::Duration};
+
+use ratatui::{
+    Frame,
+    crossterm::event::{self, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, List, Row, Table, TableState},
+};
+
+use crate::{
+    NodeId,
+    inspect::{self, Event, NodeInfo, NodeKind},
+};
+
+/// The number of log lines kept.
+const LOG_CAPACITY: usize = 200;
+
+/// How long to wait for a key press before redrawing.
+const FRAME: Duration = Duration::from_millis(100);
+
+/// Runs the inspector until the user quits.
+///
+/// The calling thread is blocked meanwhile, so the graph only changes through
+/// nodes driven from elsewhere. Use [`run_with`] to keep the application running.
+pub fn run() -> io::Result<()> {
+    run_with(|| {})
+}
+
+/// Runs the inspector until the user quits, calling `tick` before every frame.
+///
+/// `tick` runs on the calling thread, so it may read and write reactive nodes.
+pub fn run_with(mut tick: impl FnMut()) -> io::Result<()> {
+    let log = Rc::new(RefCell::new(VecDeque::with_capacity(LOG_CAPACITY)));
+    let hook = inspect::add_hook({
+        let log = log.clone();
+        move |e: &Event| {
+            let mut log = log.borrow_mut();
+            if log.len() == LOG_CAPACITY {
+                log.pop_front();
+            }
+            log.push_back(describe(e));
+        }
+    });
+
+    let mut terminal = ratatui::try_init()?;
+    let mut selected = TableState::default().with_selected(0);
+
+    let result: io::Result<()> = (|| loop {
+        tick();
+
+        let nodes = inspect::graph();
+        terminal.draw(|frame| draw(frame, &nodes, &log.borrow(), &mut selected))?;
+
+        if event::poll(FRAME)?
+            && let event::Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down => selected.select_next(),
+                KeyCode::Up => selected.select_previous(),
+                _ => {}
+            }
+        }
+    })();
+
+    ratatui::restore();
+    inspect::remove_hook(hook);
+    result
+}
+
+fn draw(frame: &mut Frame, nodes: &[NodeInfo], log: &VecDeque<String>, selected: &mut TableState) {
+    let [top, bottom] = Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .areas(frame.area());
+
+    let rows = nodes.iter().map(|n| {
+        Row::new([
+            n.id.to_string(),
+            kind(n.kind).to_string(),
+            n.name.clone().unwrap_or_default(),
+            n.location.to_string(),
+            n.value.clone().unwrap_or_else(|| "-".into()),
+            n.dependents.len().to_string(),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Percentage(20),
+            Constraint::Percentage(25),
+            Constraint::Fill(1),
+            Constraint::Length(12),
+        ],
+    )
+    .header(
+        Row::new(["Id", "Kind", "Name", "Created at", "Value", "Subscribers"])
+            .style(Style::new().add_modifier(Modifier::BOLD)),
+    )
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+    .block(Block::bordered().title(Line::from(format!(" Nodes ({}) ", nodes.len()))));
+    frame.render_stateful_widget(table, top, selected);
+
+    // Show the most recent entries that fit, newest last.
+    let height = bottom.height.saturating_sub(2) as usize;
+    let entries = log
+        .iter()
+        .skip(log.len().saturating_sub(height))
+        .map(String::as_str);
+    let list = List::new(entries).block(Block::bordered().title(" Log (q to quit) "));
+    frame.render_widget(list, bottom);
+}
+
+fn kind(kind: NodeKind) -> &'static str {
+    match kind {
+        NodeKind::Signal => "signal",
+        NodeKind::Memo => "memo",
+        NodeKind::Effect => "effect",
+    }
+}
+
+fn describe(event: &Event) -> String {
+    let (what, id) = match event {
+        Event::Changed(id) => ("changed", id),
+        Event::Invalidated(id) => ("invalidated", id),
+        Event::Recomputed(id) => ("recomputed", id),
+        Event::Evicted(id) => ("evicted", id),
+        Event::EffectRun(id) => ("ran", id),
+    };
+    format!("{} {what}", label(*id))
+}
+
+fn label(id: NodeId) -> String {
+    match inspect::name(id) {
+        Some(name) => format!("{name} ({id})"),
+        None => id.to_string(),
+    }
+}
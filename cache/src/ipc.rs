@@ -0,0 +1,713 @@
+//! Mirroring named signals between processes over a local socket.
+//!
+//! Enabled with the `ipc` feature. One process hosts an [`IpcSync`] under a name,
+//! other processes connect to it, and each side [shares](IpcSync::share) signals
+//! under names agreed on by both. When a shared signal changes on one side, the
+//! other sides set their signal of the same name to the new value, through the
+//! normal `set` path, so memos and effects react as usual. Without the `nightly`
+//! feature, a received value is only compared with the local one by the comparator of
+//! a signal created with [`Signal::new_with_eq`]: otherwise it notifies the dependents
+//! even when it is equal.
+//!
+//! The transport is a Unix domain socket or a Windows named pipe. Connections are
+//! handled on background threads, but signals are only read and written by the
+//! thread owning them: incoming changes are applied when it calls [`IpcSync::poll`],
+//! typically once per frame or event-loop iteration.
+//!
+//! # Protocol
+//!
+//! Every message is one line of JSON, naming the signal and holding its value
+//! serialized with `serde`:
+//!
+//! ```json
+//! {"name": "volume", "value": 7}
+//! ```
+//!
+//! A message is sent whenever a shared signal changes. In addition, the host sends
+//! the current value of every shared signal to each newly connected process,
+//! so a tool connecting to an application starts from the application's state.
+//! The host forwards changes received from one process to the others.
+//! Messages naming a signal that is not shared, or holding a value that
+//! cannot be deserialized, are ignored.
+//!
+//! Signals shared with a [`Merge`] strategy or as counters carry additional fields
+//! used to resolve concurrent changes: `"stamp": [milliseconds, replica]` for
+//! [`Merge::LastWriterWins`], and `"counts": {"<replica>": [increments, decrements]}`
+//! for [counters](IpcSync::share_counter).
+//!
+//! # Examples
+//!
+//! ```
+//! use reactive_cache::{ipc::IpcSync, prelude::*};
+//!
+//! let name = format!("reactive-cache-doc-{}", std::process::id());
+//!
+//! // The application.
+//! let app = IpcSync::host(&name).unwrap();
+//! let volume = Signal::new(3);
+//! app.share("volume", &volume);
+//!
+//! // A tool, usually running in another process.
+//! let tool = IpcSync::connect(&name).unwrap();
+//! let mirrored = Signal::new(0);
+//! tool.share("volume", &mirrored);
+//!
+//! // The tool starts from the application's state...
+//! while *mirrored.get() != 3 {
+//!     app.poll();
+//!     tool.poll();
+//! }
+//!
+//! // ...and can tweak it live.
+//! mirrored.set(7);
+//! while *volume.get() != 7 {
+//!     app.poll();
+//! }
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, Hasher, RandomState},
+    io::{self, BufRead, BufReader, Write},
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use interprocess::local_socket::{
+    GenericFilePath, GenericNamespaced, Listener, ListenerOptions, Name, RecvHalf, SendHalf,
+    Stream, prelude::*,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+
+use crate::{Effect, Signal};
+
+/// Identifies a connection to another process.
+type PeerId = usize;
+
+/// Identifies a process taking part in the synchronization, for conflict resolution.
+type ReplicaId = u64;
+
+type MergeFn<T> = Box<dyn Fn(&T, &T) -> T>;
+
+/// How concurrent changes of a shared signal are resolved, passed to [`IpcSync::share_with`].
+///
+/// Processes mutating the same signal at the same time receive each other's changes
+/// in different orders. Every strategy but [`Merge::Replace`] makes all processes
+/// end up with the same value regardless of that order.
+pub enum Merge<T> {
+    /// Received values replace the local one. Concurrent changes may leave processes disagreeing.
+    Replace,
+    /// The most recent change wins everywhere.
+    ///
+    /// Changes are ordered by a timestamp that is never lower than the timestamp of any
+    /// change seen before, so a change made after receiving another one always wins over it.
+    /// Ties between processes are broken by a random identifier drawn by each [`IpcSync`].
+    /// Changes received without a valid timestamp are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{ipc::{IpcSync, Merge}, prelude::*};
+    ///
+    /// let name = format!("reactive-cache-doc-lww-{}", std::process::id());
+    ///
+    /// let host = IpcSync::host(&name).unwrap();
+    /// let theme = Signal::new("dark".to_string());
+    /// let done = Signal::new(false);
+    /// host.share_with("theme", &theme, Merge::LastWriterWins);
+    /// host.share("done", &done);
+    ///
+    /// // A peer sharing the signal with another strategy sends no timestamp.
+    /// let peer = IpcSync::connect(&name).unwrap();
+    /// let peer_theme = Signal::new(String::new());
+    /// let peer_done = Signal::new(false);
+    /// peer.share("theme", &peer_theme);
+    /// peer.share("done", &peer_done);
+    /// while *peer_theme.get() != "dark" {
+    ///     host.poll();
+    ///     peer.poll();
+    /// }
+    ///
+    /// peer_theme.set("light".to_string());
+    /// peer_done.set(true);
+    /// while !*done.get() {
+    ///     host.poll();
+    /// }
+    /// assert_eq!(*theme.get(), "dark");
+    /// ```
+    LastWriterWins,
+    /// Received values are merged into the local one with a function.
+    ///
+    /// For all processes to converge, `merge(local, received)` must be commutative,
+    /// associative and idempotent, like taking the maximum or the union of two sets.
+    /// When the merged value differs from the received one, it is sent back to the sender.
+    With(MergeFn<T>),
+}
+
+/// The outcome of resolving a received value against the local one.
+enum Resolution<T> {
+    /// The local value is kept.
+    Keep,
+    /// The signal is set to the value, and the sender is told the result if `reply` is set.
+    Set { value: T, reply: bool },
+}
+
+/// The conflict resolution state of a shared signal.
+trait Resolve<T> {
+    /// Returns the fields sent along with a local change to `value`.
+    fn local(&self, value: &T) -> Map<String, Value>;
+
+    /// Returns the fields describing the current value, when forwarding it or welcoming a process.
+    fn current(&self) -> Map<String, Value>;
+
+    /// Resolves `value`, received with `fields`, against the `local` value.
+    fn remote(&self, local: &T, value: T, fields: &Map<String, Value>) -> Resolution<T>;
+}
+
+struct ReplaceResolver;
+
+impl<T> Resolve<T> for ReplaceResolver {
+    fn local(&self, _: &T) -> Map<String, Value> {
+        Map::new()
+    }
+
+    fn current(&self) -> Map<String, Value> {
+        Map::new()
+    }
+
+    fn remote(&self, _: &T, value: T, _: &Map<String, Value>) -> Resolution<T> {
+        Resolution::Set {
+            value,
+            reply: false,
+        }
+    }
+}
+
+/// Orders changes for [`Merge::LastWriterWins`]: milliseconds since the epoch, then replica.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct Stamp(u64, ReplicaId);
+
+struct LwwResolver {
+    replica: ReplicaId,
+    stamp: Cell<Stamp>,
+}
+
+impl<T> Resolve<T> for LwwResolver {
+    fn local(&self, _: &T) -> Map<String, Value> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        self.stamp
+            .set(Stamp(now.max(self.stamp.get().0 + 1), self.replica));
+        <Self as Resolve<T>>::current(self)
+    }
+
+    fn current(&self) -> Map<String, Value> {
+        let Stamp(time, replica) = self.stamp.get();
+        Map::from_iter([("stamp".to_string(), json!([time, replica]))])
+    }
+
+    fn remote(&self, _: &T, value: T, fields: &Map<String, Value>) -> Resolution<T> {
+        let Some((time, replica)) = fields
+            .get("stamp")
+            .cloned()
+            .and_then(|v| serde_json::from_value::<(u64, ReplicaId)>(v).ok())
+        else {
+            return Resolution::Keep;
+        };
+        let stamp = Stamp(time, replica);
+        if stamp <= self.stamp.get() {
+            return Resolution::Keep;
+        }
+        self.stamp.set(stamp);
+        Resolution::Set {
+            value,
+            reply: false,
+        }
+    }
+}
+
+struct MergeResolver<T>(MergeFn<T>);
+
+impl<T: PartialEq> Resolve<T> for MergeResolver<T> {
+    fn local(&self, _: &T) -> Map<String, Value> {
+        Map::new()
+    }
+
+    fn current(&self) -> Map<String, Value> {
+        Map::new()
+    }
+
+    fn remote(&self, local: &T, value: T, _: &Map<String, Value>) -> Resolution<T> {
+        let merged = (self.0)(local, &value);
+        let reply = merged != value;
+        Resolution::Set {
+            value: merged,
+            reply,
+        }
+    }
+}
+
+/// A counter whose increments and decrements by every process are all kept.
+///
+/// Each process counts its own increments and decrements; the value is their sum over all
+/// processes. Received counts are merged by keeping the highest count of each process.
+struct CounterResolver {
+    replica: ReplicaId,
+    counts: RefCell<BTreeMap<ReplicaId, (i64, i64)>>,
+    /// The value last set or seen, to tell local changes apart.
+    total: Cell<i64>,
+}
+
+impl Resolve<i64> for CounterResolver {
+    fn local(&self, value: &i64) -> Map<String, Value> {
+        let delta = value - self.total.replace(*value);
+        let mut counts = self.counts.borrow_mut();
+        let (up, down) = counts.entry(self.replica).or_default();
+        if delta > 0 {
+            *up += delta;
+        } else {
+            *down -= delta;
+        }
+        drop(counts);
+        self.current()
+    }
+
+    fn current(&self) -> Map<String, Value> {
+        let counts: Map<String, Value> = self
+            .counts
+            .borrow()
+            .iter()
+            .map(|(replica, counts)| (replica.to_string(), json!(counts)))
+            .collect();
+        Map::from_iter([("counts".to_string(), Value::Object(counts))])
+    }
+
+    fn remote(&self, _: &i64, _: i64, fields: &Map<String, Value>) -> Resolution<i64> {
+        let Some(Value::Object(received)) = fields.get("counts") else {
+            return Resolution::Keep;
+        };
+        let mut counts = self.counts.borrow_mut();
+        for (replica, count) in received {
+            if let (Ok(replica), Ok((up, down))) = (
+                replica.parse(),
+                serde_json::from_value::<(i64, i64)>(count.clone()),
+            ) {
+                let entry = counts.entry(replica).or_default();
+                *entry = (entry.0.max(up), entry.1.max(down));
+            }
+        }
+
+        let total = counts.values().map(|(up, down)| up - down).sum();
+        self.total.set(total);
+        Resolution::Set {
+            value: total,
+            reply: false,
+        }
+    }
+}
+
+type Apply = Box<dyn Fn(Map<String, Value>) -> (bool, Option<String>)>;
+
+struct Shared {
+    /// Applies a received message, returning whether the signal was set
+    /// and the message to reply to the sender with, if any.
+    apply: Apply,
+    /// Returns the message describing the current value.
+    snapshot: Box<dyn Fn() -> Option<String>>,
+}
+
+struct Inner {
+    host: bool,
+    replica: ReplicaId,
+    peers: RefCell<Vec<(PeerId, SendHalf)>>,
+    signals: RefCell<HashMap<String, Shared>>,
+    /// The peer whose change is being applied, which must not receive it back.
+    origin: Cell<Option<PeerId>>,
+}
+
+impl Inner {
+    fn new(host: bool, peers: Vec<(PeerId, SendHalf)>) -> Rc<Self> {
+        Rc::new(Inner {
+            host,
+            replica: RandomState::new().build_hasher().finish(),
+            peers: RefCell::new(peers),
+            signals: Default::default(),
+            origin: Default::default(),
+        })
+    }
+
+    /// Sends `message` to every peer but `except`, dropping peers whose connection failed.
+    fn broadcast(&self, message: &str, except: Option<PeerId>) {
+        self.peers
+            .borrow_mut()
+            .retain_mut(|(id, peer)| Some(*id) == except || writeln!(peer, "{message}").is_ok());
+    }
+
+    /// Sends `message` to `peer` only.
+    fn send(&self, message: &str, peer: PeerId) {
+        self.peers
+            .borrow_mut()
+            .retain_mut(|(id, p)| *id != peer || writeln!(p, "{message}").is_ok());
+    }
+}
+
+/// Mirrors named signals with other processes. See the [module docs](self).
+///
+/// Dropping it closes all connections and stops the background threads.
+pub struct IpcSync {
+    inner: Rc<Inner>,
+    incoming: Receiver<(PeerId, String)>,
+    accepted: Option<Receiver<(PeerId, SendHalf)>>,
+    effects: RefCell<Vec<Rc<Effect>>>,
+    listener: Option<(String, Arc<AtomicBool>)>,
+}
+
+impl IpcSync {
+    /// Starts accepting processes connecting under `name` on a background thread.
+    ///
+    /// The name is local to the machine. It fails with [`io::ErrorKind::AddrInUse`]
+    /// if another process already hosts it.
+    pub fn host(name: &str) -> io::Result<Self> {
+        let listener = ListenerOptions::new()
+            .name(socket_name(name)?)
+            .create_sync()?;
+        let (incoming_tx, incoming) = mpsc::channel();
+        let (accepted_tx, accepted) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        thread::spawn({
+            let shutdown = shutdown.clone();
+            move || accept(listener, accepted_tx, incoming_tx, shutdown)
+        });
+
+        Ok(IpcSync {
+            inner: Inner::new(true, vec![]),
+            incoming,
+            accepted: Some(accepted),
+            effects: Default::default(),
+            listener: Some((name.to_string(), shutdown)),
+        })
+    }
+
+    /// Connects to the process hosting `name`.
+    pub fn connect(name: &str) -> io::Result<Self> {
+        let (recv, send) = Stream::connect(socket_name(name)?)?.split();
+        let (incoming_tx, incoming) = mpsc::channel();
+        thread::spawn(move || read(0, recv, incoming_tx));
+
+        Ok(IpcSync {
+            inner: Inner::new(false, vec![(0, send)]),
+            incoming,
+            accepted: None,
+            effects: Default::default(),
+            listener: None,
+        })
+    }
+
+    /// Mirrors `signal` under `name` with the other processes, replacing its value
+    /// with every value received.
+    ///
+    /// On a connecting process, the signal keeps its value until the host's value arrives.
+    /// On the host, the current value is sent to the processes already connected.
+    pub fn share<T>(&self, name: &str, signal: &Rc<Signal<T>>)
+    where
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        self.share_resolved(name, signal, Rc::new(ReplaceResolver))
+    }
+
+    /// Mirrors `signal` under `name` like [`share`](Self::share), resolving concurrent
+    /// changes with `merge`.
+    ///
+    /// Every process sharing the signal must use the same strategy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeSet;
+    /// use reactive_cache::{ipc::{IpcSync, Merge}, prelude::*};
+    ///
+    /// let name = format!("reactive-cache-doc-merge-{}", std::process::id());
+    /// let union = || Merge::With(Box::new(|a: &BTreeSet<u32>, b: &BTreeSet<u32>| a | b));
+    ///
+    /// let host = IpcSync::host(&name).unwrap();
+    /// let tags = Signal::new(BTreeSet::from([1]));
+    /// host.share_with("tags", &tags, union());
+    ///
+    /// let peer = IpcSync::connect(&name).unwrap();
+    /// let peer_tags = Signal::new(BTreeSet::new());
+    /// peer.share_with("tags", &peer_tags, union());
+    ///
+    /// // Both sides add a tag at the same time.
+    /// tags.set(BTreeSet::from([1, 2]));
+    /// peer_tags.set(BTreeSet::from([3]));
+    ///
+    /// while *tags.get() != BTreeSet::from([1, 2, 3]) || *peer_tags.get() != *tags.get() {
+    ///     host.poll();
+    ///     peer.poll();
+    /// }
+    /// ```
+    pub fn share_with<T>(&self, name: &str, signal: &Rc<Signal<T>>, merge: Merge<T>)
+    where
+        T: Serialize + DeserializeOwned + PartialEq + 'static,
+    {
+        let resolver: Rc<dyn Resolve<T>> = match merge {
+            Merge::Replace => Rc::new(ReplaceResolver),
+            Merge::LastWriterWins => Rc::new(LwwResolver {
+                replica: self.inner.replica,
+                stamp: Default::default(),
+            }),
+            Merge::With(merge) => Rc::new(MergeResolver(merge)),
+        };
+        self.share_resolved(name, signal, resolver)
+    }
+
+    /// Mirrors a counter under `name`, keeping the increments and decrements of every process.
+    ///
+    /// Each process may change the counter freely. Its value is the sum of the changes made
+    /// by all processes, however they interleave. The initial value is the host's.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{ipc::IpcSync, prelude::*};
+    ///
+    /// let name = format!("reactive-cache-doc-counter-{}", std::process::id());
+    ///
+    /// let host = IpcSync::host(&name).unwrap();
+    /// let visits = Signal::new(0_i64);
+    /// host.share_counter("visits", &visits);
+    ///
+    /// let peer = IpcSync::connect(&name).unwrap();
+    /// let peer_visits = Signal::new(0_i64);
+    /// peer.share_counter("visits", &peer_visits);
+    ///
+    /// // Both sides count visits at the same time.
+    /// visits.add(2);
+    /// peer_visits.add(3);
+    ///
+    /// while *visits.get() != 5 || *peer_visits.get() != 5 {
+    ///     host.poll();
+    ///     peer.poll();
+    /// }
+    /// ```
+    pub fn share_counter(&self, name: &str, signal: &Rc<Signal<i64>>) {
+        let initial = signal.with_untracked(|v| *v);
+        // The initial value of the host counts as its own changes; other processes
+        // take the host's value once it arrives.
+        let mut counts = BTreeMap::new();
+        if self.inner.host {
+            counts.insert(self.inner.replica, (initial.max(0), (-initial).max(0)));
+        }
+        let resolver = CounterResolver {
+            replica: self.inner.replica,
+            counts: RefCell::new(counts),
+            total: Cell::new(initial),
+        };
+        self.share_resolved(name, signal, Rc::new(resolver))
+    }
+
+    fn share_resolved<T>(&self, name: &str, signal: &Rc<Signal<T>>, resolver: Rc<dyn Resolve<T>>)
+    where
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        let message = {
+            let name = name.to_string();
+            move |value: &T, mut fields: Map<String, Value>| {
+                fields.insert("name".to_string(), json!(name));
+                fields.insert("value".to_string(), serde_json::to_value(value).ok()?);
+                Some(Value::Object(fields).to_string())
+            }
+        };
+        let message = Rc::new(message);
+
+        let weak = Rc::downgrade(signal);
+        self.inner.signals.borrow_mut().insert(
+            name.to_string(),
+            Shared {
+                apply: Box::new({
+                    let (weak, resolver, message) =
+                        (weak.clone(), resolver.clone(), message.clone());
+                    move |mut fields| {
+                        let Some(signal) = weak.upgrade() else {
+                            return (false, None);
+                        };
+                        let Some(value) = fields
+                            .remove("value")
+                            .and_then(|v| serde_json::from_value(v).ok())
+                        else {
+                            return (false, None);
+                        };
+
+                        match signal.with_untracked(|local| resolver.remote(local, value, &fields))
+                        {
+                            Resolution::Keep => (false, None),
+                            Resolution::Set { value, reply } => {
+                                signal.set_value(value);
+                                let reply = reply
+                                    .then(|| {
+                                        signal.with_untracked(|v| message(v, resolver.current()))
+                                    })
+                                    .flatten();
+                                (true, reply)
+                            }
+                        }
+                    }
+                }),
+                snapshot: Box::new({
+                    let (resolver, message) = (resolver.clone(), message.clone());
+                    move || {
+                        let signal = weak.upgrade()?;
+                        signal.with_untracked(|v| message(v, resolver.current()))
+                    }
+                }),
+            },
+        );
+
+        let first = Cell::new(true);
+        let effect = Effect::new({
+            let (inner, signal) = (Rc::downgrade(&self.inner), signal.clone());
+            move || {
+                let Some(inner) = inner.upgrade() else {
+                    // Only track the signal.
+                    signal.with(|_| {});
+                    return;
+                };
+                let skip = first.replace(false) && !inner.host;
+                let origin = inner.origin.get();
+
+                let message = signal.with(|v| {
+                    if skip {
+                        return None;
+                    }
+                    // Changes received from a peer are forwarded as they were resolved.
+                    let fields = match origin {
+                        Some(_) => resolver.current(),
+                        None => resolver.local(v),
+                    };
+                    message(v, fields)
+                });
+                if let Some(message) = message {
+                    inner.broadcast(&message, origin);
+                }
+            }
+        });
+        self.effects.borrow_mut().push(effect);
+    }
+
+    /// Returns the number of connected processes.
+    pub fn peer_count(&self) -> usize {
+        self.inner.peers.borrow().len()
+    }
+
+    /// Welcomes newly connected processes and applies the changes received since the previous poll.
+    ///
+    /// Returns the number of changes applied.
+    pub fn poll(&self) -> usize {
+        if let Some(accepted) = &self.accepted {
+            for (id, mut peer) in accepted.try_iter() {
+                let snapshot: Vec<String> = self
+                    .inner
+                    .signals
+                    .borrow()
+                    .values()
+                    .filter_map(|shared| (shared.snapshot)())
+                    .collect();
+                if snapshot.iter().all(|m| writeln!(peer, "{m}").is_ok()) {
+                    self.inner.peers.borrow_mut().push((id, peer));
+                }
+            }
+        }
+
+        let mut applied = 0;
+        for (id, line) in self.incoming.try_iter() {
+            let Ok(Value::Object(mut message)) = serde_json::from_str(&line) else {
+                continue;
+            };
+            let Some(Value::String(name)) = message.remove("name") else {
+                continue;
+            };
+
+            // The signal may be re-shared while applying, so the entry is taken out meanwhile.
+            let Some(shared) = self.inner.signals.borrow_mut().remove(&name) else {
+                continue;
+            };
+            self.inner.origin.set(Some(id));
+            let (set, reply) = (shared.apply)(message);
+            self.inner.origin.set(None);
+            self.inner
+                .signals
+                .borrow_mut()
+                .entry(name)
+                .or_insert(shared);
+
+            applied += set as usize;
+            if let Some(reply) = reply {
+                self.inner.send(&reply, id);
+            }
+        }
+        applied
+    }
+}
+
+impl Drop for IpcSync {
+    fn drop(&mut self) {
+        if let Some((name, shutdown)) = &self.listener {
+            // Wake the accept loop up so that it notices the shutdown.
+            shutdown.store(true, Ordering::Relaxed);
+            if let Ok(name) = socket_name(name) {
+                let _ = Stream::connect(name);
+            }
+        }
+    }
+}
+
+/// Returns the socket name for `name`, in the abstract namespace where available.
+fn socket_name(name: &str) -> io::Result<Name<'static>> {
+    if GenericNamespaced::is_supported() {
+        name.to_string().to_ns_name::<GenericNamespaced>()
+    } else {
+        std::env::temp_dir()
+            .join(format!("{name}.sock"))
+            .to_fs_name::<GenericFilePath>()
+    }
+}
+
+fn accept(
+    listener: Listener,
+    accepted: Sender<(PeerId, SendHalf)>,
+    incoming: Sender<(PeerId, String)>,
+    shutdown: Arc<AtomicBool>,
+) {
+    for (id, stream) in listener.incoming().enumerate() {
+        if shutdown.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(stream) = stream else { continue };
+
+        let (recv, send) = stream.split();
+        if accepted.send((id, send)).is_err() {
+            return;
+        }
+        let incoming = incoming.clone();
+        thread::spawn(move || read(id, recv, incoming));
+    }
+}
+
+fn read(id: PeerId, recv: RecvHalf, incoming: Sender<(PeerId, String)>) {
+    for line in BufReader::new(recv).lines() {
+        let Ok(line) = line else { return };
+        if incoming.send((id, line)).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Feeding values from interrupt handlers into signals.
+//!
+//! Enabled with the `isr` feature. Signals are confined to the thread running the main
+//! loop, so an interrupt service routine must never touch them. An [`IsrQueue`] is a
+//! fixed-capacity queue guarded by [`critical_section`]: interrupt handlers
+//! [`push`](IsrQueue::push) values into it without allocating, and the main loop
+//! [`pump`](IsrQueue::pump)s them into a designated signal, running the effects there.
+//!
+//! The target must provide a `critical-section` implementation, as usual on embedded
+//! platforms; on hosted targets the `std` feature of `critical-section` provides one.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{cell::Cell, rc::Rc};
+//! use reactive_cache::{isr::IsrQueue, prelude::*};
+//!
+//! static BUTTON_PRESSES: IsrQueue<u32, 4> = IsrQueue::new();
+//!
+//! // In the interrupt handler.
+//! fn on_button_interrupt(timestamp: u32) {
+//!     // A full queue drops the press, rather than blocking the handler.
+//!     let _ = BUTTON_PRESSES.push(timestamp);
+//! }
+//!
+//! let last_press = Signal::new(0);
+//! let presses = Rc::new(Cell::new(0));
+//! let effect = Effect::new({
+//!     let (last_press, presses) = (last_press.clone(), presses.clone());
+//!     move || {
+//!         let _ = last_press.get();
+//!         presses.set(presses.get() + 1);
+//!     }
+//! });
+//!
+//! on_button_interrupt(120);
+//! on_button_interrupt(480);
+//!
+//! // In the main loop.
+//! assert_eq!(BUTTON_PRESSES.pump(&last_press), 2);
+//! assert_eq!(*last_press.get(), 480);
+//! assert_eq!(presses.get(), 3);
+//! ```
+
+use std::cell::RefCell;
+
+use critical_section::Mutex;
+
+use crate::Signal;
+
+/// A fixed-capacity queue of up to `N` values pushed by interrupt handlers.
+/// See the [module docs](self).
+pub struct IsrQueue<T, const N: usize> {
+    ring: Mutex<RefCell<Ring<T, N>>>,
+}
+
+struct Ring<T, const N: usize> {
+    slots: [Option<T>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> IsrQueue<T, N> {
+    /// Creates an empty queue, usable in a `static`.
+    pub const fn new() -> Self {
+        IsrQueue {
+            ring: Mutex::new(RefCell::new(Ring {
+                slots: [const { None }; N],
+                head: 0,
+                len: 0,
+            })),
+        }
+    }
+
+    /// Appends `value`, or returns it if the queue is full.
+    ///
+    /// Safe to call from an interrupt handler: it never allocates or blocks,
+    /// and only holds a critical section for the duration of the write.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        critical_section::with(|cs| {
+            let mut ring = self.ring.borrow_ref_mut(cs);
+            if ring.len == N {
+                return Err(value);
+            }
+            let tail = (ring.head + ring.len) % N;
+            ring.slots[tail] = Some(value);
+            ring.len += 1;
+            Ok(())
+        })
+    }
+
+    /// Removes the oldest value.
+    pub fn pop(&self) -> Option<T> {
+        critical_section::with(|cs| {
+            let mut ring = self.ring.borrow_ref_mut(cs);
+            if ring.len == 0 {
+                return None;
+            }
+            let head = ring.head;
+            ring.head = (head + 1) % N;
+            ring.len -= 1;
+            ring.slots[head].take()
+        })
+    }
+
+    /// Returns the number of values waiting to be pumped.
+    pub fn len(&self) -> usize {
+        critical_section::with(|cs| self.ring.borrow_ref(cs).len)
+    }
+
+    /// Returns `true` if no value is waiting to be pumped.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sets `signal` to every queued value in turn, oldest first, and returns how many
+    /// there were.
+    ///
+    /// Called by the main loop. The critical section is released before each value is
+    /// set, so interrupts are not held off while effects run.
+    ///
+    /// With the `nightly` feature, a value equal to the current one does not run the
+    /// effects. Without it, values are only compared by the comparator of a signal created
+    /// with [`Signal::new_with_eq`], and every other value runs them.
+    pub fn pump(&self, signal: &Signal<T>) -> usize {
+        let mut count = 0;
+        while let Some(value) = self.pop() {
+            signal.set_value(value);
+            count += 1;
+        }
+        count
+    }
+}
+
+impl<T, const N: usize> Default for IsrQueue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
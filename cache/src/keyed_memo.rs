@@ -0,0 +1,121 @@
+//! Memos taking a parameter.
+//!
+//! A [`KeyedMemo`] computes a value per key with the same function, the way a
+//! [`Memo`] computes a single value. Each key gets a memo of its own, with its own
+//! dependencies: a change only recomputes the values of the keys that read it. The memos
+//! of the least recently used keys are dropped once there are more than a fixed number
+//! of them.
+//!
+//! `#[memo]` functions with parameters are backed by a `KeyedMemo` keyed on their
+//! arguments.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{cell::Cell, rc::Rc};
+//! use reactive_cache::{KeyedMemo, prelude::*};
+//!
+//! let prices = Signal::new(vec![10, 20, 30]);
+//! let discount = Signal::new(0);
+//! let runs = Rc::new(Cell::new(0));
+//!
+//! let price = KeyedMemo::new({
+//!     let (prices, discount, runs) = (prices.clone(), discount.clone(), runs.clone());
+//!     move |&item: &usize| {
+//!         runs.set(runs.get() + 1);
+//!         prices.with(|prices| prices[item]) - *discount.get()
+//!     }
+//! });
+//!
+//! assert_eq!(price.get(0), 10);
+//! assert_eq!(price.get(2), 30);
+//! assert_eq!(price.get(0), 10);
+//! assert_eq!(runs.get(), 2);
+//!
+//! discount.set(5);
+//! assert_eq!(price.get(2), 25);
+//! assert_eq!(runs.get(), 3);
+//! ```
+
+use std::{cell::RefCell, hash::Hash, num::NonZeroUsize, rc::Rc};
+
+use lru::LruCache;
+
+use crate::Memo;
+
+const DEFAULT_CAPACITY: usize = 64;
+
+/// A memo per key, computed by the same function. See the [module docs](self).
+pub struct KeyedMemo<K, T> {
+    f: Rc<dyn Fn(&K) -> T>,
+    memos: RefCell<LruCache<K, Rc<Memo<T>>>>,
+}
+
+impl<K, T> KeyedMemo<K, T>
+where
+    K: Hash + Eq + Clone + 'static,
+    T: 'static,
+{
+    /// Creates a keyed memo computing the value of each key with `f`, keeping the
+    /// memos of the 64 most recently used keys.
+    pub fn new(f: impl Fn(&K) -> T + 'static) -> Rc<Self> {
+        Self::with_capacity(DEFAULT_CAPACITY, f)
+    }
+
+    /// Creates a keyed memo like [`KeyedMemo::new`], keeping the memos of the
+    /// `capacity` most recently used keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize, f: impl Fn(&K) -> T + 'static) -> Rc<Self> {
+        let Some(capacity) = NonZeroUsize::new(capacity) else {
+            panic!("the capacity of a keyed memo must not be zero");
+        };
+        crate::scope::owned(Rc::new(KeyedMemo {
+            f: Rc::new(f),
+            memos: RefCell::new(LruCache::new(capacity)),
+        }))
+    }
+
+    /// Returns the value of `key`, recomputing it only if necessary.
+    ///
+    /// Like [`Memo::get`], this registers the caller as a dependent of the memo of `key`.
+    #[track_caller]
+    pub fn get(&self, key: K) -> T
+    where
+        T: Clone,
+    {
+        self.memo(key).get()
+    }
+
+    /// Returns the memo of `key`, creating it if necessary.
+    ///
+    /// The memo is dropped from this keyed memo once `key` is among the least recently
+    /// used ones, but keeps working while it is held elsewhere.
+    #[track_caller]
+    pub fn memo(&self, key: K) -> Rc<Memo<T>> {
+        // The borrow ends before the memo is computed, which may read other keys.
+        let mut memos = self.memos.borrow_mut();
+        if let Some(memo) = memos.get(&key) {
+            return memo.clone();
+        }
+
+        let memo = Memo::new({
+            let (f, key) = (self.f.clone(), key.clone());
+            move || f(&key)
+        });
+        memos.put(key, memo.clone());
+        memo
+    }
+
+    /// Returns the number of keys whose memo is kept.
+    pub fn len(&self) -> usize {
+        self.memos.borrow().len()
+    }
+
+    /// Returns `true` if no key has a memo kept.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -1,22 +1,115 @@
-#![allow(incomplete_features)]
-#![feature(specialization)]
+#![cfg_attr(
+    feature = "nightly",
+    allow(incomplete_features),
+    feature(specialization)
+)]
+
+/// Declares the method of a blanket impl, specializable when the `nightly` feature is on.
+/// Without it, the blanket impl is the only one.
+#[cfg(feature = "nightly")]
+macro_rules! specializable {
+    ($(#[$attr:meta])* fn $($rest:tt)*) => {
+        $(#[$attr])* default fn $($rest)*
+    };
+}
+
+// `default fn` only appears once expanded, so that stable compilers never parse it.
+#[cfg(not(feature = "nightly"))]
+macro_rules! specializable {
+    ($(#[$attr:meta])* fn $($rest:tt)*) => {
+        $(#[$attr])* fn $($rest)*
+    };
+}
 
 pub(crate) mod cache;
 pub(crate) mod effect_stack;
+pub(crate) mod error;
 pub(crate) mod memo_stack;
+pub(crate) mod node;
 pub(crate) mod observable;
+pub(crate) mod revision;
+pub(crate) mod scheduler;
 
+pub mod animation;
+pub mod async_memo;
+pub mod boundary;
+pub mod builder;
+pub mod combinators;
+pub mod copy_signal;
+#[cfg(feature = "devtools")]
+#[cfg_attr(docsrs, doc(cfg(feature = "devtools")))]
+pub mod devtools;
+pub mod diff_memo;
 pub mod effect;
+#[cfg(feature = "ffi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
+pub mod ffi;
+pub mod inspect;
+#[cfg(feature = "inspector")]
+#[cfg_attr(docsrs, doc(cfg(feature = "inspector")))]
+pub mod inspector;
+#[cfg(feature = "ipc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ipc")))]
+pub mod ipc;
+#[cfg(feature = "isr")]
+#[cfg_attr(docsrs, doc(cfg(feature = "isr")))]
+pub mod isr;
+pub mod keyed_memo;
 pub mod macros;
 pub mod memo;
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+pub mod persist;
+#[cfg(feature = "python")]
+#[cfg_attr(docsrs, doc(cfg(feature = "python")))]
+pub mod python;
+pub mod query;
+#[cfg(feature = "remote")]
+#[cfg_attr(docsrs, doc(cfg(feature = "remote")))]
+pub mod remote;
+pub mod runtime;
+pub mod scope;
+#[cfg(feature = "scripting")]
+#[cfg_attr(docsrs, doc(cfg(feature = "scripting")))]
+pub mod scripting;
 pub mod signal;
+pub mod signal_sender;
+pub mod signal_vec;
+pub mod split_signal;
+pub mod swr_memo;
+#[cfg(feature = "sync")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
+pub mod sync;
+pub mod tag;
+pub mod task;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+#[cfg(feature = "tui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tui")))]
+pub mod tui;
+#[cfg(feature = "wasm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm")))]
+pub mod wasm;
 
-pub(crate) use cache::{remove_from_cache, store_in_cache, touch};
-pub use effect::Effect;
-pub(crate) use memo::IMemo;
-pub use memo::Memo;
-pub(crate) use observable::IObservable;
+pub use async_memo::AsyncMemo;
+pub(crate) use cache::{store_in_cache, touch};
+pub use copy_signal::CopySignal;
+pub use effect::{Effect, EffectHandle, EffectOptions, Trigger};
+pub use error::ReactiveError;
+pub use keyed_memo::KeyedMemo;
+pub use memo::{CacheCapacity, CacheSlot, Memo, MemoNode, configure_cache};
+pub use node::NodeId;
+pub use observable::{Observable, Tracker};
+pub use revision::Durability;
+pub use runtime::{Runtime, batch, drain_remote_updates, flush};
+pub use scope::{Scope, on_cleanup};
 pub use signal::{Signal, SignalSetter};
+pub use signal_sender::SignalSender;
+pub use signal_vec::{SignalVec, VecDiff};
+pub use split_signal::{ReadSignal, ReadonlySignal, WriteSignal};
+pub use swr_memo::SwrMemo;
+pub use tag::{Tag, invalidate_tag};
 
 pub use once_cell::unsync::Lazy;
 
@@ -17,6 +17,19 @@
 ///   The closure `f` will still be executed when dependencies change, but its
 ///   execution does **not** collect new dependencies.
 ///
+/// - `effect!(options; f)` and `effect!(options; f, deps)`
+///   Like the forms above, with scheduling options given as a comma-separated list,
+///   mapped onto [`EffectOptions`](crate::EffectOptions):
+///   - `defer` queues triggered runs until [`flush`](crate::flush),
+///   - `priority = n` orders effects triggered together (higher first),
+///   - `debounce = 100ms` defers runs until the signal has been quiet for the duration,
+///   - `throttle = 100ms` defers runs so that they are at least the duration apart,
+///   - `dynamic` collects dependencies again on every triggered run,
+///   - `once` disposes the effect after its first triggered run.
+///
+///   Durations use the units `ns`, `us`, `ms` and `s`; a parenthesized expression such as
+///   `debounce = (Duration::from_millis(delay))` is accepted as well.
+///
 /// # Requirements
 ///
 /// - `f` must be a closure or function pointer that takes no arguments and returns `()`.
@@ -42,7 +55,7 @@
 ///     counter_clone.set(counter_clone.get() + 1); // increment effect counter
 /// });
 ///
-/// let ptr = Rc::into_raw(e); // actively leak to avoid implicitly dropping the effect
+/// let handle = e.keep_alive(); // keep the effect alive without holding on to it
 ///
 /// // Effect runs immediately upon creation
 /// assert_eq!(counter.get(), 1);
@@ -62,10 +75,28 @@
 /// );
 /// ```
 ///
-/// # SAFETY
+/// With scheduling options:
+///
+/// ```rust
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{effect, prelude::*};
+///
+/// let query = Signal::new(String::new());
+/// let searches = Rc::new(Cell::new(0));
 ///
-/// The macro internally uses [`reactive_cache::Effect`], which relies on
-/// `static` tracking and is **not thread-safe**. Only use in single-threaded contexts.
+/// let e = effect!(defer, priority = 2, debounce = 0ms; {
+///     let (query, searches) = (query.clone(), searches.clone());
+///     move || {
+///         let _ = query.get();
+///         searches.set(searches.get() + 1);
+///     }
+/// });
+///
+/// query.set("r".into());
+/// query.set("rust".into());
+/// reactive_cache::flush();
+/// assert_eq!(searches.get(), 2);
+/// ```
 ///
 /// # Warning
 ///
@@ -83,6 +114,16 @@
 /// update signals within their own dependency chain.
 #[macro_export]
 macro_rules! effect {
+    ($($opt:ident $(= $val:tt)?),+ ; $f:expr) => {{
+        let mut options = $crate::EffectOptions::new();
+        $( $crate::__effect_option!(options, $opt $(= $val)?); )+
+        $crate::Effect::new_with_options($f, options)
+    }};
+    ($($opt:ident $(= $val:tt)?),+ ; $f:expr, $f2:expr) => {{
+        let mut options = $crate::EffectOptions::new();
+        $( $crate::__effect_option!(options, $opt $(= $val)?); )+
+        $crate::Effect::new_with_deps_and_options($f, $f2, options)
+    }};
     ($f:expr) => {
         $crate::Effect::new($f)
     };
@@ -90,3 +131,40 @@ macro_rules! effect {
         $crate::Effect::new_with_deps($f, $f2)
     };
 }
+
+/// Applies one option of the [`effect!`] macro.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __effect_option {
+    ($o:ident, defer) => {
+        $o = $o.deferred();
+    };
+    ($o:ident, dynamic) => {
+        $o = $o.dynamic();
+    };
+    ($o:ident, once) => {
+        $o = $o.once();
+    };
+    ($o:ident, priority = $p:tt) => {
+        $o = $o.priority($p);
+    };
+    ($o:ident, debounce = ($d:expr)) => {
+        $o = $o.debounce($d);
+    };
+    ($o:ident, debounce = $d:tt) => {
+        $o = $o.debounce(const { $crate::effect::parse_duration(stringify!($d)) });
+    };
+    ($o:ident, throttle = ($d:expr)) => {
+        $o = $o.throttle($d);
+    };
+    ($o:ident, throttle = $d:tt) => {
+        $o = $o.throttle(const { $crate::effect::parse_duration(stringify!($d)) });
+    };
+    ($o:ident, $($other:tt)*) => {
+        compile_error!(concat!(
+            "Unknown `effect!` option `",
+            stringify!($($other)*),
+            "`: expected `defer`, `dynamic`, `once`, `priority = n`, `debounce = <duration>` or `throttle = <duration>`."
+        ));
+    };
+}
@@ -1,9 +1,17 @@
 use std::{
-    cell::RefCell,
+    any::Any,
+    cell::{Cell, RefCell},
+    mem,
+    num::NonZeroUsize,
     rc::{Rc, Weak},
+    time::{Duration, Instant},
 };
 
-use crate::{IObservable, memo_stack, store_in_cache, touch};
+use crate::{
+    Durability, NodeId, Observable, Tag, Tracker,
+    inspect::{self, DebugValue, Event, NodeKind},
+    memo_stack, revision, runtime, store_in_cache, tag, touch,
+};
 
 /// A memoized reactive computation that caches its result and tracks dependencies.
 ///
@@ -18,7 +26,7 @@ use crate::{IObservable, memo_stack, store_in_cache, touch};
 /// # Type Parameters
 ///
 /// - `T`: The result type of the computation. Must implement `Clone`.
-/// 
+///
 /// # Memory Management Note
 ///
 /// When referencing `Memo` instances that belong to other struct instances
@@ -70,18 +78,38 @@ use crate::{IObservable, memo_stack, store_in_cache, touch};
 /// assert_eq!(vm.double.get(), 8);
 /// ```
 pub struct Memo<T> {
-    f: Box<dyn Fn() -> T>,
-    dependents: RefCell<Vec<Weak<dyn IMemo>>>,
+    /// The computation, replaced by [`Memo::set_fn`]. Called through a clone, so that it
+    /// can replace itself.
+    f: RefCell<Rc<dyn Fn() -> T>>,
+    /// Weighs the cached value against the cache budget, see [`Memo::new_weighted`].
+    weigher: Option<Weigher<T>>,
+    /// Tells whether two values are equal, see [`Memo::new_with_eq`].
+    eq: Option<Comparator<T>>,
+    slot: CacheSlot<T>,
+    /// How many times the cached value was evicted, see [`Memo::evictions`].
+    evictions: Cell<usize>,
+    /// Called when the cached value is evicted, see [`Memo::on_evict`].
+    on_evict: RefCell<Vec<EvictHandler<T>>>,
+    tracker: Tracker,
     /// A self-referential weak pointer, set during construction with `Rc::new_cyclic`.
-    /// Used to upgrade to `Rc<Memo<T>>` and then coerce into `Rc<dyn IMemo>` when needed.
+    /// Used to upgrade to `Rc<Memo<T>>` and then coerce into `Rc<dyn MemoNode>` when needed.
     weak: Weak<Memo<T>>,
 }
 
+type Weigher<T> = Box<dyn Fn(&T) -> usize>;
+
+type Comparator<T> = Box<dyn Fn(&T, &T) -> bool>;
+
+type EvictHandler<T> = Rc<dyn Fn(&Memo<T>)>;
+
 impl<T> Memo<T> {
     /// Creates a new `Memo` wrapping the provided closure.
     ///
+    /// The memo owns its cached value, which is kept until it is recomputed, unless the
+    /// memo is [bounded](Memo::set_bounded).
+    ///
     /// # Requirements
-    /// - `T` must be `'static`, because the value is stored in global cache.
+    /// - `T` must be `'static`, because the memo is registered with the runtime.
     /// - The closure must be `'static` as well.
     ///
     /// # Examples
@@ -129,15 +157,159 @@ impl<T> Memo<T> {
     /// vm.a.set(10);
     /// assert_eq!(vm.sum.get(), 13);
     /// ```
+    #[track_caller]
     pub fn new(f: impl Fn() -> T + 'static) -> Rc<Self>
     where
         T: 'static,
     {
-        Rc::new_cyclic(|weak| Memo {
-            f: Box::new(f),
-            dependents: vec![].into(),
+        Self::build(Rc::new(f), None, None)
+    }
+
+    /// Creates a new `Memo` wrapping the provided closure, attached to `tags`.
+    ///
+    /// All the memos attached to a tag can be recomputed at once with
+    /// [`invalidate_tag`](crate::invalidate_tag).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{Memo, invalidate_tag};
+    ///
+    /// let memo = Memo::new_tagged(|| 10, ["pricing", "report"]);
+    /// assert_eq!(memo.get(), 10);
+    /// assert_eq!(invalidate_tag("report"), 1);
+    /// ```
+    #[track_caller]
+    pub fn new_tagged<I>(f: impl Fn() -> T + 'static, tags: I) -> Rc<Self>
+    where
+        T: 'static,
+        I: IntoIterator,
+        I::Item: Into<Tag>,
+    {
+        let memo = Self::new(f);
+        for t in tags {
+            tag::register(t.into(), Rc::downgrade(&memo) as _);
+        }
+        memo
+    }
+
+    /// Creates a new [bounded](Memo::set_bounded) `Memo` wrapping the provided closure,
+    /// whose cached value weighs `weigher(value)` against the cache budget.
+    ///
+    /// The runtime cache keeps the total weight of the memo values it holds under
+    /// [`set_cache_budget`]. Values of memos created otherwise weigh 1. Weighing large
+    /// values by their size in bytes, for example, keeps a few huge values from sharing
+    /// the budget equally with many small ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let lines = Signal::new(vec!["fn main() {}".to_string()]);
+    /// let document = Memo::new_weighted(
+    ///     {
+    ///         let lines = lines.clone();
+    ///         move || lines.get().join("\n")
+    ///     },
+    ///     |text: &String| text.len(),
+    /// );
+    /// assert_eq!(document.get(), "fn main() {}");
+    /// ```
+    #[track_caller]
+    pub fn new_weighted(
+        f: impl Fn() -> T + 'static,
+        weigher: impl Fn(&T) -> usize + 'static,
+    ) -> Rc<Self>
+    where
+        T: 'static,
+    {
+        let memo = Self::build(Rc::new(f), Some(Box::new(weigher)), None);
+        memo.set_bounded(true);
+        memo
+    }
+
+    /// Creates a new `Memo` wrapping the provided closure, whose recomputed values are
+    /// compared with the previous one by `eq`.
+    ///
+    /// When a dependency changes but the memo recomputes a value `eq` deems equal to the
+    /// previous one, the memos and effects reading it are not run again. Memos of
+    /// `PartialEq` types get this cutoff with `==`; `eq` replaces it, for example to
+    /// compare floats approximately or structs by a key field, and provides it for types
+    /// that cannot be compared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let celsius = Signal::new(20.0_f64);
+    /// let fahrenheit = Memo::new_with_eq(
+    ///     {
+    ///         let celsius = celsius.clone();
+    ///         move || *celsius.get() * 1.8 + 32.0
+    ///     },
+    ///     |a, b| (a - b).abs() < 0.5,
+    /// );
+    ///
+    /// let renders = Rc::new(Cell::new(0));
+    /// let effect = Effect::new({
+    ///     let (fahrenheit, renders) = (fahrenheit.clone(), renders.clone());
+    ///     move || {
+    ///         let _ = fahrenheit.get();
+    ///         renders.set(renders.get() + 1);
+    ///     }
+    /// });
+    ///
+    /// // A change too small to be noticed keeps the previous value.
+    /// celsius.set(20.1);
+    /// assert_eq!(renders.get(), 1);
+    /// assert_eq!(fahrenheit.get(), 68.0);
+    ///
+    /// celsius.set(25.0);
+    /// assert_eq!(renders.get(), 2);
+    /// assert_eq!(fahrenheit.get(), 77.0);
+    /// ```
+    #[track_caller]
+    pub fn new_with_eq(
+        f: impl Fn() -> T + 'static,
+        eq: impl Fn(&T, &T) -> bool + 'static,
+    ) -> Rc<Self>
+    where
+        T: 'static,
+    {
+        Self::build(Rc::new(f), None, Some(Box::new(eq)))
+    }
+
+    #[track_caller]
+    fn build(
+        f: Rc<dyn Fn() -> T>,
+        weigher: Option<Weigher<T>>,
+        eq: Option<Comparator<T>>,
+    ) -> Rc<Self>
+    where
+        T: 'static,
+    {
+        runtime::increment(&runtime::MEMOS);
+        let tracker = Tracker::new();
+        let memo = Rc::new_cyclic(|weak| Memo {
+            f: RefCell::new(f),
+            weigher,
+            eq,
+            slot: CacheSlot::new(),
+            evictions: Cell::new(0),
+            on_evict: RefCell::new(Vec::new()),
+            tracker,
             weak: weak.clone(),
-        })
+        });
+        crate::scope::adopt(memo.clone());
+        memo
+    }
+
+    /// Returns the unique id of this memo.
+    pub fn id(&self) -> NodeId {
+        self.tracker.id()
     }
 
     /// Returns the memoized value, recomputing it only if necessary.
@@ -156,36 +328,994 @@ impl<T> Memo<T> {
     where
         T: Clone + 'static,
     {
-        self.dependency_collection();
+        self.track();
 
-        memo_stack::push(self.weak.clone());
+        (*self.value()).clone()
+    }
 
-        let rc = if let Some(this) = self.weak.upgrade() {
-            let key: Rc<dyn IMemo> = this.clone();
-            if let Some(rc) = touch(&key) {
-                rc
-            } else {
-                let result: T = (self.f)();
-                store_in_cache(&key, result)
-            }
-        } else {
+    /// Returns the memoized value, recomputing it only if necessary, without registering
+    /// the caller as a dependent.
+    ///
+    /// This mirrors [`Signal::get_untracked`](crate::Signal::get_untracked): an effect
+    /// can log or inspect a derived value without running again when it changes.
+    /// It is the same as [`Memo::snapshot_value`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::RefCell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let clicks = Signal::new(0);
+    /// let total = Memo::new({
+    ///     let clicks = clicks.clone();
+    ///     move || *clicks.get() * 10
+    /// });
+    /// let page = Signal::new("home");
+    ///
+    /// let log = Rc::new(RefCell::new(Vec::new()));
+    /// let effect = Effect::new({
+    ///     let (page, total, log) = (page.clone(), total.clone(), log.clone());
+    ///     move || log.borrow_mut().push(format!("{}: {}", page.get(), total.peek()))
+    /// });
+    ///
+    /// clicks.set(3);
+    /// page.set("cart");
+    /// assert_eq!(*log.borrow(), ["home: 0", "cart: 30"]);
+    /// ```
+    pub fn peek(&self) -> T
+    where
+        T: Clone + 'static,
+    {
+        self.snapshot_value()
+    }
+
+    /// Returns a detached copy of the memoized value.
+    ///
+    /// Unlike [`Memo::get`], reading the value this way never registers the caller
+    /// (a memo or an effect) as a dependent. The returned value is a plain copy
+    /// suitable for handing to other threads, serializers or undo stacks.
+    ///
+    /// The value is still recomputed if necessary, and the memo's own
+    /// dependencies are tracked as usual.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let counter = Signal::new(1);
+    /// let double = Memo::new({
+    ///     let counter = counter.clone();
+    ///     move || *counter.get() * 2
+    /// });
+    ///
+    /// let runs = Rc::new(Cell::new(0));
+    /// let effect = Effect::new({
+    ///     let (double, runs) = (double.clone(), runs.clone());
+    ///     move || {
+    ///         runs.set(runs.get() + 1);
+    ///         let _ = double.snapshot_value();
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(double.snapshot_value(), 2);
+    ///
+    /// // The effect did not subscribe through the snapshot.
+    /// counter.set(5);
+    /// assert_eq!(runs.get(), 1);
+    /// assert_eq!(double.snapshot_value(), 10);
+    /// ```
+    pub fn snapshot_value(&self) -> T
+    where
+        T: Clone + 'static,
+    {
+        crate::effect_stack::effect_suspended(|| (*self.value()).clone())
+    }
+
+    /// Replaces the computation of this memo with `f`, and notifies its dependents.
+    ///
+    /// The value is computed with `f` when the memo is read next, which tracks the
+    /// dependencies `f` reads in place of the previous ones. Memos and effects reading this memo are invalidated
+    /// as if one of its dependencies changed, so the graph around the memo is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let price = Signal::new(100);
+    /// let discounted = Memo::new({
+    ///     let price = price.clone();
+    ///     move || *price.get()
+    /// });
+    /// let label = Rc::new(Cell::new(0));
+    /// let effect = Effect::new({
+    ///     let (discounted, label) = (discounted.clone(), label.clone());
+    ///     move || label.set(discounted.get())
+    /// });
+    ///
+    /// // A plugin loaded at runtime swaps the pricing rule.
+    /// discounted.set_fn({
+    ///     let price = price.clone();
+    ///     move || *price.get() * 9 / 10
+    /// });
+    /// assert_eq!(label.get(), 90);
+    ///
+    /// price.set(200);
+    /// assert_eq!(label.get(), 180);
+    /// ```
+    pub fn set_fn(&self, f: impl Fn() -> T + 'static)
+    where
+        T: 'static,
+    {
+        *self.f.borrow_mut() = Rc::new(f);
+        self.tracker.mark_dirty();
+        inspect::emit(Event::Invalidated(self.id()));
+        self.notify_changing();
+        self.notify_changed();
+    }
+
+    /// Marks the cached value as outdated, so that it is recomputed when read next, and
+    /// notifies the memos and effects reading this memo.
+    ///
+    /// Use it when the memo reads state that is neither a signal nor a memo, such as
+    /// file contents or foreign data, after that state changed. Like after any change,
+    /// readers only run again if the recomputed value differs from the previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let file_len = Rc::new(Cell::new(10));
+    /// let size = Memo::new({
+    ///     let file_len = file_len.clone();
+    ///     move || file_len.get()
+    /// });
+    /// assert_eq!(size.get(), 10);
+    ///
+    /// file_len.set(20);
+    /// assert_eq!(size.get(), 10);
+    /// size.invalidate();
+    /// assert_eq!(size.get(), 20);
+    /// ```
+    pub fn invalidate(&self)
+    where
+        T: 'static,
+    {
+        // Any memo may read this one, so every memo is verified again.
+        revision::bump(Durability::High);
+        self.tracker.mark_dirty();
+        inspect::emit(Event::Invalidated(self.id()));
+        self.notify_changed();
+    }
+
+    /// Invalidates the memo like [`Memo::invalidate`], then recomputes and returns its
+    /// value, tracking it like [`Memo::get`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let seed = Rc::new(Cell::new(1_u32));
+    /// let roll = Memo::new({
+    ///     let seed = seed.clone();
+    ///     move || {
+    ///         seed.set(seed.get() * 7 % 11);
+    ///         seed.get() % 6 + 1
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(roll.get(), 2);
+    /// assert_eq!(roll.get(), 2);
+    /// assert_eq!(roll.refresh(), 6);
+    /// ```
+    pub fn refresh(&self) -> T
+    where
+        T: Clone + 'static,
+    {
+        self.invalidate();
+        self.get()
+    }
+
+    /// Returns how many times the cached value of this memo was evicted from the runtime
+    /// cache. See [`set_cache_budget`].
+    pub fn evictions(&self) -> usize {
+        self.evictions.get()
+    }
+
+    /// Registers `f` to be called with this memo whenever its cached value is evicted from
+    /// the runtime cache, after the value was dropped.
+    ///
+    /// The value is recomputed when the memo is read next. Handlers can
+    /// [pin](Memo::set_pinned) memos that are evicted too often, or recompute their value
+    /// ahead of time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{memo::set_cache_budget, prelude::*};
+    ///
+    /// let report = Memo::new(|| "expensive report".to_string());
+    /// report.set_bounded(true);
+    /// // Pin the report once it was evicted twice.
+    /// report.on_evict(|memo| {
+    ///     if memo.evictions() >= 2 {
+    ///         memo.set_pinned(true);
+    ///     }
+    /// });
+    ///
+    /// for _ in 0..3 {
+    ///     let _ = report.get();
+    ///     // Shrinking the budget evicts the report, until it is pinned.
+    ///     set_cache_budget(0);
+    ///     set_cache_budget(128);
+    /// }
+    /// assert_eq!(report.evictions(), 2);
+    /// assert!(report.is_pinned());
+    /// ```
+    pub fn on_evict(&self, f: impl Fn(&Memo<T>) + 'static) {
+        self.on_evict.borrow_mut().push(Rc::new(f));
+    }
+
+    /// Sets whether the cached value of this memo is held by the runtime cache, which
+    /// evicts it to stay within the [cache budget](set_cache_budget).
+    ///
+    /// Memos own their value by default, so that hot values are never evicted by
+    /// unrelated ones. Bounding the memos of large values keeps their total size in
+    /// check instead. See [`CacheSlot::set_bounded`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::{memo::set_cache_budget, prelude::*};
+    ///
+    /// let runs = Rc::new(Cell::new(0));
+    /// let thumbnail = Memo::new({
+    ///     let runs = runs.clone();
+    ///     move || {
+    ///         runs.set(runs.get() + 1);
+    ///         vec![0_u8; 1024]
+    ///     }
+    /// });
+    /// thumbnail.set_bounded(true);
+    ///
+    /// thumbnail.get();
+    /// set_cache_budget(0);
+    /// set_cache_budget(128);
+    /// thumbnail.get();
+    /// assert_eq!(runs.get(), 2);
+    /// ```
+    pub fn set_bounded(&self, bounded: bool)
+    where
+        T: 'static,
+    {
+        self.slot.set_bounded(bounded);
+    }
+
+    /// Returns whether the cached value of this memo is held by the runtime cache,
+    /// see [`Memo::set_bounded`].
+    pub fn is_bounded(&self) -> bool {
+        self.slot.is_bounded()
+    }
+
+    /// Sets whether the cached value of this memo is kept regardless of the cache budget.
+    ///
+    /// See [`CacheSlot::set_pinned`].
+    pub fn set_pinned(&self, pinned: bool) {
+        self.slot.set_pinned(pinned);
+    }
+
+    /// Returns whether the cached value of this memo is pinned, see [`Memo::set_pinned`].
+    pub fn is_pinned(&self) -> bool {
+        self.slot.is_pinned()
+    }
+
+    /// Returns whether the memo holds a cached value that is up to date, so that reading
+    /// it would not recompute it.
+    ///
+    /// This is `false` if the value was never computed, was evicted from the cache, or
+    /// [is dirty](Memo::is_dirty).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let count = Signal::new(1);
+    /// let double = Memo::new({
+    ///     let count = count.clone();
+    ///     move || *count.get() * 2
+    /// });
+    /// assert!(!double.is_cached());
+    ///
+    /// double.get();
+    /// assert!(double.is_cached());
+    ///
+    /// count.set(2);
+    /// assert!(!double.is_cached());
+    /// ```
+    pub fn is_cached(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.slot.peek().is_some() && !self.is_dirty()
+    }
+
+    /// Returns whether the memo was invalidated since it was last computed or verified,
+    /// because one of the signals or memos it read may have changed, or it was
+    /// [invalidated](Memo::invalidate). Nothing is computed to tell.
+    ///
+    /// A dirty memo is recomputed when read next, unless the memos it read turn out to
+    /// keep their values. A memo that was never computed is dirty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let query = Signal::new("rust");
+    /// let results = Memo::new({
+    ///     let query = query.clone();
+    ///     move || format!("results for {}", query.get())
+    /// });
+    /// results.get();
+    /// assert!(!results.is_dirty());
+    ///
+    /// // A UI can show a stale indicator before the expensive recompute.
+    /// query.set("reactive");
+    /// assert!(results.is_dirty());
+    /// assert_eq!(results.get(), "results for reactive");
+    /// assert!(!results.is_dirty());
+    /// ```
+    pub fn is_dirty(&self) -> bool {
+        self.tracker.may_be_stale()
+    }
+
+    /// Computes and caches the value if necessary, without registering anyone as a dependent.
+    pub(crate) fn warm(&self)
+    where
+        T: 'static,
+    {
+        crate::effect_stack::effect_suspended(|| {
+            self.value();
+        })
+    }
+
+    /// Returns the cached value, recomputing it if necessary,
+    /// without registering the caller as a dependent.
+    pub(crate) fn value(&self) -> Rc<T>
+    where
+        T: 'static,
+    {
+        let Some(this) = self.weak.upgrade() else {
             unreachable!()
         };
 
-        memo_stack::pop();
+        let hit = memo_stack::strict() && self.is_cached();
+        let value = this.cached(&self.slot, || self.compute());
+        if hit {
+            self.check_fresh(&value);
+        }
+        value
+    }
+
+    /// Runs the computation, without caching its result.
+    fn compute(&self) -> T {
+        let f = self.f.borrow().clone();
+        f()
+    }
+
+    /// Recomputes the value and panics if it differs from the cached `value`.
+    fn check_fresh(&self, value: &T)
+    where
+        T: 'static,
+    {
+        let fresh = crate::effect_stack::effect_suspended(|| {
+            let _computing = memo_stack::push(self.weak.clone());
+            self.compute()
+        });
+
+        assert!(
+            self.eq_values(value, &fresh)
+                .or_else(|| value.same_value(&fresh))
+                .unwrap_or(true),
+            "Memo {} created at {} may cache stale data: recomputing it gave a different value \
+             although none of its dependencies changed. \
+             It probably reads state that is neither a signal nor a memo.",
+            self.id(),
+            self.tracker.location()
+        );
+    }
+}
+
+impl<T> Drop for Memo<T> {
+    fn drop(&mut self) {
+        runtime::decrement(&runtime::MEMOS, 1);
+        crate::cache::forget(&self.slot);
+    }
+}
+
+impl<T: 'static> Observable for Memo<T> {
+    fn tracker(&self) -> &Tracker {
+        &self.tracker
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Memo
+    }
+
+    fn debug_value(&self) -> Option<String> {
+        self.slot.peek()?.debug_string()
+    }
+}
+
+impl<T: 'static> MemoNode for Memo<T> {
+    fn refresh(&self) -> bool {
+        self.warm();
+        true
+    }
+
+    fn weigh(&self, value: &dyn Any) -> usize {
+        match (&self.weigher, value.downcast_ref::<T>()) {
+            (Some(weigher), Some(value)) => weigher(value),
+            _ => 1,
+        }
+    }
+
+    fn eq_values(&self, old: &dyn Any, new: &dyn Any) -> Option<bool> {
+        let eq = self.eq.as_ref()?;
+        Some(eq(old.downcast_ref()?, new.downcast_ref()?))
+    }
+
+    fn on_evict(&self) {
+        self.evictions.set(self.evictions.get() + 1);
+        // Handlers may register more handlers, so they run from a copy.
+        let handlers = self.on_evict.borrow().clone();
+        for handler in handlers {
+            handler(self);
+        }
+    }
+}
+
+/// Sets the total weight of the memo values kept by the runtime cache.
+///
+/// Only the values of [bounded](Memo::set_bounded) memos are held by the runtime cache,
+/// other memos own their value. Values weigh 1 unless their memo was created with [`Memo::new_weighted`], so by default
+/// the budget is the number of values kept. When a value does not fit, values are evicted
+/// among the least recently used ones, starting with those that took the least time to
+/// compute per unit of weight. An evicted value is recomputed when its memo is read next.
+/// A value heavier than the whole budget is never kept.
+///
+/// The default budget is 128. To never evict values, see [`configure_cache`].
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{Memo, memo::set_cache_budget};
+///
+/// set_cache_budget(1000);
+///
+/// let computations = Rc::new(Cell::new(0));
+/// let huge = Memo::new_weighted(
+///     {
+///         let computations = computations.clone();
+///         move || {
+///             computations.set(computations.get() + 1);
+///             vec![0_u8; 4096]
+///         }
+///     },
+///     |bytes: &Vec<u8>| bytes.len(),
+/// );
+///
+/// // Too heavy to be cached.
+/// huge.get();
+/// huge.get();
+/// assert_eq!(computations.get(), 2);
+/// ```
+pub fn set_cache_budget(budget: usize) {
+    crate::cache::set_budget(budget)
+}
+
+/// How the runtime cache chooses the values it evicts, set with [`set_eviction_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EvictionPolicy {
+    /// Among the least recently used values, evicts first the one that took the least
+    /// time to compute per unit of weight.
+    #[default]
+    Lru,
+    /// Evicts first the value read the least times from the cache, so that values read
+    /// often outlive values computed for a single use.
+    Lfu,
+    /// Evicts values once they were cached for the given duration, when they are read
+    /// next or when the cache is over budget. Otherwise evicts like [`Lru`](Self::Lru).
+    Ttl(Duration),
+    /// Never evicts values, like an [unbounded](CacheCapacity::Unbounded) cache.
+    NoEviction,
+}
+
+/// Sets how the runtime cache chooses the values it evicts when it is over budget, among
+/// the values of [bounded](Memo::set_bounded) memos: other memos are never evicted.
+///
+/// The default policy is [`EvictionPolicy::Lru`]. It applies to the runtime entered,
+/// and can also be chosen when a runtime is built, see
+/// [`Runtime::with_eviction_policy`](crate::Runtime::with_eviction_policy).
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc, time::Duration};
+/// use reactive_cache::{Memo, memo::{EvictionPolicy, set_eviction_policy}};
+///
+/// set_eviction_policy(EvictionPolicy::Ttl(Duration::ZERO));
+///
+/// let computations = Rc::new(Cell::new(0));
+/// let rates = Memo::new({
+///     let computations = computations.clone();
+///     move || {
+///         computations.set(computations.get() + 1);
+///         [1.08, 0.86]
+///     }
+/// });
+/// rates.set_bounded(true);
+///
+/// // The cached value expired as soon as it was stored.
+/// rates.get();
+/// rates.get();
+/// assert_eq!(computations.get(), 2);
+/// ```
+pub fn set_eviction_policy(policy: EvictionPolicy) {
+    crate::cache::set_policy(policy)
+}
+
+/// The capacity of the runtime cache, set with [`configure_cache`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CacheCapacity {
+    /// Values are evicted once their total weight exceeds the budget, see
+    /// [`set_cache_budget`].
+    Bounded(NonZeroUsize),
+    /// Values are never evicted.
+    Unbounded,
+}
+
+impl From<NonZeroUsize> for CacheCapacity {
+    fn from(budget: NonZeroUsize) -> Self {
+        CacheCapacity::Bounded(budget)
+    }
+}
+
+/// Sets the capacity of the runtime cache, which only holds the values of memos made
+/// [bounded](Memo::set_bounded): memos are unbounded by default and never evicted.
+///
+/// The capacity is a budget or unbounded. Call it before memos are first read to keep
+/// every value the application computes, or at any time: lowering the budget evicts
+/// values right away. It applies to the runtime entered, see [`Runtime`](crate::Runtime).
+///
+/// This function is re-exported as `reactive_cache::configure_cache`.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, num::NonZeroUsize, rc::Rc};
+/// use reactive_cache::{CacheCapacity, Memo, configure_cache};
+///
+/// configure_cache(CacheCapacity::Unbounded);
+///
+/// let computations = Rc::new(Cell::new(0));
+/// let memos: Vec<_> = (0..1000)
+///     .map(|i| {
+///         let computations = computations.clone();
+///         let memo = Memo::new(move || {
+///             computations.set(computations.get() + 1);
+///             i
+///         });
+///         memo.set_bounded(true);
+///         memo
+///     })
+///     .collect();
+///
+/// // Every value stays cached.
+/// memos.iter().for_each(|memo| _ = memo.get());
+/// memos.iter().for_each(|memo| _ = memo.get());
+/// assert_eq!(computations.get(), 1000);
+///
+/// configure_cache(NonZeroUsize::new(256).unwrap());
+/// assert_eq!(reactive_cache::runtime::stats().cache_entries, 256);
+/// ```
+pub fn configure_cache(capacity: impl Into<CacheCapacity>) {
+    let budget = match capacity.into() {
+        CacheCapacity::Bounded(budget) => budget.get(),
+        CacheCapacity::Unbounded => usize::MAX,
+    };
+    crate::cache::set_budget(budget)
+}
+
+/// Sets the maximum nesting depth of memo computations.
+///
+/// A memo whose computation reads another memo nests one level deeper.
+/// When a chain of memo computations exceeds `depth`, the runtime panics with a
+/// message listing the ids of the memos involved, instead of overflowing the native stack.
+/// A memo that (directly or indirectly) reads itself is always reported as a cycle.
+///
+/// The default limit is 512.
+///
+/// # Examples
+///
+/// ```should_panic
+/// use std::rc::Rc;
+/// use reactive_cache::{Memo, memo::set_max_depth};
+///
+/// set_max_depth(2);
+///
+/// let a = Memo::new(|| 1);
+/// let b = Memo::new({
+///     let a = a.clone();
+///     move || a.get() + 1
+/// });
+/// let c = Memo::new({
+///     let b = b.clone();
+///     move || b.get() + 1
+/// });
+///
+/// // Panics: `c -> b -> a` is three memos deep.
+/// c.get();
+/// ```
+pub fn set_max_depth(depth: usize) {
+    memo_stack::set_max_depth(depth)
+}
+
+/// Enables or disables strict mode, detecting memos that read untracked state.
+///
+/// In strict mode, every read of a cached memo value recomputes it and panics if the
+/// result differs from the cached one. Since none of the memo's dependencies changed,
+/// a different result means the memo reads state that is neither a signal nor a memo,
+/// such as a `Cell` or a global, and would otherwise silently keep a stale value.
+///
+/// Only memos whose value implements `PartialEq` are checked. Strict mode doubles
+/// the cost of every cache hit, so it is meant for tests and debug builds.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "nightly", doc = "```should_panic")]
+#[cfg_attr(not(feature = "nightly"), doc = "```ignore")]
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{Memo, memo::set_strict};
+///
+/// set_strict(true);
+///
+/// let untracked = Rc::new(Cell::new(1));
+/// let double = Memo::new({
+///     let untracked = untracked.clone();
+///     move || untracked.get() * 2
+/// });
+/// assert_eq!(double.get(), 2);
+///
+/// // Panics: the memo cached a value computed from an untracked `Cell`.
+/// untracked.set(2);
+/// double.get();
+/// ```
+pub fn set_strict(enabled: bool) {
+    memo_stack::set_strict(enabled)
+}
+
+/// Compares memo values, for strict mode and to tell whether a recomputation changed the value.
+pub(crate) trait SameValue {
+    /// Returns whether both values are equal, or `None` if they cannot be compared.
+    fn same_value(&self, other: &Self) -> Option<bool>;
+}
+
+impl<T> SameValue for T {
+    specializable! {
+        fn same_value(&self, _: &Self) -> Option<bool> {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T: PartialEq> SameValue for T {
+    fn same_value(&self, other: &Self) -> Option<bool> {
+        Some(self == other)
+    }
+}
+
+/// A memoized computation node whose value is cached and invalidated by the runtime.
+///
+/// `MemoNode` is the extension point for specialized memo types (incremental,
+/// diff-producing, externally computed, ...). A node implementing it:
+/// - owns a [`CacheSlot`] holding its cached value, passed to [`MemoNode::cached`],
+/// - is registered as a dependent of every [`Observable`] read inside [`MemoNode::cached`],
+/// - is notified through [`MemoNode::on_invalidate`] when one of those observables changes,
+/// - has its cached value verified against those observables before it is reused,
+///   and recomputed only if one of them changed since.
+///
+/// When a recomputation gives a value equal to the previous one, the memos reading the node
+/// keep their cached values as well (early cutoff). Values that cannot be compared are
+/// always considered changed.
+///
+/// [`Memo`] itself is implemented on top of this trait.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{CacheSlot, MemoNode, Observable, Tracker, prelude::*};
+///
+/// /// A memo that counts how often it was invalidated.
+/// struct Doubled {
+///     source: Rc<Signal<i32>>,
+///     invalidations: Cell<u32>,
+///     value: CacheSlot<i32>,
+///     tracker: Tracker,
+/// }
+///
+/// impl Observable for Doubled {
+///     fn tracker(&self) -> &Tracker {
+///         &self.tracker
+///     }
+/// }
+///
+/// impl MemoNode for Doubled {
+///     fn on_invalidate(&self) {
+///         self.invalidations.set(self.invalidations.get() + 1);
+///     }
+/// }
+///
+/// impl Doubled {
+///     fn get(self: &Rc<Self>) -> i32 {
+///         self.track();
+///         *self.cached(&self.value, || *self.source.get() * 2)
+///     }
+/// }
+///
+/// let source = Signal::new(1);
+/// let doubled = Rc::new(Doubled {
+///     source: source.clone(),
+///     invalidations: Cell::new(0),
+///     value: CacheSlot::new(),
+///     tracker: Tracker::new(),
+/// });
+///
+/// // Custom nodes compose with regular memos.
+/// let plus_one = Memo::new({
+///     let doubled = doubled.clone();
+///     move || doubled.get() + 1
+/// });
+///
+/// assert_eq!(plus_one.get(), 3);
+/// source.set(5);
+/// assert_eq!(doubled.invalidations.get(), 1);
+/// assert_eq!(plus_one.get(), 11);
+/// ```
+pub trait MemoNode: Observable + 'static {
+    /// Called when an observable read directly by this node changed, so that its cached value
+    /// is recomputed when read next.
+    ///
+    /// It is not called when only memos read by this node may have changed: those are
+    /// verified when this node is read, which recomputes it only if one of them did change.
+    fn on_invalidate(&self) {}
+
+    /// Brings the cached value of this node up to date, recomputing it if necessary,
+    /// and returns `true`; or returns `false` if the node cannot do so on its own.
+    ///
+    /// Memos reading this node call it to find out whether its value changed, and
+    /// reuse their own cached values if it did not. The default returns `false`, in which
+    /// case those memos are recomputed whenever this node may have changed.
+    fn refresh(&self) -> bool {
+        false
+    }
+
+    /// Returns the weight of `value`, the cached value of this node, against the cache
+    /// budget. See [`set_cache_budget`].
+    ///
+    /// The default weighs every value 1.
+    fn weigh(&self, value: &dyn Any) -> usize {
+        let _ = value;
+        1
+    }
+
+    /// Returns whether `old` and `new`, two values of this node, are equal, or `None` to
+    /// compare them with `PartialEq` if their type implements it.
+    ///
+    /// A recomputed value equal to the previous one does not run the memos and effects
+    /// reading this node again. Values that cannot be compared are always considered
+    /// changed. The default returns `None`.
+    fn eq_values(&self, old: &dyn Any, new: &dyn Any) -> Option<bool> {
+        let _ = (old, new);
+        None
+    }
+
+    /// Called when the cached value of this node was evicted from the runtime cache to
+    /// stay within the [cache budget](set_cache_budget), after its slot was emptied.
+    ///
+    /// The value is recomputed when the node is read next; its dependents are not
+    /// notified, since the value did not change. Values dropped for other reasons, such as
+    /// a dependency change, do not call this hook.
+    fn on_evict(&self) {}
+
+    /// Returns the value cached in `slot`, the slot of this node, computing it with `f`
+    /// if necessary.
+    ///
+    /// Observables read inside `f` register this node as their dependent.
+    /// This does **not** register the caller as a dependent of this node;
+    /// call [`Observable::track`] first for that.
+    fn cached<T: 'static>(self: &Rc<Self>, slot: &CacheSlot<T>, f: impl FnOnce() -> T) -> Rc<T>
+    where
+        Self: Sized,
+    {
+        let key: Rc<dyn MemoNode> = self.clone();
+        let tracker = self.tracker();
+
+        let previous = touch(slot);
+        // While an Effect collects its dependencies, the memos it reads are recomputed,
+        // otherwise the underlying Signals would not remember the Effect. The previous
+        // value is kept for comparison, so that an equal value does not invalidate others.
+        let collecting = crate::effect_stack::effect_peak().is_some_and(|e| e.collecting);
+        if let Some(rc) = &previous
+            && !collecting
+            && tracker.verify()
+        {
+            return rc.clone();
+        }
+
+        // The stale value stays cached, to be overwritten in place without allocating.
+        let started = tracker.begin_computation(&key);
+        let computing = memo_stack::push(Rc::downgrade(&key));
+        let start = Instant::now();
+        let value = f();
+        let cost = start.elapsed();
+        drop(computing);
+
+        let changed = previous.as_ref().is_none_or(|p| {
+            self.eq_values(&**p, &value)
+                .or_else(|| (**p).same_value(&value))
+                != Some(true)
+        });
+        tracker.end_computation(started, changed);
+        let rc = match previous {
+            // An equal value keeps the previous one, which dependents already read.
+            Some(p) if !changed && slot.peek().is_some_and(|s| Rc::ptr_eq(&s, &p)) => p,
+            previous => {
+                drop(previous);
+                store_in_cache(&key, slot, value, cost)
+            }
+        };
+        inspect::emit(Event::Recomputed(key.id()));
+        rc
+    }
+}
+
+/// The cached value of a [`MemoNode`], filled by [`MemoNode::cached`].
+///
+/// Every node owns its slot, so cached values keep their type. The value is kept until the
+/// node recomputes it or is dropped, unless the slot is [bounded](CacheSlot::set_bounded):
+/// the runtime cache then tracks how recently the value was used, and empties the slot to
+/// stay within the [cache budget](set_cache_budget).
+pub struct CacheSlot<T> {
+    value: Rc<Slot<T>>,
+}
+
+struct Slot<T> {
+    value: RefCell<Option<Rc<T>>>,
+    bounded: Cell<bool>,
+    pinned: Cell<bool>,
+}
+
+impl<T> CacheSlot<T> {
+    /// Creates an empty slot.
+    pub fn new() -> Self {
+        CacheSlot {
+            value: Rc::new(Slot {
+                value: RefCell::new(None),
+                bounded: Cell::new(false),
+                pinned: Cell::new(false),
+            }),
+        }
+    }
+
+    /// Returns the cached value, if any, without computing or verifying it.
+    pub fn peek(&self) -> Option<Rc<T>> {
+        self.value.value.borrow().clone()
+    }
+
+    /// Returns whether the value of this slot is held by the runtime cache,
+    /// see [`CacheSlot::set_bounded`].
+    pub fn is_bounded(&self) -> bool {
+        self.value.bounded.get()
+    }
+
+    /// Sets whether the value of this slot is kept regardless of the cache budget.
+    ///
+    /// A pinned value still counts towards the budget, so other values are evicted in its
+    /// place, but it is only dropped when its node recomputes it or is dropped.
+    pub fn set_pinned(&self, pinned: bool) {
+        self.value.pinned.set(pinned);
+    }
+
+    /// Returns whether the value of this slot is pinned, see [`CacheSlot::set_pinned`].
+    pub fn is_pinned(&self) -> bool {
+        self.value.pinned.get()
+    }
+
+    /// Stores `value`, returning it.
+    ///
+    /// The previous value is overwritten in place when the slot holds the only reference
+    /// to it, so recomputing a memo whose value is not shared does not allocate.
+    pub(crate) fn replace(&self, value: T) -> Rc<T> {
+        let mut slot = self.value.value.borrow_mut();
+        if let Some(rc) = slot.as_mut()
+            && let Some(previous) = Rc::get_mut(rc)
+        {
+            let previous = mem::replace(previous, value);
+            let rc = Rc::clone(rc);
+            // Dropped once the slot is no longer borrowed, in case it reads a memo.
+            drop(slot);
+            drop(previous);
+            return rc;
+        }
+
+        let rc = Rc::new(value);
+        let previous = slot.replace(Rc::clone(&rc));
+        drop(slot);
+        drop(previous);
+        rc
+    }
+}
+
+impl<T> CacheSlot<T> {
+    /// Identifies this slot in the runtime cache.
+    pub(crate) fn key(&self) -> *const () {
+        Rc::as_ptr(&self.value) as *const ()
+    }
+}
+
+impl<T: 'static> CacheSlot<T> {
+    /// Sets whether the value of this slot is held by the runtime cache, which evicts it
+    /// to stay within the [cache budget](set_cache_budget). Slots are unbounded when
+    /// created.
+    ///
+    /// A value stored before the slot was bounded is only held by the runtime cache once
+    /// it is recomputed. Unbounding the slot keeps its value.
+    pub fn set_bounded(&self, bounded: bool) {
+        self.value.bounded.set(bounded);
+        if !bounded {
+            crate::cache::forget(self);
+        }
+    }
 
-        (*rc).clone()
+    pub(crate) fn downgrade(&self) -> Weak<dyn Evict> {
+        Rc::downgrade(&self.value) as _
     }
 }
 
-impl<T> IObservable for Memo<T> {
-    fn dependents(&self) -> &RefCell<Vec<Weak<dyn IMemo>>> {
-        &self.dependents
+impl<T> Default for CacheSlot<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-/// Internal marker trait for all memoized computations.
-/// Used for type erasure when storing heterogeneous `Memo<T>` in caches.
-pub(crate) trait IMemo: IObservable {}
+/// Empties a [`CacheSlot`] whose value is evicted.
+pub(crate) trait Evict {
+    fn clear(&self);
 
-impl<T> IMemo for Memo<T> {}
+    fn is_pinned(&self) -> bool;
+}
+
+impl<T> Evict for Slot<T> {
+    fn clear(&self) {
+        let value = self.value.borrow_mut().take();
+        drop(value);
+    }
+
+    fn is_pinned(&self) -> bool {
+        self.pinned.get()
+    }
+}
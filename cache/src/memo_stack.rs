@@ -1,21 +1,116 @@
-#![allow(static_mut_refs)]
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
 
-use std::rc::Weak;
+use crate::{MemoNode, NodeId};
 
-use once_cell::sync::Lazy;
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 512;
 
-use crate::IMemo;
+thread_local! {
+    static MEMO_STACK: RefCell<Vec<Weak<dyn MemoNode>>> = const { RefCell::new(Vec::new()) };
 
-static mut MEMO_STACK: Lazy<Vec<Weak<dyn IMemo>>> = Lazy::new(Vec::new);
+    static MAX_DEPTH: Cell<usize> = const { Cell::new(DEFAULT_MAX_DEPTH) };
 
-pub(crate) fn push(op: Weak<dyn IMemo>) {
-    unsafe { MEMO_STACK.push(op) }
+    static STRICT: Cell<bool> = const { Cell::new(false) };
 }
 
-pub(crate) fn last() -> Option<&'static Weak<dyn IMemo>> {
-    unsafe { MEMO_STACK.last() }
+/// The memo stack and its settings in a runtime that is not entered,
+/// see [`Runtime`](crate::Runtime).
+pub(crate) struct State {
+    stack: Vec<Weak<dyn MemoNode>>,
+    max_depth: usize,
+    strict: bool,
 }
 
-pub(crate) fn pop() -> Option<Weak<dyn IMemo>> {
-    unsafe { MEMO_STACK.pop() }
+impl Default for State {
+    fn default() -> Self {
+        State {
+            stack: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            strict: false,
+        }
+    }
+}
+
+/// Exchanges the memo stack and its settings with those of the runtime being entered
+/// or left.
+pub(crate) fn swap(state: &mut State) {
+    MEMO_STACK.with_borrow_mut(|stack| std::mem::swap(stack, &mut state.stack));
+    state.max_depth = MAX_DEPTH.replace(state.max_depth);
+    state.strict = STRICT.replace(state.strict);
+}
+
+pub(crate) fn set_max_depth(depth: usize) {
+    MAX_DEPTH.set(depth)
+}
+
+pub(crate) fn set_strict(enabled: bool) {
+    STRICT.set(enabled)
+}
+
+pub(crate) fn strict() -> bool {
+    STRICT.get()
+}
+
+/// Pops the memo pushed by [`push`] when its computation returns or unwinds, so that a
+/// panic does not leave it on the stack.
+#[must_use]
+pub(crate) struct Computing;
+
+impl Drop for Computing {
+    fn drop(&mut self) {
+        MEMO_STACK.with_borrow_mut(Vec::pop);
+    }
+}
+
+/// Pushes `op` as the memo being computed until the returned guard is dropped.
+///
+/// Panics, without pushing it, if `op` is already being computed or the stack is full.
+pub(crate) fn push(op: Weak<dyn MemoNode>) -> Computing {
+    if MEMO_STACK.with_borrow(|stack| stack.iter().any(|m| Weak::ptr_eq(m, &op))) {
+        let mut cycle = nodes();
+        cycle.reverse();
+        cycle.extend(op.upgrade());
+        panic!("Memo dependency cycle detected: {}", format_path(&cycle));
+    }
+
+    let max_depth = MAX_DEPTH.get();
+    if depth() >= max_depth {
+        let mut chain = nodes();
+        chain.reverse();
+        panic!(
+            "Memo nesting depth exceeded the limit of {max_depth}: {}",
+            format_path(&chain)
+        );
+    }
+
+    MEMO_STACK.with_borrow_mut(|stack| stack.push(op));
+    Computing
+}
+
+pub(crate) fn last() -> Option<Weak<dyn MemoNode>> {
+    MEMO_STACK.with_borrow(|stack| stack.last().cloned())
+}
+
+/// The number of memos currently being computed.
+pub(crate) fn depth() -> usize {
+    MEMO_STACK.with_borrow(Vec::len)
+}
+
+/// Ids of the memos currently being computed, innermost first.
+pub(crate) fn path() -> Vec<NodeId> {
+    nodes().iter().map(|m| m.id()).collect()
+}
+
+/// The memos currently being computed, innermost first.
+fn nodes() -> Vec<Rc<dyn MemoNode>> {
+    MEMO_STACK.with_borrow(|stack| stack.iter().rev().filter_map(Weak::upgrade).collect())
+}
+
+fn format_path(path: &[Rc<dyn MemoNode>]) -> String {
+    path.iter()
+        .map(|m| format!("{} ({})", m.id(), m.tracker().location()))
+        .collect::<Vec<_>>()
+        .join(" -> ")
 }
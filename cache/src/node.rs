@@ -0,0 +1,42 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// An opaque identifier of a reactive node (`Signal`, `Memo` or `Effect`).
+///
+/// Every node receives a unique id at creation. Ids are never reused,
+/// so they can be compared and stored after the node itself has been dropped.
+///
+/// # Examples
+///
+/// ```
+/// use reactive_cache::prelude::*;
+///
+/// let a = Signal::new(1);
+/// let b = Signal::new(1);
+/// assert_ne!(a.id(), b.id());
+/// assert_eq!(a.id(), a.id());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub(crate) fn next() -> Self {
+        NodeId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Returns the raw value of this id, for serialization.
+    #[cfg_attr(not(feature = "devtools"), allow(dead_code))]
+    pub(crate) fn get(self) -> usize {
+        self.0
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
@@ -1,33 +1,539 @@
-use std::{cell::RefCell, rc::Weak};
+use std::{
+    cell::RefCell,
+    panic::Location,
+    rc::{Rc, Weak},
+};
 
-use crate::{IMemo, memo_stack, remove_from_cache};
+use crate::{
+    Durability, Effect, MemoNode, NodeId,
+    effect_stack::EffectStackEntry,
+    inspect::{self, Event, NodeKind},
+    memo_stack,
+    revision::{self, Revision, Revisions},
+    runtime::{self, EDGES},
+    scheduler,
+};
 
-pub(crate) trait IObservable {
-    fn dependents(&self) -> &RefCell<Vec<Weak<dyn IMemo>>>;
+/// A source node that participates in dependency tracking.
+///
+/// `Observable` is the extension point for building custom reactive primitives
+/// (for example a GPU-buffer-backed value) that behave like a [`Signal`](crate::Signal):
+/// memos and effects reading the node become its dependents, and changing the
+/// node invalidates those memos and re-runs those effects.
+///
+/// Implementors only need to store a [`Tracker`] and return it from [`Observable::tracker`].
+/// The node must then:
+/// - call [`Observable::track`] on every read,
+/// - call [`Observable::notify_changing`] before its value changes,
+/// - call [`Observable::notify_changed`] after its value changed.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{Observable, Tracker, prelude::*};
+///
+/// #[derive(Default)]
+/// struct Counter {
+///     count: Cell<u32>,
+///     tracker: Tracker,
+/// }
+///
+/// impl Observable for Counter {
+///     fn tracker(&self) -> &Tracker {
+///         &self.tracker
+///     }
+/// }
+///
+/// impl Counter {
+///     fn get(&self) -> u32 {
+///         self.track();
+///         self.count.get()
+///     }
+///
+///     fn increment(&self) {
+///         self.notify_changing();
+///         self.count.set(self.count.get() + 1);
+///         self.notify_changed();
+///     }
+/// }
+///
+/// let counter = Rc::new(Counter::default());
+/// let double = Memo::new({
+///     let counter = counter.clone();
+///     move || counter.get() * 2
+/// });
+///
+/// let seen = Rc::new(Cell::new(0));
+/// let effect = Effect::new({
+///     let (double, seen) = (double.clone(), seen.clone());
+///     move || seen.set(double.get())
+/// });
+///
+/// counter.increment();
+/// assert_eq!(double.get(), 2);
+/// assert_eq!(seen.get(), 2);
+/// ```
+pub trait Observable {
+    /// Returns the dependency tracking state of this node.
+    fn tracker(&self) -> &Tracker;
 
-    /// Invalidates all dependent observables.
+    /// Returns the unique id of this node.
+    fn id(&self) -> NodeId {
+        self.tracker().id()
+    }
+
+    /// Registers the currently running memo or effect as a dependent of this node.
+    fn track(&self) {
+        self.tracker().track()
+    }
+
+    /// Starts a new revision and notifies the memos reading this node directly.
+    ///
+    /// Memos reading it through other memos are verified when they are read next.
+    fn notify_changing(&self) {
+        self.tracker().invalidate()
+    }
+
+    /// Re-runs all effects depending on this node.
+    fn notify_changed(&self) {
+        self.tracker().flush_effects()
+    }
+
+    /// Registers `f` to be called when this node is dropped.
+    ///
+    /// Owners can release resources associated with the node, such as GPU buffers,
+    /// file handles or registry entries, exactly when the node is destroyed.
+    /// Callbacks run in registration order, after the value of the node was dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::{Observable, prelude::*};
+    ///
+    /// let released = Rc::new(Cell::new(false));
+    /// let texture = Signal::new(vec![0_u8; 16]);
+    /// texture.on_drop({
+    ///     let released = released.clone();
+    ///     move || released.set(true)
+    /// });
+    ///
+    /// let handle = texture.clone();
+    /// drop(texture);
+    /// assert!(!released.get());
+    ///
+    /// drop(handle);
+    /// assert!(released.get());
+    /// ```
+    fn on_drop(&self, f: impl FnOnce() + 'static)
+    where
+        Self: Sized,
+    {
+        self.tracker().on_drop(f)
+    }
+
+    /// Returns the kind of this node, as reported by [`inspect`](crate::inspect).
+    fn kind(&self) -> NodeKind {
+        NodeKind::Signal
+    }
+
+    /// Returns a `Debug` rendering of the current value, as reported by
+    /// [`inspect`](crate::inspect), or `None` if the value cannot be shown.
+    fn debug_value(&self) -> Option<String> {
+        None
+    }
+}
+
+/// The dependents of an [`Observable`] node.
+///
+/// Stores weak references to the memos and effects that read the node,
+/// so tracking never keeps them alive.
+pub struct Tracker {
+    id: NodeId,
+
+    /// The runtime the node was created in, see [`Runtime`](crate::Runtime).
+    runtime: usize,
+
+    /// Where the node owning this tracker was created.
+    location: &'static Location<'static>,
+
+    /// When this node changed and was verified, and the memos and effects depending on
+    /// it, shared with the memos and effects reading it.
+    revisions: Rc<Revisions>,
+
+    /// The nodes read by the last computation of this node, if it is a memo.
+    sources: RefCell<Vec<Weak<Revisions>>>,
+
+    /// The nodes read by the previous computation, while this memo is computed.
+    previous_sources: RefCell<Vec<Weak<Revisions>>>,
+
+    /// Callbacks run when the node is dropped.
+    on_drop: RefCell<Vec<DropHook>>,
+}
+
+pub(crate) type DropHook = Box<dyn FnOnce()>;
+
+impl Default for Tracker {
+    #[track_caller]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tracker {
+    /// Creates a tracker without dependents, with a fresh node id.
+    ///
+    /// The caller is recorded as the creation site of the node, see [`Tracker::location`].
+    #[track_caller]
+    pub fn new() -> Self {
+        Tracker {
+            id: NodeId::next(),
+            runtime: runtime::current_id(),
+            location: Location::caller(),
+            revisions: Default::default(),
+            sources: Default::default(),
+            previous_sources: Default::default(),
+            on_drop: Default::default(),
+        }
+    }
+
+    /// Returns the id of the node owning this tracker.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Returns where the node owning this tracker was created.
+    ///
+    /// Constructors of nodes are `#[track_caller]`, so this is the line that called
+    /// `Signal::new`, `Memo::new` and the like, which identifies nodes without names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{Observable, prelude::*};
+    ///
+    /// let (signal, line) = (Signal::new(1), line!());
+    /// assert_eq!(signal.tracker().location().file(), file!());
+    /// assert_eq!(signal.tracker().location().line(), line);
+    /// ```
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Registers `f` to be called when this tracker, and so its node, is dropped.
+    ///
+    /// See [`Observable::on_drop`].
+    pub fn on_drop(&self, f: impl FnOnce() + 'static) {
+        self.on_drop.borrow_mut().push(Box::new(f));
+    }
+
+    /// Returns the live memos depending on this node.
+    pub(crate) fn dependent_memos(&self) -> Vec<Rc<dyn MemoNode>> {
+        self.revisions
+            .dependents
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+
+    /// Returns the live effects depending on this node.
+    pub(crate) fn dependent_effects(&self) -> Vec<Rc<Effect>> {
+        self.revisions
+            .effects
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+
+    /// Returns how often this node is expected to change.
+    ///
+    /// For a memo, this is the lowest durability of the nodes read by its last computation.
+    pub fn durability(&self) -> Durability {
+        self.revisions.durability.get()
+    }
+
+    /// Sets how often this node is expected to change. See [`Durability`].
+    ///
+    /// The durability of memos is derived from their dependencies whenever they are computed.
+    pub fn set_durability(&self, durability: Durability) {
+        self.revisions.durability.set(durability);
+    }
+
+    /// Starts a new revision and notifies the memos reading this node directly.
     fn invalidate(&self) {
-        self.dependents().borrow_mut().retain(|d| {
+        runtime::assert_runtime(self.runtime);
+
+        let revision = revision::bump(self.durability());
+        self.revisions.changed_at.set(revision);
+
+        self.revisions.dependents.borrow_mut().retain(|d| {
             if let Some(d) = d.upgrade() {
-                remove_from_cache(&d);
-                d.invalidate();
+                inspect::emit(Event::Invalidated(d.id()));
+                d.on_invalidate();
                 true
             } else {
+                runtime::decrement(&EDGES, 1);
                 false
             }
         });
     }
 
+    /// Returns whether the cached value of this memo is still up to date,
+    /// bringing the memos it read up to date first.
+    ///
+    /// A memo is up to date if none of the nodes read by its last computation changed
+    /// since it was last verified. Verification is skipped if no node as durable as
+    /// the memo changed meanwhile.
+    pub(crate) fn verify(&self) -> bool {
+        let revisions = &self.revisions;
+        let verified_at = revisions.verified_at.get();
+        let current = revision::current();
+        if verified_at == current {
+            return true;
+        }
+        // A memo that was never computed, or was marked dirty, must be recomputed.
+        // A value cached for it may also belong to a dropped memo at the same address.
+        if verified_at == 0 {
+            return false;
+        }
+
+        if revision::last_changed(revisions.durability.get()) > verified_at {
+            // Refreshing a source may read this memo's sources again, so they are not
+            // borrowed across it. They are not cloned either, to keep reads allocation-free.
+            for i in 0.. {
+                let Some(source) = self.sources.borrow().get(i).cloned() else {
+                    break;
+                };
+                let Some(source) = source.upgrade() else {
+                    return false;
+                };
+                let node = source.node.borrow().clone();
+                if let Some(node) = node {
+                    let Some(node) = node.upgrade() else {
+                        return false;
+                    };
+                    if !node.refresh() && !node.tracker().verify() {
+                        return false;
+                    }
+                }
+
+                if source.changed_at.get() > verified_at {
+                    return false;
+                }
+            }
+        }
+
+        revisions.verified_at.set(current);
+        true
+    }
+
+    /// Returns whether the cached value of this memo may be outdated, because it was
+    /// marked dirty or a node read by its last computation may have changed since it was
+    /// last verified. Unlike [`Tracker::verify`], nothing is computed.
+    pub(crate) fn may_be_stale(&self) -> bool {
+        let revisions = &self.revisions;
+        let verified_at = revisions.verified_at.get();
+        if verified_at == 0 {
+            return true;
+        }
+        if verified_at == revision::current()
+            || revision::last_changed(revisions.durability.get()) <= verified_at
+        {
+            return false;
+        }
+
+        self.sources.borrow().iter().any(|source| {
+            let Some(source) = source.upgrade() else {
+                return true;
+            };
+            if source.changed_at.get() > verified_at {
+                return true;
+            }
+            let node = source.node.borrow().clone();
+            node.is_some_and(|node| node.upgrade().is_none_or(|n| n.tracker().may_be_stale()))
+        })
+    }
+
+    /// Forces this memo to be recomputed when it is read next.
+    pub(crate) fn mark_dirty(&self) {
+        self.revisions.verified_at.set(0);
+    }
+
+    /// Prepares this memo for a computation, forgetting the nodes read by the previous one.
+    ///
+    /// Those nodes stop notifying this memo when the computation ends, unless it reads
+    /// them again.
+    ///
+    /// The memo stays dirty until the computation ends, so a stale value left in the cache
+    /// by a computation that panicked is never reused.
+    ///
+    /// Returns the revision the computation starts in.
+    pub(crate) fn begin_computation(&self, node: &Rc<dyn MemoNode>) -> Revision {
+        self.revisions
+            .node
+            .borrow_mut()
+            .get_or_insert_with(|| Rc::downgrade(node));
+        // The nodes of a computation that panicked are kept as well, to be released by this one.
+        let mut previous = self.previous_sources.borrow_mut();
+        for source in self.sources.borrow_mut().drain(..) {
+            if !previous.iter().any(|p| Weak::ptr_eq(p, &source)) {
+                previous.push(source);
+            }
+        }
+        self.mark_dirty();
+        revision::current()
+    }
+
+    /// Records the end of a computation of this memo started in revision `started`,
+    /// which `changed` its value or not.
+    ///
+    /// The value is only known to be up to date as of `started`, in case an asynchronous
+    /// computation read a node that changed before it completed.
+    pub(crate) fn end_computation(&self, started: Revision, changed: bool) {
+        let current = revision::current();
+        let revisions = &self.revisions;
+        revisions.verified_at.set(started);
+        if changed {
+            revisions.changed_at.set(current);
+        }
+
+        let durability = self
+            .sources
+            .borrow()
+            .iter()
+            .map(|s| s.upgrade().map_or(Durability::Low, |s| s.durability.get()))
+            .min()
+            .unwrap_or(Durability::High);
+        revisions.durability.set(durability);
+
+        self.release_previous_sources();
+    }
+
+    /// Removes this memo from the dependents of the nodes its previous computation read,
+    /// but the last one did not.
+    fn release_previous_sources(&self) {
+        let Some(node) = self.revisions.node.borrow().clone() else {
+            return;
+        };
+        let sources = self.sources.borrow();
+        for previous in self.previous_sources.borrow_mut().drain(..) {
+            if sources.iter().any(|s| Weak::ptr_eq(s, &previous)) {
+                continue;
+            }
+            if let Some(previous) = previous.upgrade() {
+                unlink(&previous, &node);
+            }
+        }
+    }
+
     /// Track observables in the call stack
     fn dependency_collection(&self) {
-        if let Some(last) = memo_stack::last()
-            && !self
-                .dependents()
-                .borrow()
-                .iter()
-                .any(|d| Weak::ptr_eq(d, last))
+        let Some(last) = memo_stack::last() else {
+            return;
+        };
+
         {
-            self.dependents().borrow_mut().push(last.clone());
+            let mut dependents = self.revisions.dependents.borrow_mut();
+            if !dependents.iter().any(|d| Weak::ptr_eq(d, &last)) {
+                dependents.push(last.clone());
+                runtime::increment(&EDGES);
+            }
+        }
+
+        if let Some(reader) = last.upgrade() {
+            let revisions = Rc::downgrade(&self.revisions);
+            let mut sources = reader.tracker().sources.borrow_mut();
+            if !sources.iter().any(|s| Weak::ptr_eq(s, &revisions)) {
+                sources.push(revisions);
+            }
+        }
+    }
+
+    /// Registers the memo and effect currently in the call stack as dependents.
+    fn track(&self) {
+        runtime::assert_runtime(self.runtime);
+        self.dependency_collection();
+
+        // Track effects in the call stack
+        let Some(EffectStackEntry {
+            effect: e,
+            collecting: true,
+        }) = crate::effect_stack::effect_peak()
+        else {
+            return;
+        };
+        let effects = &self.revisions.effects;
+        if !effects.borrow().iter().any(|w| Weak::ptr_eq(w, &e)) {
+            effects.borrow_mut().push(e.clone());
+            runtime::increment(&EDGES);
+            if let Some(e) = e.upgrade() {
+                e.record_source(self.id, Rc::downgrade(&self.revisions), memo_stack::path());
+            }
+        }
+        if memo_stack::depth() == 0
+            && let Some(e) = e.upgrade()
+        {
+            e.record_read(Rc::downgrade(&self.revisions));
+        }
+    }
+
+    /// Re-runs all dependent effects that are still alive, or queues the deferred ones.
+    ///
+    /// Dead effects (already dropped) are cleaned up automatically.
+    fn flush_effects(&self) {
+        inspect::emit(Event::Changed(self.id));
+
+        // When triggering an Effect, dependencies are not collected for that Effect.
+        let mut effects = scheduler::buffer();
+        self.revisions.effects.borrow_mut().retain(|w| {
+            if let Some(e) = w.upgrade() {
+                e.record_trigger(self.id);
+                effects.push(w.clone());
+                true
+            } else {
+                runtime::decrement(&EDGES, 1);
+                false
+            }
+        });
+
+        scheduler::schedule(effects);
+    }
+}
+
+/// Removes `node` from the dependents of `source`.
+fn unlink(source: &Revisions, node: &Weak<dyn MemoNode>) {
+    source.dependents.borrow_mut().retain(|d| {
+        let stale = Weak::ptr_eq(d, node);
+        if stale {
+            runtime::decrement(&EDGES, 1);
+        }
+        !stale
+    });
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        inspect::forget(self.id);
+        runtime::decrement(
+            &EDGES,
+            self.revisions.dependents.borrow().len() + self.revisions.effects.borrow().len(),
+        );
+
+        // A dropped memo no longer depends on the nodes it read.
+        let node = self.revisions.node.borrow().clone();
+        if let Some(node) = node {
+            for source in self.sources.get_mut().drain(..) {
+                if let Some(source) = source.upgrade() {
+                    unlink(&source, &node);
+                }
+            }
+        }
+
+        for f in self.on_drop.get_mut().drain(..) {
+            f();
         }
     }
 }
@@ -0,0 +1,181 @@
+//! Memo values persisted across runs of an application.
+//!
+//! Enabled with the `persist` feature. A [`CacheStore`] keeps the values of named memos in
+//! a JSON file, together with a fingerprint of the inputs each value was computed from.
+//! After a restart, a memo created with [`CacheStore::memo`] reuses the stored value as
+//! long as the fingerprint of its inputs matches, instead of running an expensive
+//! computation again, the way build tools reuse the outputs of unchanged inputs.
+//!
+//! Fingerprints hash the inputs with a fixed function, so they are stable across runs and
+//! builds on the same platform. Values and inputs are only as stable as their
+//! `Serialize` and `Hash` implementations: changing those, or the computation itself,
+//! calls for a new memo name or a fresh store.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{cell::Cell, rc::Rc};
+//! use reactive_cache::{persist::CacheStore, prelude::*};
+//!
+//! let path = std::env::temp_dir().join("reactive-cache-persist-example.json");
+//! # let _ = std::fs::remove_file(&path);
+//! let source = Signal::new("fn main() {}".to_string());
+//! let compilations = Rc::new(Cell::new(0));
+//!
+//! let run = || {
+//!     let store = CacheStore::open(&path).unwrap();
+//!     let binary = store.memo(
+//!         "binary",
+//!         {
+//!             let source = source.clone();
+//!             move || source.get().clone()
+//!         },
+//!         {
+//!             let compilations = compilations.clone();
+//!             move |source: String| {
+//!                 compilations.set(compilations.get() + 1);
+//!                 source.len()
+//!             }
+//!         },
+//!     );
+//!     let size = binary.get();
+//!     store.save().unwrap();
+//!     size
+//! };
+//!
+//! assert_eq!(run(), 12);
+//! // A restart with the same source reuses the stored value.
+//! assert_eq!(run(), 12);
+//! assert_eq!(compilations.get(), 1);
+//!
+//! source.set("fn main() { run() }".to_string());
+//! assert_eq!(run(), 19);
+//! assert_eq!(compilations.get(), 2);
+//! # let _ = std::fs::remove_file(&path);
+//! ```
+
+use std::{
+    cell::RefCell,
+    fs,
+    hash::{Hash, Hasher},
+    io::{self, ErrorKind},
+    path::PathBuf,
+    rc::Rc,
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+
+use crate::Memo;
+
+/// Memo values stored in a JSON file. See the [module docs](self).
+pub struct CacheStore {
+    path: PathBuf,
+    /// The stored entries by memo name, each holding a `fingerprint` and a `value`.
+    entries: RefCell<Map<String, Value>>,
+}
+
+impl CacheStore {
+    /// Opens the store kept in the file at `path`, which is created on the first save.
+    ///
+    /// Returns an error if the file cannot be read, or does not hold a store.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Rc<Self>> {
+        let path = path.into();
+        let entries = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Map::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Rc::new(CacheStore {
+            path,
+            entries: RefCell::new(entries),
+        }))
+    }
+
+    /// Writes the stored values to the file of this store.
+    ///
+    /// Values computed since the store was opened are only persisted once it is saved.
+    /// Entries of memos not created during this run are kept.
+    pub fn save(&self) -> io::Result<()> {
+        let json = serde_json::to_vec(&*self.entries.borrow())?;
+        fs::write(&self.path, json)
+    }
+
+    /// Creates a memo named `name` computing its value with `compute` from the value
+    /// returned by `inputs`.
+    ///
+    /// `inputs` reads the signals and memos the value depends on, and is called whenever
+    /// one of them changed. `compute` is only called if the store holds no value computed
+    /// from equal inputs, and its result is stored under `name`. Dependencies read by
+    /// `compute` itself are tracked as well, but are not part of the fingerprint.
+    #[track_caller]
+    pub fn memo<K, T>(
+        self: &Rc<Self>,
+        name: impl Into<String>,
+        inputs: impl Fn() -> K + 'static,
+        compute: impl Fn(K) -> T + 'static,
+    ) -> Rc<Memo<T>>
+    where
+        K: Hash,
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        let (store, name) = (self.clone(), name.into());
+        Memo::new(move || {
+            let inputs = inputs();
+            let mut hasher = Fnv::default();
+            inputs.hash(&mut hasher);
+            let fingerprint = hasher.finish();
+
+            if let Some(value) = store.lookup(&name, fingerprint) {
+                return value;
+            }
+            let value = compute(inputs);
+            store.record(&name, fingerprint, &value);
+            value
+        })
+    }
+
+    /// Returns the value stored under `name` if it was computed from inputs with `fingerprint`.
+    fn lookup<T: DeserializeOwned>(&self, name: &str, fingerprint: u64) -> Option<T> {
+        let entries = self.entries.borrow();
+        let entry = entries.get(name)?;
+        if entry["fingerprint"].as_u64() != Some(fingerprint) {
+            return None;
+        }
+        // A value that no longer deserializes, for example after its type changed, is recomputed.
+        T::deserialize(&entry["value"]).ok()
+    }
+
+    fn record<T: Serialize>(&self, name: &str, fingerprint: u64, value: &T) {
+        let Ok(value) = serde_json::to_value(value) else {
+            return;
+        };
+        self.entries.borrow_mut().insert(
+            name.to_owned(),
+            json!({ "fingerprint": fingerprint, "value": value }),
+        );
+    }
+}
+
+/// The FNV-1a hash function, whose output does not depend on the run or the build,
+/// unlike the hasher of the standard library.
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x100000001b3);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Python bindings.
+//!
+//! Enabled with the `python` feature. Exposes `Signal`, `Memo` and `Effect` to Python
+//! through [pyo3](https://pyo3.rs), so Python scripts embedded in a Rust application
+//! can observe and mutate the same reactive state the Rust side uses.
+//!
+//! - [`PySignal`] wraps either a Python-created signal holding arbitrary Python objects,
+//!   or an existing Rust `Rc<Signal<T>>` (via `From`) whose values convert to and from Python.
+//! - [`PyMemo`] wraps a Python callable as a memo. Exceptions raised by the callable
+//!   are cached like values and re-raised on every `get()` until the memo is invalidated.
+//! - [`PyEffect`] wraps a Python callable as an effect. Exceptions raised by the
+//!   callable are printed, since there is no caller to propagate them to.
+//!
+//! Register the classes with [`reactive_cache()`], for example with
+//! `pyo3::append_to_inittab!(reactive_cache)` before initializing the interpreter.
+//!
+//! # SAFETY
+//!
+//! All classes are `unsendable`: like the rest of the crate they are confined to the
+//! thread that created them, and pyo3 raises an error if another thread touches them.
+//!
+//! # Examples
+//!
+//! ```
+//! use pyo3::{ffi::c_str, prelude::*};
+//! use reactive_cache::{prelude::*, python::PySignal};
+//!
+//! let counter = Signal::new(1_i64);
+//!
+//! Python::initialize();
+//! Python::attach(|py| {
+//!     let module = PyModule::new(py, "reactive_cache").unwrap();
+//!     reactive_cache::python::reactive_cache(&module).unwrap();
+//!
+//!     let globals = pyo3::types::PyDict::new(py);
+//!     globals.set_item("rc", module).unwrap();
+//!     globals.set_item("counter", PySignal::from(counter.clone())).unwrap();
+//!
+//!     py.run(
+//!         c_str!(
+//!             "
+//! seen = []
+//! double = rc.Memo(lambda: counter.get() * 2)
+//! effect = rc.Effect(lambda: seen.append(double.get()))
+//! counter.set(5)
+//! "
+//!         ),
+//!         Some(&globals),
+//!         None,
+//!     )
+//!     .unwrap();
+//!
+//!     let seen: Vec<i64> = globals.get_item("seen").unwrap().unwrap().extract().unwrap();
+//!     assert_eq!(seen, [2, 10]);
+//! });
+//!
+//! // The Python side wrote through to the Rust signal.
+//! assert_eq!(*counter.get(), 5);
+//! ```
+
+use std::{convert::Infallible, rc::Rc};
+
+use pyo3::{IntoPyObjectExt, conversion::FromPyObjectOwned, prelude::*};
+
+use crate::{Effect, Memo, Signal};
+
+/// A Python object held by a reactive node.
+///
+/// Values are compared with Python `==`; a comparison that raises counts as "changed".
+pub struct PyValue(Py<PyAny>);
+
+impl Clone for PyValue {
+    fn clone(&self) -> Self {
+        Python::attach(|py| PyValue(self.0.clone_ref(py)))
+    }
+}
+
+impl PartialEq for PyValue {
+    fn eq(&self, other: &Self) -> bool {
+        Python::attach(|py| self.0.bind(py).eq(other.0.bind(py)).unwrap_or(false))
+    }
+}
+
+impl Eq for PyValue {}
+
+impl<'py> IntoPyObject<'py> for PyValue {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = Infallible;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(self.0.into_bound(py))
+    }
+}
+
+impl<'a, 'py> FromPyObject<'a, 'py> for PyValue {
+    type Error = Infallible;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> Result<Self, Self::Error> {
+        Ok(PyValue(obj.to_owned().unbind()))
+    }
+}
+
+/// Type-erased access to a signal whose values convert to and from Python.
+trait DynSignal {
+    fn get(&self, py: Python<'_>) -> PyResult<Py<PyAny>>;
+    fn set(&self, value: &Bound<'_, PyAny>) -> PyResult<bool>;
+}
+
+impl<T> DynSignal for Signal<T>
+where
+    T: Clone + for<'py> IntoPyObject<'py> + for<'py> FromPyObjectOwned<'py>,
+{
+    fn get(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        Signal::get(self).clone().into_py_any(py)
+    }
+
+    fn set(&self, value: &Bound<'_, PyAny>) -> PyResult<bool> {
+        Ok(self.set_value(value.extract().map_err(Into::into)?))
+    }
+}
+
+/// A `Signal` accessible from Python as `Signal(value)`.
+#[pyclass(unsendable, name = "Signal")]
+pub struct PySignal(Rc<dyn DynSignal>);
+
+#[pymethods]
+impl PySignal {
+    /// Creates a signal holding an arbitrary Python object.
+    #[new]
+    fn new(value: Py<PyAny>) -> Self {
+        PySignal(Signal::new(PyValue(value)))
+    }
+
+    /// Returns the current value, tracking it as a dependency.
+    fn get(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        self.0.get(py)
+    }
+
+    /// Sets the value, returning `True` if it changed.
+    ///
+    /// Without the `nightly` feature, values of a signal wrapped with `From` are only
+    /// compared by the comparator of `Signal::new_with_eq`, and `True` is returned otherwise.
+    fn set(&self, value: &Bound<'_, PyAny>) -> PyResult<bool> {
+        self.0.set(value)
+    }
+}
+
+impl<T> From<Rc<Signal<T>>> for PySignal
+where
+    T: Clone + for<'py> IntoPyObject<'py> + for<'py> FromPyObjectOwned<'py> + 'static,
+{
+    fn from(signal: Rc<Signal<T>>) -> Self {
+        PySignal(signal)
+    }
+}
+
+/// A `Memo` accessible from Python as `Memo(callable)`.
+#[pyclass(unsendable, name = "Memo")]
+pub struct PyMemo(Rc<Memo<Result<PyValue, Rc<PyErr>>>>);
+
+#[pymethods]
+impl PyMemo {
+    /// Creates a memo computed by calling `f` without arguments.
+    #[new]
+    fn new(f: Py<PyAny>) -> Self {
+        PyMemo(Memo::new(move || {
+            Python::attach(|py| f.call0(py).map(PyValue).map_err(Rc::new))
+        }))
+    }
+
+    /// Returns the memoized value, recomputing it only if necessary.
+    fn get(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        match self.0.get() {
+            Ok(value) => Ok(value.0),
+            Err(err) => Err(err.clone_ref(py)),
+        }
+    }
+}
+
+/// An `Effect` accessible from Python as `Effect(callable)`.
+///
+/// The effect stays active as long as the Python object is alive.
+#[pyclass(unsendable, name = "Effect")]
+pub struct PyEffect(#[allow(dead_code)] Rc<Effect>);
+
+#[pymethods]
+impl PyEffect {
+    /// Creates an effect that calls `f` now and whenever its dependencies change.
+    #[new]
+    fn new(f: Py<PyAny>) -> Self {
+        PyEffect(Effect::new(move || {
+            Python::attach(|py| {
+                if let Err(err) = f.call0(py) {
+                    err.print(py);
+                }
+            })
+        }))
+    }
+}
+
+/// Adds the `Signal`, `Memo` and `Effect` classes to a Python module.
+#[pymodule]
+pub fn reactive_cache(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySignal>()?;
+    m.add_class::<PyMemo>()?;
+    m.add_class::<PyEffect>()?;
+    Ok(())
+}
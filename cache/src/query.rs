@@ -0,0 +1,367 @@
+//! Keyed queries over an external data source.
+//!
+//! A [`QueryClient`] fetches values identified by a key, remembers the results and
+//! exposes the state of each query through signals. Memos and effects reading a
+//! [`Query`] are tracked like with any other signal, so derived values are cached
+//! until the query resolves again and effects re-run when it does.
+//!
+//! Fetching is left to the application: the fetcher receives a [`QueryResolver`]
+//! that may be resolved immediately or later, for example once a request
+//! completes on the application's event loop.
+//!
+//! Results are cached by the client rather than in the runtime cache of memos: a client
+//! given a [capacity](QueryClient::set_capacity) evicts its least recently requested
+//! queries, like the runtime cache evicts the values of bounded memos.
+
+use std::{
+    cell::{Cell, RefCell},
+    hash::Hash,
+    rc::{Rc, Weak},
+};
+
+use lru::LruCache;
+
+use crate::{CacheCapacity, Signal, SignalSetter};
+
+/// The state of a [`Query`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryStatus {
+    /// The first fetch has not resolved yet.
+    Loading,
+    /// The last fetch succeeded.
+    Success,
+    /// The last fetch failed.
+    Error,
+}
+
+/// The state of one key of a [`QueryClient`].
+///
+/// Every accessor reads a signal, so it is tracked when called from a memo or an effect.
+pub struct Query<V, E> {
+    status: Rc<Signal<QueryStatus>>,
+    data: Rc<Signal<Option<Rc<V>>>>,
+    error: Rc<Signal<Option<Rc<E>>>>,
+    fetching: Rc<Signal<bool>>,
+    /// Identifies the current fetch; results of superseded fetches are ignored.
+    generation: Cell<u64>,
+}
+
+impl<V, E> Query<V, E> {
+    fn new() -> Rc<Self>
+    where
+        V: 'static,
+        E: 'static,
+    {
+        Rc::new(Query {
+            status: Signal::new(QueryStatus::Loading),
+            data: Signal::new(None),
+            error: Signal::new(None),
+            fetching: Signal::new(false),
+            generation: Cell::new(0),
+        })
+    }
+
+    /// Returns the state of the query.
+    pub fn status(&self) -> QueryStatus {
+        *self.status.get()
+    }
+
+    /// Returns the value of the last successful fetch.
+    ///
+    /// The previous value is kept while the query is refetched and when a later fetch fails.
+    pub fn data(&self) -> Option<Rc<V>> {
+        self.data.get().clone()
+    }
+
+    /// Returns the error of the last fetch, if it failed.
+    pub fn error(&self) -> Option<Rc<E>> {
+        self.error.get().clone()
+    }
+
+    /// Returns `true` while a fetch is in flight.
+    pub fn is_fetching(&self) -> bool {
+        *self.fetching.get()
+    }
+
+    fn start(self: &Rc<Self>) -> QueryResolver<V, E> {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        self.fetching.set(true);
+
+        QueryResolver {
+            query: Rc::downgrade(self),
+            generation,
+        }
+    }
+}
+
+/// Completes one fetch started by a [`QueryClient`].
+///
+/// Dropping the resolver without resolving it leaves the query fetching
+/// until it is invalidated.
+pub struct QueryResolver<V, E> {
+    query: Weak<Query<V, E>>,
+    generation: u64,
+}
+
+impl<V, E> QueryResolver<V, E> {
+    /// Stores the result of the fetch in the query.
+    ///
+    /// The result is ignored if the query was invalidated or removed since this fetch started.
+    /// Effects reading the query run once it is stored, and never observe it half updated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::RefCell, rc::Rc};
+    /// use reactive_cache::{prelude::*, query::{QueryClient, QueryStatus}};
+    ///
+    /// let pending = Rc::new(RefCell::new(None));
+    /// let client = QueryClient::new({
+    ///     let pending = pending.clone();
+    ///     move |_: &(), resolver| *pending.borrow_mut() = Some(resolver)
+    /// });
+    ///
+    /// let query = client.query(());
+    /// let seen = Rc::new(RefCell::new(Vec::new()));
+    /// let _effect = Effect::new({
+    ///     let (query, seen) = (query.clone(), seen.clone());
+    ///     move || seen.borrow_mut().push((query.status(), query.data()))
+    /// });
+    ///
+    /// pending.take().unwrap().resolve(Ok::<_, ()>(7));
+    /// assert_eq!(
+    ///     *seen.borrow(),
+    ///     [(QueryStatus::Loading, None), (QueryStatus::Success, Some(Rc::new(7)))]
+    /// );
+    /// ```
+    pub fn resolve(self, result: Result<V, E>) {
+        let Some(query) = self.query.upgrade() else {
+            return;
+        };
+        if query.generation.get() != self.generation {
+            return;
+        }
+
+        // Effects run once every signal is set, so they observe the new data and status together.
+        crate::batch(|| {
+            query.fetching.set(false);
+            match result {
+                Ok(value) => {
+                    query.data.set_value(Some(Rc::new(value)));
+                    query.error.set_value(None);
+                    query.status.set(QueryStatus::Success);
+                }
+                Err(error) => {
+                    query.error.set_value(Some(Rc::new(error)));
+                    query.status.set(QueryStatus::Error);
+                }
+            }
+        });
+    }
+}
+
+type Fetcher<K, V, E> = Box<dyn Fn(&K, QueryResolver<V, E>)>;
+
+/// Fetches and caches values identified by a key.
+///
+/// Each key is fetched once, when its query is first requested. Requesting it
+/// again returns the same [`Query`], even while the fetch is still in flight,
+/// until [`invalidate_query`](Self::invalidate_query) fetches it anew.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use reactive_cache::{prelude::*, query::{QueryClient, QueryResolver, QueryStatus}};
+///
+/// // Requests complete later, when the application polls for them.
+/// let pending: Rc<RefCell<Vec<(u32, QueryResolver<String, String>)>>> = Default::default();
+/// let client = QueryClient::new({
+///     let pending = pending.clone();
+///     move |id: &u32, resolver| pending.borrow_mut().push((*id, resolver))
+/// });
+///
+/// let user = client.query(1);
+/// let title = Memo::new({
+///     let user = user.clone();
+///     move || match user.status() {
+///         QueryStatus::Loading => "loading".to_string(),
+///         QueryStatus::Success => format!("user {}", user.data().unwrap()),
+///         QueryStatus::Error => "failed".to_string(),
+///     }
+/// });
+/// assert_eq!(title.get(), "loading");
+///
+/// // The second request is deduplicated with the one in flight.
+/// assert!(Rc::ptr_eq(&client.query(1), &user));
+/// assert_eq!(pending.borrow().len(), 1);
+///
+/// let (id, resolver) = pending.borrow_mut().pop().unwrap();
+/// resolver.resolve(Ok(format!("#{id}")));
+/// assert_eq!(title.get(), "user #1");
+///
+/// // Invalidating refetches while keeping the previous data.
+/// assert!(client.invalidate_query(&1));
+/// assert!(user.is_fetching());
+/// assert_eq!(title.get(), "user #1");
+///
+/// let (_, resolver) = pending.borrow_mut().pop().unwrap();
+/// resolver.resolve(Err("offline".to_string()));
+/// assert_eq!(title.get(), "failed");
+/// assert_eq!(user.data().as_deref().map(String::as_str), Some("#1"));
+/// ```
+pub struct QueryClient<K, V, E> {
+    fetcher: Fetcher<K, V, E>,
+    /// The queries, in order of recency.
+    queries: RefCell<LruCache<K, Rc<Query<V, E>>>>,
+    capacity: Cell<usize>,
+}
+
+impl<K, V, E> QueryClient<K, V, E>
+where
+    K: Clone + Eq + Hash,
+    V: 'static,
+    E: 'static,
+{
+    /// Creates a client fetching keys with `fetcher`.
+    ///
+    /// The fetcher must eventually resolve the given [`QueryResolver`].
+    /// It may do so before returning.
+    pub fn new(fetcher: impl Fn(&K, QueryResolver<V, E>) + 'static) -> Self {
+        QueryClient {
+            fetcher: Box::new(fetcher),
+            queries: RefCell::new(LruCache::unbounded()),
+            capacity: Cell::new(usize::MAX),
+        }
+    }
+
+    /// Sets how many queries the client keeps. Queries are kept without limit by default.
+    ///
+    /// Once there are more, the least recently requested queries are removed like with
+    /// [`remove_query`](Self::remove_query), except those still held outside the client:
+    /// requesting their key again fetches it anew.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, num::NonZeroUsize, rc::Rc};
+    /// use reactive_cache::query::QueryClient;
+    ///
+    /// let fetches = Rc::new(Cell::new(0));
+    /// let client = QueryClient::new({
+    ///     let fetches = fetches.clone();
+    ///     move |id: &u32, resolver| {
+    ///         fetches.set(fetches.get() + 1);
+    ///         resolver.resolve(Ok::<_, ()>(id * 10));
+    ///     }
+    /// });
+    /// client.set_capacity(NonZeroUsize::new(2).unwrap());
+    ///
+    /// let held = client.query(1);
+    /// client.query(2);
+    /// client.query(3);
+    ///
+    /// // The query for 2 was evicted, while the one for 1 is still held.
+    /// assert!(client.get_query(&2).is_none());
+    /// assert!(Rc::ptr_eq(&client.query(1), &held));
+    /// client.query(2);
+    /// assert_eq!(fetches.get(), 4);
+    /// ```
+    pub fn set_capacity(&self, capacity: impl Into<CacheCapacity>) {
+        self.capacity.set(match capacity.into() {
+            CacheCapacity::Bounded(capacity) => capacity.get(),
+            CacheCapacity::Unbounded => usize::MAX,
+        });
+        self.evict();
+    }
+
+    /// Returns the query for `key`, starting to fetch it if it was never requested.
+    pub fn query(&self, key: K) -> Rc<Query<V, E>> {
+        if let Some(query) = self.get_query(&key) {
+            return query;
+        }
+
+        let query = Query::new();
+        self.queries.borrow_mut().put(key.clone(), query.clone());
+        self.fetch(&key, &query);
+        self.evict();
+        query
+    }
+
+    /// Returns the query for `key` without fetching it, marking it as recently requested.
+    pub fn get_query(&self, key: &K) -> Option<Rc<Query<V, E>>> {
+        self.queries.borrow_mut().get(key).cloned()
+    }
+
+    /// Fetches `key` again, superseding any fetch in flight.
+    ///
+    /// The query keeps its data until the new fetch resolves.
+    /// Returns `false` if the key was never requested.
+    pub fn invalidate_query(&self, key: &K) -> bool {
+        match self.get_query(key) {
+            Some(query) => {
+                self.fetch(key, &query);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Fetches again every query whose key matches `predicate`.
+    ///
+    /// Returns the number of invalidated queries.
+    pub fn invalidate_queries(&self, mut predicate: impl FnMut(&K) -> bool) -> usize {
+        let matching: Vec<_> = self
+            .queries
+            .borrow()
+            .iter()
+            .filter(|(key, _)| predicate(key))
+            .map(|(key, query)| (key.clone(), query.clone()))
+            .collect();
+
+        for (key, query) in &matching {
+            self.fetch(key, query);
+        }
+        matching.len()
+    }
+
+    /// Forgets the query for `key`, ignoring the result of any fetch in flight.
+    ///
+    /// Returns the removed query, which keeps its last state.
+    pub fn remove_query(&self, key: &K) -> Option<Rc<Query<V, E>>> {
+        let query = self.queries.borrow_mut().pop(key)?;
+        Self::forget(&query);
+        Some(query)
+    }
+
+    /// Removes the least recently requested queries held only by the client, until at
+    /// most the capacity is left.
+    fn evict(&self) {
+        let evicted: Vec<_> = {
+            let mut queries = self.queries.borrow_mut();
+            let excess = queries.len().saturating_sub(self.capacity.get());
+            let keys: Vec<K> = queries
+                .iter()
+                .rev()
+                .filter(|(_, query)| Rc::strong_count(query) == 1)
+                .take(excess)
+                .map(|(key, _)| key.clone())
+                .collect();
+            keys.iter().filter_map(|key| queries.pop(key)).collect()
+        };
+        // The queries are dropped once the borrow is released, in case dropping them
+        // runs effects requesting other keys.
+        evicted.iter().for_each(Self::forget);
+    }
+
+    fn forget(query: &Rc<Query<V, E>>) {
+        query.generation.set(query.generation.get() + 1);
+        query.fetching.set(false);
+    }
+
+    fn fetch(&self, key: &K, query: &Rc<Query<V, E>>) {
+        // No borrow is held here, so the fetcher may resolve immediately or request other keys.
+        (self.fetcher)(key, query.start());
+    }
+}
@@ -0,0 +1,384 @@
+//! Publishing signals over WebSocket to read-only mirrors in other processes.
+//!
+//! Enabled with the `remote` feature. A [`SignalServer`] publishes chosen signals under
+//! a name to every connected WebSocket client. On the client side, a [`SignalMirror`]
+//! turns the received messages into memos following the published signals, so
+//! dashboards and secondary windows can derive state from them like from local signals.
+//!
+//! The server accepts connections on a background thread, but only reads signals on
+//! the thread owning them: changes are sent when it calls [`SignalServer::flush`],
+//! typically once per frame or event-loop iteration.
+//!
+//! [`RemoteClient`] connects a mirror to a server on native targets. A mirror does not
+//! depend on any transport, so a WASM build feeds it the text of every message received
+//! by a browser `WebSocket` with [`SignalMirror::apply`] instead.
+//!
+//! # Protocol
+//!
+//! The server only sends messages; anything received from a client is ignored.
+//! Every message is a WebSocket text frame holding one JSON object, naming the signal
+//! and holding its value serialized with `serde`:
+//!
+//! ```json
+//! {"name": "temperature", "value": 21.5}
+//! ```
+//!
+//! A client receives the current value of every published signal on the first flush after
+//! it connected, then the new value of each signal that changed before every later flush.
+//!
+//! # Examples
+//!
+//! ```
+//! use reactive_cache::{
+//!     prelude::*,
+//!     remote::{RemoteClient, SignalServer},
+//! };
+//!
+//! let server = SignalServer::bind("127.0.0.1:0").unwrap();
+//! let temperature = Signal::new(21.5);
+//! server.publish("temperature", &temperature);
+//!
+//! let client = RemoteClient::connect(format!("ws://{}", server.local_addr())).unwrap();
+//! let mirrored = client.subscribe("temperature", 0.0);
+//! let label = Memo::new({
+//!     let mirrored = mirrored.clone();
+//!     move || format!("{:.1} °C", mirrored.get())
+//! });
+//!
+//! while server.client_count() == 0 {
+//!     std::thread::yield_now();
+//! }
+//! server.flush();
+//! while label.get() != "21.5 °C" {
+//!     client.poll();
+//! }
+//!
+//! temperature.set(23.0);
+//! server.flush();
+//! while label.get() != "23.0 °C" {
+//!     client.poll();
+//! }
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+    rc::{Rc, Weak},
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+#[cfg(not(target_arch = "wasm32"))]
+use tungstenite::{Message, WebSocket, stream::MaybeTlsStream};
+
+use crate::{Effect, Memo, Signal};
+
+#[cfg(not(target_arch = "wasm32"))]
+struct Client {
+    socket: WebSocket<TcpStream>,
+    /// Whether the client has not received the published values yet.
+    fresh: bool,
+}
+
+type Snapshot = Box<dyn Fn() -> Option<Value>>;
+
+/// Publishes signals to WebSocket clients. See the [module docs](self).
+///
+/// Dropping the server closes all connections and stops the background thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct SignalServer {
+    addr: SocketAddr,
+    clients: Arc<Mutex<Vec<Client>>>,
+    shutdown: Arc<AtomicBool>,
+    published: RefCell<HashMap<String, Snapshot>>,
+    /// Names of the signals that changed since the previous flush.
+    changed: Rc<RefCell<BTreeSet<String>>>,
+    effects: RefCell<Vec<Rc<Effect>>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SignalServer {
+    /// Starts listening on `addr` and accepting clients on a background thread.
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        thread::spawn({
+            let (clients, shutdown) = (clients.clone(), shutdown.clone());
+            move || accept(listener, clients, shutdown)
+        });
+
+        Ok(SignalServer {
+            addr,
+            clients,
+            shutdown,
+            published: Default::default(),
+            changed: Default::default(),
+            effects: Default::default(),
+        })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the number of connected clients.
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// Publishes `signal` under `name`.
+    ///
+    /// Only a weak reference is kept: publishing a signal never keeps it alive.
+    pub fn publish<T>(&self, name: &str, signal: &Rc<Signal<T>>)
+    where
+        T: Serialize + 'static,
+    {
+        let weak = Rc::downgrade(signal);
+        self.published.borrow_mut().insert(
+            name.to_string(),
+            Box::new({
+                let weak = weak.clone();
+                move || {
+                    let signal = weak.upgrade()?;
+                    signal.with_untracked(|v| serde_json::to_value(v).ok())
+                }
+            }),
+        );
+
+        let effect = Effect::new_with_deps(
+            {
+                let (changed, name) = (self.changed.clone(), name.to_string());
+                move || {
+                    changed.borrow_mut().insert(name.clone());
+                }
+            },
+            move || {
+                if let Some(signal) = weak.upgrade() {
+                    signal.with(|_| {});
+                }
+            },
+        );
+        // The initial value is part of the snapshot sent to new clients.
+        self.changed.borrow_mut().remove(name);
+        self.effects.borrow_mut().push(effect);
+    }
+
+    /// Sends the published values to newly connected clients,
+    /// and the values that changed since the previous flush to the others.
+    ///
+    /// Clients whose connection failed are dropped.
+    pub fn flush(&self) {
+        let changed = std::mem::take(&mut *self.changed.borrow_mut());
+        let mut clients = self.clients.lock().unwrap();
+        if clients.is_empty() {
+            return;
+        }
+
+        let published = self.published.borrow();
+        let message = |name: &String| {
+            let value = published.get(name)?();
+            Some(json!({ "name": name, "value": value }).to_string())
+        };
+        let changes: Vec<String> = changed.iter().filter_map(message).collect();
+        let snapshot: Vec<String> = match clients.iter().any(|c| c.fresh) {
+            true => published.keys().filter_map(message).collect(),
+            false => vec![],
+        };
+
+        clients.retain_mut(|client| {
+            let messages = match std::mem::replace(&mut client.fresh, false) {
+                true => &snapshot,
+                false => &changes,
+            };
+            messages
+                .iter()
+                .try_for_each(|m| client.socket.send(Message::text(m.as_str())))
+                .is_ok()
+        });
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for SignalServer {
+    fn drop(&mut self) {
+        // Wake the accept loop up so that it notices the shutdown.
+        self.shutdown.store(true, Ordering::Relaxed);
+        let _ = TcpStream::connect(self.addr);
+
+        for mut client in self.clients.lock().unwrap().drain(..) {
+            let _ = client.socket.close(None);
+            let _ = client.socket.flush();
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn accept(listener: TcpListener, clients: Arc<Mutex<Vec<Client>>>, shutdown: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if shutdown.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(stream) = stream else { continue };
+
+        // Handshake on a separate thread, so a stalled client cannot block others.
+        let (clients, shutdown) = (clients.clone(), shutdown.clone());
+        thread::spawn(move || {
+            if let Ok(socket) = tungstenite::accept(stream)
+                && !shutdown.load(Ordering::Relaxed)
+            {
+                clients.lock().unwrap().push(Client {
+                    socket,
+                    fresh: true,
+                });
+            }
+        });
+    }
+}
+
+type Apply = Box<dyn Fn(Value) -> bool>;
+
+/// Read-only mirrors of signals published by a [`SignalServer`].
+///
+/// # Examples
+///
+/// ```
+/// use reactive_cache::remote::SignalMirror;
+///
+/// let mirror = SignalMirror::new();
+/// let online = mirror.subscribe("online", false);
+///
+/// // In a WASM build, this is the text of a message received by a `WebSocket`.
+/// assert!(mirror.apply(r#"{"name": "online", "value": true}"#));
+/// assert!(online.get());
+/// ```
+#[derive(Default)]
+pub struct SignalMirror {
+    signals: RefCell<HashMap<String, Apply>>,
+}
+
+impl SignalMirror {
+    /// Creates a mirror without subscriptions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a memo following the signal published under `name`,
+    /// holding `initial` until its value is received.
+    ///
+    /// Subscribing again to the same name replaces the previous subscription.
+    ///
+    /// Without the `nightly` feature, received values are not compared with the current
+    /// one, so every value received invalidates the dependents of the memo.
+    pub fn subscribe<T>(&self, name: &str, initial: T) -> Rc<Memo<T>>
+    where
+        T: DeserializeOwned + Clone + 'static,
+    {
+        let signal = Signal::new(initial);
+        let weak: Weak<Signal<T>> = Rc::downgrade(&signal);
+        self.signals.borrow_mut().insert(
+            name.to_string(),
+            Box::new(
+                move |value| match (weak.upgrade(), serde_json::from_value(value)) {
+                    (Some(signal), Ok(value)) => {
+                        signal.set_value(value);
+                        true
+                    }
+                    _ => false,
+                },
+            ),
+        );
+
+        Memo::new(move || signal.get().clone())
+    }
+
+    /// Applies a message received from the server.
+    ///
+    /// Returns `false` if the message is malformed, names a signal that is not subscribed to,
+    /// or holds a value that cannot be deserialized.
+    pub fn apply(&self, message: &str) -> bool {
+        let Ok(Value::Object(mut message)) = serde_json::from_str(message) else {
+            return false;
+        };
+        let (Some(Value::String(name)), Some(value)) =
+            (message.remove("name"), message.remove("value"))
+        else {
+            return false;
+        };
+
+        // Subscribing while applying is allowed, so the entry is taken out meanwhile.
+        let Some(apply) = self.signals.borrow_mut().remove(&name) else {
+            return false;
+        };
+        let applied = apply(value);
+        self.signals.borrow_mut().entry(name).or_insert(apply);
+        applied
+    }
+}
+
+/// A [`SignalMirror`] connected to a [`SignalServer`] on native targets.
+///
+/// Messages are received on a background thread and applied by [`poll`](Self::poll).
+#[cfg(not(target_arch = "wasm32"))]
+pub struct RemoteClient {
+    mirror: SignalMirror,
+    messages: Receiver<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RemoteClient {
+    /// Connects to the server at `url`, such as `ws://127.0.0.1:9000`.
+    pub fn connect(url: impl AsRef<str>) -> tungstenite::Result<Self> {
+        let (mut socket, _) = tungstenite::connect(url.as_ref())?;
+        let (tx, messages) = mpsc::channel();
+        thread::spawn(move || receive(&mut socket, tx));
+
+        Ok(RemoteClient {
+            mirror: SignalMirror::new(),
+            messages,
+        })
+    }
+
+    /// See [`SignalMirror::subscribe`].
+    pub fn subscribe<T>(&self, name: &str, initial: T) -> Rc<Memo<T>>
+    where
+        T: DeserializeOwned + Clone + 'static,
+    {
+        self.mirror.subscribe(name, initial)
+    }
+
+    /// Applies the messages received since the previous poll.
+    ///
+    /// Returns the number of messages applied.
+    pub fn poll(&self) -> usize {
+        self.messages
+            .try_iter()
+            .filter(|m| self.mirror.apply(m))
+            .count()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn receive(socket: &mut WebSocket<MaybeTlsStream<TcpStream>>, messages: mpsc::Sender<String>) {
+    while let Ok(message) = socket.read() {
+        if let Message::Text(text) = message
+            && messages.send(text.to_string()).is_err()
+        {
+            return;
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Weak,
+};
+
+use crate::{Effect, MemoNode};
+
+/// A point in the history of the runtime, advanced by every change of a signal.
+pub(crate) type Revision = u64;
+
+/// How often a signal is expected to change.
+///
+/// A memo is as durable as the least durable signal it depends on, directly or through
+/// other memos. When a memo is read after a change, it is only verified against its
+/// dependencies if a signal at least as durable as itself changed since it was last
+/// verified. Marking low-churn inputs such as configuration as [`Durability::High`]
+/// lets the memos derived only from them skip that verification entirely while
+/// high-churn inputs such as the mouse position change.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{Durability, prelude::*};
+///
+/// let theme = Signal::new("dark");
+/// theme.set_durability(Durability::High);
+/// let mouse = Signal::new((0, 0));
+///
+/// let runs = Rc::new(Cell::new(0));
+/// let palette = Memo::new({
+///     let (theme, runs) = (theme.clone(), runs.clone());
+///     move || {
+///         runs.set(runs.get() + 1);
+///         format!("{} palette", theme.get())
+///     }
+/// });
+/// let cursor = Memo::new({
+///     let (mouse, palette) = (mouse.clone(), palette.clone());
+///     move || format!("{:?} in {}", *mouse.get(), palette.get())
+/// });
+///
+/// assert_eq!(cursor.get(), "(0, 0) in dark palette");
+/// mouse.set((4, 2));
+/// assert_eq!(cursor.get(), "(4, 2) in dark palette");
+/// assert_eq!(runs.get(), 1);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Durability {
+    /// Changes often, such as user input. The default for signals.
+    #[default]
+    Low,
+    /// Changes occasionally, such as the content of a loaded document.
+    Medium,
+    /// Changes rarely, such as configuration.
+    High,
+}
+
+impl Durability {
+    const LEVELS: usize = 3;
+}
+
+thread_local! {
+    static CURRENT: Cell<Revision> = const { Cell::new(1) };
+
+    /// The revision of the most recent change of a signal at least as durable as each level.
+    static LAST_CHANGED: Cell<[Revision; Durability::LEVELS]> =
+        const { Cell::new([0; Durability::LEVELS]) };
+}
+
+/// The revisions of a runtime that is not entered, see [`Runtime`](crate::Runtime).
+pub(crate) struct State {
+    current: Revision,
+    last_changed: [Revision; Durability::LEVELS],
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            current: 1,
+            last_changed: [0; Durability::LEVELS],
+        }
+    }
+}
+
+/// Exchanges the revisions with those of the runtime being entered or left.
+pub(crate) fn swap(state: &mut State) {
+    state.current = CURRENT.replace(state.current);
+    state.last_changed = LAST_CHANGED.replace(state.last_changed);
+}
+
+/// Returns the current revision.
+pub(crate) fn current() -> Revision {
+    CURRENT.get()
+}
+
+/// Starts a new revision for the change of a signal with the given durability.
+pub(crate) fn bump(durability: Durability) -> Revision {
+    let current = CURRENT.get() + 1;
+    CURRENT.set(current);
+    let mut last_changed = LAST_CHANGED.get();
+    last_changed[..=durability as usize].fill(current);
+    LAST_CHANGED.set(last_changed);
+    current
+}
+
+/// Returns the revision of the most recent change of a signal at least as durable as `durability`.
+pub(crate) fn last_changed(durability: Durability) -> Revision {
+    LAST_CHANGED.get()[durability as usize]
+}
+
+/// The revisions of one node, shared with the memos reading it.
+#[derive(Default)]
+pub(crate) struct Revisions {
+    /// When the value of the node last changed.
+    pub(crate) changed_at: Cell<Revision>,
+    /// When the cached value of a memo was last known to be up to date.
+    pub(crate) verified_at: Cell<Revision>,
+    pub(crate) durability: Cell<Durability>,
+    /// The node, if it is a memo, so that the memos reading it can bring it up to date.
+    pub(crate) node: RefCell<Option<Weak<dyn MemoNode>>>,
+    /// The memos reading the node, so that they stop depending on it once they no longer
+    /// read it.
+    pub(crate) dependents: RefCell<Vec<Weak<dyn MemoNode>>>,
+    /// The effects reading the node, so that a disposed effect stops depending on it.
+    pub(crate) effects: RefCell<Vec<Weak<Effect>>>,
+}
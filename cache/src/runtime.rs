@@ -0,0 +1,661 @@
+//! Introspection of the reactive runtime as a whole.
+//!
+//! The functions of the crate act on the default runtime of the current thread, unless
+//! a [`Runtime`] of its own is entered. Each thread has a default runtime of its own.
+//!
+//! # Allocations
+//!
+//! Once memos and effects have subscribed to their dependencies, setting signals,
+//! verifying and recomputing memos and re-running immediate effects do not allocate,
+//! so the runtime fits frame loops and audio callbacks. Allocations remain where they
+//! are inherent:
+//!
+//! - creating nodes, and subscribing to a dependency for the first time,
+//! - whatever the closures of memos and effects allocate, including cloned values,
+//! - recomputing a memo while the `Rc` of its previous value is still held,
+//! - the flight recorder, which formats every write unless disabled with
+//!   [`set_recorder_capacity(0)`](set_recorder_capacity),
+//! - [`inspect`](crate::inspect) hooks, and deferred effects.
+
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    mem::size_of,
+    panic::Location,
+    rc::{Rc, Weak},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread::LocalKey,
+    time::{Duration, SystemTime},
+};
+
+use crate::{Effect, Memo, MemoNode, NodeId, Signal, Tracker};
+
+/// The number of changes kept by the flight recorder unless configured otherwise.
+pub const DEFAULT_RECORDER_CAPACITY: usize = 32;
+
+static NEXT_RUNTIME: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    pub(crate) static SIGNALS: Cell<usize> = const { Cell::new(0) };
+    pub(crate) static MEMOS: Cell<usize> = const { Cell::new(0) };
+    pub(crate) static EFFECTS: Cell<usize> = const { Cell::new(0) };
+    pub(crate) static EDGES: Cell<usize> = const { Cell::new(0) };
+
+    /// The id of the runtime entered, initially the default runtime of the thread.
+    static CURRENT_RUNTIME: Cell<usize> = Cell::new(next_runtime_id());
+
+    static RECORDER_CAPACITY: Cell<usize> = const { Cell::new(DEFAULT_RECORDER_CAPACITY) };
+
+    static RECORDER: RefCell<VecDeque<Change>> = const { RefCell::new(VecDeque::new()) };
+}
+
+fn next_runtime_id() -> usize {
+    NEXT_RUNTIME.fetch_add(1, Ordering::Relaxed)
+}
+
+pub(crate) fn increment(counter: &'static LocalKey<Cell<usize>>) {
+    counter.set(counter.get() + 1);
+}
+
+pub(crate) fn decrement(counter: &'static LocalKey<Cell<usize>>, n: usize) {
+    counter.set(counter.get() - n);
+}
+
+/// A signal write kept by the flight recorder, returned by [`recent_changes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    /// The signal that was written.
+    pub node: NodeId,
+    /// The name given to the signal with [`inspect::set_name`](crate::inspect::set_name), if any.
+    pub name: Option<String>,
+    /// Where the signal was created.
+    pub location: &'static Location<'static>,
+    /// A `Debug` rendering of the value before the write, if the value implements `Debug`.
+    pub old: Option<String>,
+    /// A `Debug` rendering of the value after the write, if the value implements `Debug`.
+    pub new: Option<String>,
+    /// When the write happened.
+    pub at: SystemTime,
+}
+
+/// Sets how many of the most recent signal writes the flight recorder keeps.
+///
+/// The recorder is always on, keeping [`DEFAULT_RECORDER_CAPACITY`] writes by default.
+/// Setting the capacity to zero disables it, which also avoids formatting
+/// the old and new value of every write.
+pub fn set_recorder_capacity(capacity: usize) {
+    RECORDER_CAPACITY.set(capacity);
+    RECORDER.with_borrow_mut(|recorder| {
+        let excess = recorder.len().saturating_sub(capacity);
+        recorder.drain(..excess);
+    });
+}
+
+/// Returns the most recent signal writes, oldest first.
+///
+/// Crash handlers and bug reports can include them to show what the state was doing
+/// right before a failure. Writes that did not change the value of an `Eq` signal are not recorded.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "nightly", doc = "```")]
+#[cfg_attr(not(feature = "nightly"), doc = "```ignore")]
+/// use reactive_cache::{inspect, prelude::*, runtime};
+///
+/// let volume = Signal::new(3);
+/// inspect::set_name(volume.id(), "volume");
+/// volume.set(4);
+///
+/// let change = runtime::recent_changes().pop().unwrap();
+/// assert_eq!(change.name.as_deref(), Some("volume"));
+/// assert_eq!(change.old.as_deref(), Some("3"));
+/// assert_eq!(change.new.as_deref(), Some("4"));
+/// ```
+pub fn recent_changes() -> Vec<Change> {
+    RECORDER.with_borrow(|recorder| recorder.iter().cloned().collect())
+}
+
+/// Returns whether writes should be passed to [`record_change`].
+pub(crate) fn recording() -> bool {
+    RECORDER_CAPACITY.get() > 0
+}
+
+/// Returns the id of the runtime entered, recorded by the nodes created in it.
+pub(crate) fn current_id() -> usize {
+    CURRENT_RUNTIME.get()
+}
+
+/// Asserts, in debug builds, that a node created in the runtime `id` is used in it.
+#[inline]
+pub(crate) fn assert_runtime(id: usize) {
+    debug_assert!(
+        id == current_id(),
+        "a reactive node created in runtime {id} was used in runtime {}. \
+         Enter the runtime a node was created in before using it.",
+        current_id(),
+    );
+}
+
+pub(crate) fn record_change(tracker: &Tracker, old: Option<String>, new: Option<String>) {
+    let change = Change {
+        node: tracker.id(),
+        name: crate::inspect::name(tracker.id()),
+        location: tracker.location(),
+        old,
+        new,
+        at: SystemTime::now(),
+    };
+    let capacity = RECORDER_CAPACITY.get();
+    RECORDER.with_borrow_mut(|recorder| {
+        if recorder.len() >= capacity {
+            recorder.pop_front();
+        }
+        recorder.push_back(change);
+    });
+}
+
+/// Runs the effects queued by [deferred](crate::effect::EffectOptions::deferred) scheduling.
+///
+/// Every queued effect runs once, in descending priority, however often it was
+/// triggered since the previous flush. Debounced effects whose delay has not
+/// elapsed yet stay queued. Effects triggered during the flush are queued for
+/// the next one.
+///
+/// Returns the number of effects that ran.
+///
+/// This function is re-exported as `reactive_cache::flush`.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{effect::EffectOptions, prelude::*};
+///
+/// let count = Signal::new(0);
+/// let runs = Rc::new(Cell::new(0));
+/// let effect = Effect::new_with_options(
+///     {
+///         let (count, runs) = (count.clone(), runs.clone());
+///         move || {
+///             let _ = *count.get();
+///             runs.set(runs.get() + 1);
+///         }
+///     },
+///     EffectOptions::new().deferred(),
+/// );
+///
+/// for _ in 0..10 {
+///     count.increment();
+/// }
+/// assert_eq!(runs.get(), 1);
+///
+/// reactive_cache::flush();
+/// assert_eq!(runs.get(), 2);
+/// ```
+pub fn flush() -> usize {
+    crate::scheduler::flush(None)
+}
+
+/// Runs `f`, then runs once every effect triggered by the signals it set.
+///
+/// Memos are still invalidated on every write, so reads inside `f` see the new values,
+/// but effects wait for the end of the batch: an effect depending on several signals set
+/// in a row runs once, with all of them updated, instead of once per write. Batches can
+/// be nested, in which case the effects run when the outermost one ends. If `f` panics,
+/// the effects it triggered do not run.
+///
+/// Returns the result of `f`.
+///
+/// This function is re-exported as `reactive_cache::batch`.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use reactive_cache::prelude::*;
+///
+/// let first = Signal::new("Ada");
+/// let last = Signal::new("Lovelace");
+/// let seen = Rc::new(RefCell::new(Vec::new()));
+/// let effect = Effect::new({
+///     let (first, last, seen) = (first.clone(), last.clone(), seen.clone());
+///     move || seen.borrow_mut().push(format!("{} {}", first.get(), last.get()))
+/// });
+///
+/// reactive_cache::batch(|| {
+///     first.set("Grace");
+///     last.set("Hopper");
+/// });
+/// assert_eq!(*seen.borrow(), ["Ada Lovelace", "Grace Hopper"]);
+/// ```
+pub fn batch<R>(f: impl FnOnce() -> R) -> R {
+    crate::scheduler::batch(f)
+}
+
+/// Runs the effects queued by [deferred](crate::effect::EffectOptions::deferred) scheduling
+/// until `budget` is spent, like [`flush`].
+///
+/// Call it once per frame when a single change may queue hundreds of effects: the
+/// effects that did not fit stay queued, and run first on the next call. An effect is
+/// never interrupted once started, so the budget can be exceeded by the duration of one
+/// effect, and at least one due effect runs on every call.
+///
+/// Returns the number of effects that ran.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc, thread, time::Duration};
+/// use reactive_cache::{effect::EffectOptions, prelude::*, runtime};
+///
+/// let count = Signal::new(0);
+/// let runs = Rc::new(Cell::new(0));
+/// let effects: Vec<_> = (0..3)
+///     .map(|_| {
+///         Effect::new_with_options(
+///             {
+///                 let (count, runs) = (count.clone(), runs.clone());
+///                 move || {
+///                     let _ = *count.get();
+///                     runs.set(runs.get() + 1);
+///                     thread::sleep(Duration::from_millis(5));
+///                 }
+///             },
+///             EffectOptions::new().deferred(),
+///         )
+///     })
+///     .collect();
+/// runs.set(0);
+///
+/// count.increment();
+/// assert_eq!(runtime::flush_for(Duration::from_millis(1)), 1);
+/// assert_eq!(runtime::flush_for(Duration::from_millis(1)), 1);
+/// assert_eq!(runtime::flush_for(Duration::from_secs(1)), 1);
+/// assert_eq!(runs.get(), 3);
+/// ```
+pub fn flush_for(budget: Duration) -> usize {
+    crate::scheduler::flush(Some(budget))
+}
+
+/// Registers `memo` for speculative recomputation by [`run_idle`].
+///
+/// Whenever a change invalidates the memo, the next call to [`run_idle`] with enough
+/// budget recomputes it, so a later read finds the value already cached.
+/// Only a weak reference is kept: registering a memo never keeps it alive.
+///
+/// Warmed values compete for the same cache as every other memo,
+/// so only register memos that are likely to be read soon.
+pub fn precompute_on_idle<T: 'static>(memo: &Rc<Memo<T>>) {
+    crate::scheduler::precompute_on_idle(memo)
+}
+
+/// Recomputes invalidated memos registered with [`precompute_on_idle`]
+/// until `budget` is spent.
+///
+/// Call it when the application has nothing else to do, for example after
+/// rendering a frame early. A memo is never interrupted once started, so the
+/// budget can be exceeded by the duration of one computation.
+///
+/// Returns `true` if every registered memo is up to date.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc, time::Duration};
+/// use reactive_cache::{prelude::*, runtime};
+///
+/// let computations = Rc::new(Cell::new(0));
+/// let source = Signal::new(1);
+/// let report = Memo::new({
+///     let (source, computations) = (source.clone(), computations.clone());
+///     move || {
+///         computations.set(computations.get() + 1);
+///         format!("value: {}", source.get())
+///     }
+/// });
+/// runtime::precompute_on_idle(&report);
+///
+/// source.set(2);
+/// assert!(runtime::run_idle(Duration::from_millis(10)));
+/// assert_eq!(computations.get(), 1);
+///
+/// // The value is already cached when it is needed.
+/// assert_eq!(report.get(), "value: 2");
+/// assert_eq!(computations.get(), 1);
+/// ```
+pub fn run_idle(budget: Duration) -> bool {
+    crate::scheduler::run_idle(budget)
+}
+
+/// Applies the values sent to signals from other threads through their
+/// [senders](crate::Signal::sender), then runs the effects they triggered.
+///
+/// Values sent to the same signal are set in the order they were sent. Call it from the
+/// event loop of the thread owning the signals, for example once per frame, or when a
+/// worker signals that it sent values.
+///
+/// Returns how many values were applied.
+///
+/// This function is re-exported as `reactive_cache::drain_remote_updates`.
+pub fn drain_remote_updates() -> usize {
+    crate::scheduler::drain_remote()
+}
+
+/// A snapshot of the size of the reactive runtime, returned by [`stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Live [`Signal`]s.
+    pub signals: usize,
+    /// Live [`Memo`]s.
+    pub memos: usize,
+    /// Live [`Effect`]s.
+    pub effects: usize,
+    /// Recorded dependency edges, from a signal or memo to a dependent memo or effect.
+    pub edges: usize,
+    /// Values of [bounded](crate::Memo::set_bounded) memos currently held by the runtime
+    /// cache.
+    pub cache_entries: usize,
+    /// The total weight of those values, kept under the
+    /// [cache budget](crate::memo::set_cache_budget).
+    pub cache_weight: usize,
+    /// Memo values evicted from the runtime cache since the program started.
+    /// See [`MemoNode::on_evict`].
+    pub cache_evictions: usize,
+    /// Approximate heap memory used by the nodes, edges and cache entries above, in bytes.
+    ///
+    /// Values held by signals and memos are only counted by their inline size,
+    /// not by the heap memory they own themselves.
+    pub approx_bytes: usize,
+}
+
+/// Returns the number of live reactive nodes and the memory they occupy.
+///
+/// Sampling this periodically lets a long-running application detect
+/// a steadily growing graph, usually the sign of leaked nodes.
+///
+/// Nodes implementing [`Observable`](crate::Observable) or [`MemoNode`] outside this crate
+/// are not counted, but their edges and cache entries are.
+///
+/// # Examples
+///
+/// ```
+/// use reactive_cache::{prelude::*, runtime};
+///
+/// let before = runtime::stats();
+///
+/// let a = Signal::new(1);
+/// let double = Memo::new({
+///     let a = a.clone();
+///     move || *a.get() * 2
+/// });
+/// let effect = Effect::new({
+///     let double = double.clone();
+///     move || {
+///         double.get();
+///     }
+/// });
+///
+/// let stats = runtime::stats();
+/// assert_eq!(stats.signals, before.signals + 1);
+/// assert_eq!(stats.memos, before.memos + 1);
+/// assert_eq!(stats.effects, before.effects + 1);
+/// // `a -> double`, `double -> effect` and `a -> effect`.
+/// assert_eq!(stats.edges, before.edges + 3);
+///
+/// drop((a, double, effect));
+/// assert_eq!(runtime::stats().effects, before.effects);
+/// ```
+pub fn stats() -> Stats {
+    let signals = SIGNALS.get();
+    let memos = MEMOS.get();
+    let effects = EFFECTS.get();
+    let edges = EDGES.get();
+    let cache_entries = crate::cache::len();
+    let cache_weight = crate::cache::weight();
+    let cache_evictions = crate::cache::evictions();
+
+    // Every node lives in its own `Rc` allocation, with two reference counts.
+    let rc = 2 * size_of::<usize>();
+    let approx_bytes = signals * (rc + size_of::<Signal<()>>())
+        + memos * (rc + size_of::<Memo<()>>())
+        + effects * (rc + size_of::<Effect>())
+        + edges * size_of::<Weak<dyn MemoNode>>()
+        // An LRU entry holds the key, the node and the slot, its weight and cost, and two
+        // list links, next to its hash table slot.
+        + cache_entries
+            * (size_of::<*const dyn MemoNode>()
+                + size_of::<Weak<dyn MemoNode>>()
+                + size_of::<Rc<dyn Any>>()
+                + size_of::<Duration>()
+                + 4 * size_of::<usize>());
+
+    Stats {
+        signals,
+        memos,
+        effects,
+        edges,
+        cache_entries,
+        cache_weight,
+        cache_evictions,
+        approx_bytes,
+    }
+}
+
+/// An independent reactive runtime.
+///
+/// Everything the crate keeps globally, such as the effect queues, the memo cache and its
+/// budget, the current revision, the hooks and the flight recorder, belongs to a runtime.
+/// Only the node counts of [`stats`] are shared by all runtimes of a thread.
+/// Each thread has a default runtime; a `Runtime` of its own lets a
+/// library keep its reactive graph apart from the one of the application using it.
+///
+/// Signals, memos and effects belong to the runtime [entered](Runtime::enter) when they
+/// are created, and must only be used while it is entered, which debug builds assert.
+/// Dropping a runtime releases the effects it [kept alive](crate::Effect::keep_alive).
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{Runtime, effect::EffectOptions, prelude::*};
+///
+/// let runtime = Runtime::new();
+/// let runs = Rc::new(Cell::new(0));
+/// let (count, _effect) = runtime.enter(|| {
+///     let count = Signal::new(0);
+///     let effect = Effect::new_with_options(
+///         {
+///             let (count, runs) = (count.clone(), runs.clone());
+///             move || {
+///                 let _ = count.get();
+///                 runs.set(runs.get() + 1);
+///             }
+///         },
+///         EffectOptions::new().deferred(),
+///     );
+///     (count, effect)
+/// });
+///
+/// runtime.enter(|| count.set(1));
+///
+/// // The effect is queued in its own runtime, not in the default one.
+/// assert_eq!(reactive_cache::flush(), 0);
+/// assert_eq!(runtime.enter(reactive_cache::flush), 1);
+/// assert_eq!(runs.get(), 2);
+/// ```
+pub struct Runtime {
+    id: usize,
+    /// The state of the runtime while it is not entered.
+    state: RefCell<Option<Box<State>>>,
+}
+
+impl Runtime {
+    /// Creates a runtime without nodes.
+    pub fn new() -> Self {
+        let id = next_runtime_id();
+        Runtime {
+            id,
+            state: RefCell::new(Some(Box::new(State::new(id)))),
+        }
+    }
+
+    /// Sets the capacity of the cache of this runtime, which only holds the values of
+    /// [bounded](crate::Memo::set_bounded) memos. See [`configure_cache`].
+    ///
+    /// [`configure_cache`]: crate::configure_cache
+    pub fn with_cache_capacity(self, capacity: impl Into<crate::CacheCapacity>) -> Self {
+        self.enter(|| crate::configure_cache(capacity));
+        self
+    }
+
+    /// Sets how the cache of this runtime chooses the values it evicts, which are only
+    /// those of [bounded](crate::Memo::set_bounded) memos.
+    /// See [`set_eviction_policy`](crate::memo::set_eviction_policy).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use reactive_cache::{Memo, Runtime, memo::EvictionPolicy};
+    ///
+    /// let runtime = Runtime::new()
+    ///     .with_cache_capacity(NonZeroUsize::new(2).unwrap())
+    ///     .with_eviction_policy(EvictionPolicy::Lfu);
+    ///
+    /// runtime.enter(|| {
+    ///     let memos: Vec<_> = (0..3)
+    ///         .map(|i| {
+    ///             let memo = Memo::new(move || i);
+    ///             memo.set_bounded(true);
+    ///             memo
+    ///         })
+    ///         .collect();
+    ///
+    ///     // The first memo is read often, the second once.
+    ///     for _ in 0..3 {
+    ///         memos[0].get();
+    ///     }
+    ///     memos[1].get();
+    ///
+    ///     // The third value evicts the least frequently used one.
+    ///     memos[2].get();
+    ///     assert!(memos[0].is_cached());
+    ///     assert!(!memos[1].is_cached());
+    /// });
+    /// ```
+    pub fn with_eviction_policy(self, policy: crate::memo::EvictionPolicy) -> Self {
+        self.enter(|| crate::memo::set_eviction_policy(policy));
+        self
+    }
+
+    /// Runs `f` in this runtime, then returns to the runtime entered before.
+    ///
+    /// Runtimes can be entered inside each other.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this runtime is already entered.
+    pub fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        let mut state = self
+            .state
+            .borrow_mut()
+            .take()
+            .expect("the runtime is already entered");
+        swap(&mut state);
+
+        // The previous runtime is restored even if `f` panics.
+        struct Leave<'a> {
+            runtime: &'a Runtime,
+            state: Option<Box<State>>,
+        }
+
+        impl Drop for Leave<'_> {
+            fn drop(&mut self) {
+                let mut state = self.state.take().unwrap();
+                swap(&mut state);
+                *self.runtime.state.borrow_mut() = Some(state);
+            }
+        }
+
+        let _leave = Leave {
+            runtime: self,
+            state: Some(state),
+        };
+        f()
+    }
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        let Some(state) = self.state.get_mut().take() else {
+            return;
+        };
+        // The nodes released with the runtime unregister from an empty copy of it,
+        // rather than from the runtime entered.
+        let mut empty = Box::new(State::new(self.id));
+        swap(&mut empty);
+        drop(state);
+        swap(&mut empty);
+    }
+}
+
+/// The global state of a runtime that is not entered.
+struct State {
+    id: usize,
+    recorder: VecDeque<Change>,
+    recorder_capacity: usize,
+    boundary: crate::boundary::State,
+    cache: crate::cache::State,
+    effect: crate::effect::State,
+    effect_stack: crate::effect_stack::State,
+    inspect: crate::inspect::State,
+    memo_stack: crate::memo_stack::State,
+    revision: crate::revision::State,
+    scheduler: crate::scheduler::State,
+    scope: crate::scope::State,
+    tag: crate::tag::State,
+}
+
+impl State {
+    fn new(id: usize) -> Self {
+        State {
+            id,
+            recorder: VecDeque::new(),
+            recorder_capacity: DEFAULT_RECORDER_CAPACITY,
+            boundary: Default::default(),
+            cache: Default::default(),
+            effect: Default::default(),
+            effect_stack: Default::default(),
+            inspect: Default::default(),
+            memo_stack: Default::default(),
+            revision: Default::default(),
+            scheduler: Default::default(),
+            scope: Default::default(),
+            tag: Default::default(),
+        }
+    }
+}
+
+/// Exchanges the global state with `state`, entering or leaving a runtime.
+fn swap(state: &mut State) {
+    state.id = CURRENT_RUNTIME.replace(state.id);
+    RECORDER.with_borrow_mut(|recorder| std::mem::swap(recorder, &mut state.recorder));
+    state.recorder_capacity = RECORDER_CAPACITY.replace(state.recorder_capacity);
+    crate::boundary::swap(&mut state.boundary);
+    crate::cache::swap(&mut state.cache);
+    crate::effect::swap(&mut state.effect);
+    crate::effect_stack::swap(&mut state.effect_stack);
+    crate::inspect::swap(&mut state.inspect);
+    crate::memo_stack::swap(&mut state.memo_stack);
+    crate::revision::swap(&mut state.revision);
+    crate::scheduler::swap(&mut state.scheduler);
+    crate::scope::swap(&mut state.scope);
+    crate::tag::swap(&mut state.tag);
+}
@@ -0,0 +1,268 @@
+use std::{
+    cell::{Cell, RefCell},
+    cmp::Reverse,
+    rc::{Rc, Weak},
+    time::{Duration, Instant},
+};
+
+use crate::{Effect, Memo, effect::run_triggered};
+
+struct Pending {
+    effect: Weak<Effect>,
+    /// The earliest time the effect may run, for debounced effects.
+    due: Option<Instant>,
+}
+
+/// Warms a memo registered for idle precomputation.
+/// Returns `None` once the memo was dropped, otherwise whether any work was done.
+type IdleTask = Box<dyn Fn() -> Option<bool>>;
+
+/// Applies the values sent to a signal from other threads.
+/// Returns `None` once the signal was dropped or every sender was, otherwise how many
+/// values were applied.
+pub(crate) type RemoteTask = Box<dyn Fn() -> Option<usize>>;
+
+thread_local! {
+    static PENDING: RefCell<Vec<Pending>> = const { RefCell::new(Vec::new()) };
+
+    /// Emptied buffers of triggered effects, reused so that propagating a change does not
+    /// allocate.
+    ///
+    /// Effects setting signals trigger nested propagations, each taking a buffer of its own.
+    static BUFFERS: RefCell<Vec<Vec<Weak<Effect>>>> = const { RefCell::new(Vec::new()) };
+
+    /// How many calls to [`batch`] are running.
+    static BATCH_DEPTH: Cell<usize> = const { Cell::new(0) };
+
+    /// Effects triggered inside a batch, run once the outermost batch ends.
+    static BATCHED: RefCell<Vec<Weak<Effect>>> = const { RefCell::new(Vec::new()) };
+
+    static IDLE: RefCell<Vec<IdleTask>> = const { RefCell::new(Vec::new()) };
+
+    static REMOTE: RefCell<Vec<RemoteTask>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The queues of a runtime that is not entered, see [`Runtime`](crate::Runtime).
+#[derive(Default)]
+pub(crate) struct State {
+    pending: Vec<Pending>,
+    buffers: Vec<Vec<Weak<Effect>>>,
+    batch_depth: usize,
+    batched: Vec<Weak<Effect>>,
+    idle: Vec<IdleTask>,
+    remote: Vec<RemoteTask>,
+}
+
+/// Exchanges the queues with those of the runtime being entered or left.
+pub(crate) fn swap(state: &mut State) {
+    PENDING.with_borrow_mut(|pending| std::mem::swap(pending, &mut state.pending));
+    BUFFERS.with_borrow_mut(|buffers| std::mem::swap(buffers, &mut state.buffers));
+    state.batch_depth = BATCH_DEPTH.replace(state.batch_depth);
+    BATCHED.with_borrow_mut(|batched| std::mem::swap(batched, &mut state.batched));
+    IDLE.with_borrow_mut(|idle| std::mem::swap(idle, &mut state.idle));
+    REMOTE.with_borrow_mut(|remote| std::mem::swap(remote, &mut state.remote));
+}
+
+/// Returns an empty buffer to collect triggered effects into, for [`schedule`].
+pub(crate) fn buffer() -> Vec<Weak<Effect>> {
+    BUFFERS.with_borrow_mut(Vec::pop).unwrap_or_default()
+}
+
+/// Runs `effects` in descending priority, or queues the deferred ones.
+///
+/// Effects dropped by an earlier run of the same pass are skipped.
+pub(crate) fn schedule(mut effects: Vec<Weak<Effect>>) {
+    if BATCH_DEPTH.get() > 0 {
+        BATCHED.with_borrow_mut(|batched| {
+            for w in effects.drain(..) {
+                if !batched.iter().any(|b| b.ptr_eq(&w)) {
+                    batched.push(w);
+                }
+            }
+        });
+        BUFFERS.with_borrow_mut(|buffers| buffers.push(effects));
+        return;
+    }
+
+    // Sorting allocates, and effects usually share the default priority.
+    let priority = |w: &Weak<Effect>| Reverse(w.upgrade().map_or(0, |e| e.options.priority));
+    if !effects.is_sorted_by_key(priority) {
+        effects.sort_by_key(priority);
+    }
+
+    for w in &effects {
+        let Some(e) = w.upgrade() else { continue };
+        if e.options.deferred {
+            enqueue(&e);
+        } else {
+            run_triggered(&e);
+        }
+    }
+
+    effects.clear();
+    BUFFERS.with_borrow_mut(|buffers| buffers.push(effects));
+}
+
+/// Runs `f`, holding back the effects triggered meanwhile until the outermost batch ends.
+pub(crate) fn batch<R>(f: impl FnOnce() -> R) -> R {
+    struct Depth;
+
+    impl Drop for Depth {
+        fn drop(&mut self) {
+            let depth = BATCH_DEPTH.get() - 1;
+            BATCH_DEPTH.set(depth);
+            // A batch that panicked does not run its effects.
+            if depth == 0 && std::thread::panicking() {
+                BATCHED.with_borrow_mut(Vec::clear);
+            }
+        }
+    }
+
+    BATCH_DEPTH.set(BATCH_DEPTH.get() + 1);
+    let depth = Depth;
+    let result = f();
+    drop(depth);
+
+    if BATCH_DEPTH.get() == 0 {
+        let mut effects = buffer();
+        BATCHED.with_borrow_mut(|batched| effects.append(batched));
+        schedule(effects);
+    }
+    result
+}
+
+fn enqueue(e: &Rc<Effect>) {
+    let now = Instant::now();
+    PENDING.with_borrow_mut(|pending| {
+        let queued = pending
+            .iter_mut()
+            .find(|p| std::ptr::eq(p.effect.as_ptr(), Rc::as_ptr(e)));
+
+        let options = &e.options;
+        if let Some(delay) = options.debounce {
+            // A new trigger restarts the debounce delay.
+            match queued {
+                Some(p) => p.due = Some(now + delay),
+                None => pending.push(Pending {
+                    effect: Rc::downgrade(e),
+                    due: Some(now + delay),
+                }),
+            }
+        } else if queued.is_none() {
+            let due = options
+                .throttle
+                .zip(e.last_run.get())
+                .map(|(interval, last)| last + interval);
+            pending.push(Pending {
+                effect: Rc::downgrade(e),
+                due,
+            });
+        }
+    });
+}
+
+/// Runs every queued effect that is due once, in descending priority,
+/// until `budget` is spent if there is one.
+pub(crate) fn flush(budget: Option<Duration>) -> usize {
+    let now = Instant::now();
+    let pending = PENDING.take();
+    let (due, waiting): (Vec<_>, Vec<_>) = pending
+        .into_iter()
+        .filter(|p| p.effect.strong_count() > 0)
+        .partition(|p| p.due.is_none_or(|due| due <= now));
+
+    // Effects triggered while flushing are queued behind the ones still waiting.
+    PENDING.with_borrow_mut(|pending| {
+        pending.splice(0..0, waiting);
+    });
+
+    let mut effects: Vec<_> = due
+        .into_iter()
+        .filter_map(|p| Some((p.effect.upgrade()?, p)))
+        .collect();
+    effects.sort_by_key(|(e, _)| Reverse(e.options.priority));
+
+    let mut ran = 0;
+    let mut effects = effects.into_iter();
+    for (e, _) in effects.by_ref() {
+        if run_triggered(&e) {
+            ran += 1;
+        }
+        if budget.is_some_and(|budget| now.elapsed() >= budget) {
+            break;
+        }
+    }
+
+    // Effects left over by an exhausted budget are queued ahead of the others,
+    // including their own runs triggered while flushing.
+    let left: Vec<_> = effects.map(|(_, p)| p).collect();
+    if !left.is_empty() {
+        PENDING.with_borrow_mut(|pending| {
+            pending.retain(|p| !left.iter().any(|l| l.effect.ptr_eq(&p.effect)));
+            pending.splice(0..0, left);
+        });
+    }
+    ran
+}
+
+pub(crate) fn precompute_on_idle<T: 'static>(memo: &Rc<Memo<T>>) {
+    let memo = Rc::downgrade(memo);
+    IDLE.with_borrow_mut(|idle| {
+        idle.push(Box::new(move || {
+            let memo = memo.upgrade()?;
+            let dirty = !memo.is_cached();
+            if dirty {
+                memo.warm();
+            }
+            Some(dirty)
+        }))
+    });
+}
+
+/// Warms registered memos until `budget` is spent.
+/// Returns `true` if every registered memo is up to date.
+pub(crate) fn run_idle(budget: Duration) -> bool {
+    let start = Instant::now();
+
+    // Tasks registered while warming are kept for the next call.
+    let mut tasks = IDLE.take();
+    let mut done = true;
+    tasks.retain(|task| {
+        if start.elapsed() >= budget {
+            done = false;
+            return true;
+        }
+        task().is_some()
+    });
+    IDLE.with_borrow_mut(|idle| {
+        idle.splice(0..0, tasks);
+    });
+
+    done
+}
+
+pub(crate) fn register_remote(task: RemoteTask) {
+    REMOTE.with_borrow_mut(|remote| remote.push(task));
+}
+
+/// Applies the values sent from other threads in a batch, dropping the tasks of signals
+/// that can no longer receive any. Returns how many values were applied.
+pub(crate) fn drain_remote() -> usize {
+    // Tasks registered while applying values are kept for the next call.
+    let mut tasks = REMOTE.take();
+    let mut applied = 0;
+    batch(|| {
+        tasks.retain(|task| {
+            let Some(count) = task() else {
+                return false;
+            };
+            applied += count;
+            true
+        })
+    });
+    REMOTE.with_borrow_mut(|remote| {
+        remote.splice(0..0, tasks);
+    });
+
+    applied
+}
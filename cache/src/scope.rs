@@ -0,0 +1,226 @@
+//! Scopes owning the reactive state of a subtree.
+//!
+//! The signals, memos and effects created while a [`Scope`] [runs](Scope::run) a closure
+//! are owned by the scope: they stay alive as long as it does, and are torn down together
+//! when it is [disposed](Scope::dispose) or dropped. Its effects stop running, and the
+//! scopes created inside it are disposed as well. A component of a UI can keep its state
+//! in a scope, and drop the scope when the component is removed.
+//!
+//! Only the nodes created by the closure itself are owned by the scope. Effects created by
+//! a running effect are owned by the current run of that effect, and nodes created while
+//! a memo computes belong to that computation.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{cell::RefCell, rc::Rc};
+//! use reactive_cache::{Scope, prelude::*};
+//!
+//! let theme = Signal::new("light");
+//! let log = Rc::new(RefCell::new(Vec::new()));
+//!
+//! let component = Scope::new();
+//! let clicks = component.run(|_| {
+//!     let clicks = Signal::new(0);
+//!     Effect::new({
+//!         let (theme, clicks, log) = (theme.clone(), clicks.clone(), log.clone());
+//!         move || log.borrow_mut().push(format!("{} {}", theme.get(), clicks.get()))
+//!     });
+//!     Rc::downgrade(&clicks)
+//! });
+//!
+//! // The scope keeps the signal and the effect alive.
+//! clicks.upgrade().unwrap().set(1);
+//! assert_eq!(*log.borrow(), ["light 0", "light 1"]);
+//!
+//! // Dropping the scope tears them down.
+//! drop(component);
+//! assert!(clicks.upgrade().is_none());
+//! theme.set("dark");
+//! assert_eq!(log.borrow().len(), 2);
+//! ```
+
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    Effect,
+    effect::CleanupFn,
+    effect_stack::{effect_depth, effect_peak, effect_suspended},
+    memo_stack,
+};
+
+/// A scope running, with the depths of the effect and memo stacks when it started.
+struct Frame {
+    scope: Weak<Scope>,
+    effect_depth: usize,
+    memo_depth: usize,
+}
+
+thread_local! {
+    static SCOPE_STACK: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The running scopes of a runtime that is not entered, see [`Runtime`](crate::Runtime).
+#[derive(Default)]
+pub(crate) struct State(Vec<Frame>);
+
+/// Exchanges the running scopes with those of the runtime being entered or left.
+pub(crate) fn swap(state: &mut State) {
+    SCOPE_STACK.with_borrow_mut(|stack| std::mem::swap(stack, &mut state.0))
+}
+
+/// Owns the signals, memos and effects created inside it. See the [module docs](self).
+pub struct Scope {
+    nodes: RefCell<Vec<Rc<dyn Any>>>,
+    effects: RefCell<Vec<Rc<Effect>>>,
+    children: RefCell<Vec<Rc<Scope>>>,
+    cleanups: RefCell<Vec<CleanupFn>>,
+    disposed: Cell<bool>,
+}
+
+impl Scope {
+    /// Creates a scope.
+    ///
+    /// A scope created while another scope runs is owned by it, and disposed with it.
+    pub fn new() -> Rc<Self> {
+        let scope = Rc::new(Scope {
+            nodes: RefCell::new(Vec::new()),
+            effects: RefCell::new(Vec::new()),
+            children: RefCell::new(Vec::new()),
+            cleanups: RefCell::new(Vec::new()),
+            disposed: Cell::new(false),
+        });
+        if let Some(parent) = current() {
+            parent.children.borrow_mut().push(scope.clone());
+        }
+        scope
+    }
+
+    /// Runs `f`, making this scope own the signals, memos, effects and scopes it creates.
+    pub fn run<R>(self: &Rc<Self>, f: impl FnOnce(&Rc<Self>) -> R) -> R {
+        let frame = Frame {
+            scope: Rc::downgrade(self),
+            effect_depth: effect_depth(),
+            memo_depth: memo_stack::depth(),
+        };
+        SCOPE_STACK.with_borrow_mut(|stack| stack.push(frame));
+
+        // The frame is popped even if `f` panics.
+        struct Pop;
+
+        impl Drop for Pop {
+            fn drop(&mut self) {
+                SCOPE_STACK.with_borrow_mut(Vec::pop);
+            }
+        }
+
+        let _pop = Pop;
+        f(self)
+    }
+
+    /// Disposes the effects owned by this scope, releases its signals and memos, and
+    /// disposes the scopes created inside it. Then calls the closures registered with
+    /// [`on_cleanup`] while it ran, in the order they were registered.
+    pub fn dispose(&self) {
+        let effects = self.effects.take();
+        for effect in &effects {
+            effect.dispose();
+        }
+        drop(effects);
+        drop(self.nodes.take());
+        for child in self.children.take() {
+            child.dispose();
+        }
+        for cleanup in self.cleanups.take() {
+            effect_suspended(cleanup);
+        }
+        self.disposed.set(true);
+    }
+
+    /// Returns whether the scope was [disposed](Self::dispose).
+    pub fn is_disposed(&self) -> bool {
+        self.disposed.get()
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        self.dispose();
+    }
+}
+
+/// Registers `f` to be called when the current reactive context is torn down.
+///
+/// Called while an effect runs, `f` is called before the effect runs again, or when it
+/// is disposed or dropped, like a [cleanup](crate::effect::Cleanup) returned by the
+/// effect. Called by a closure run by a [`Scope`], `f` is called when the scope is
+/// disposed. Elsewhere, `f` is dropped without being called.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use reactive_cache::{on_cleanup, prelude::*};
+///
+/// let size = Signal::new(64);
+/// let buffers = Rc::new(RefCell::new(Vec::new()));
+/// let effect = Effect::new({
+///     let (size, buffers) = (size.clone(), buffers.clone());
+///     move || {
+///         let size = *size.get();
+///         buffers.borrow_mut().push(size);
+///         let buffers = buffers.clone();
+///         on_cleanup(move || buffers.borrow_mut().retain(|b| *b != size));
+///     }
+/// });
+///
+/// size.set(128);
+/// assert_eq!(*buffers.borrow(), [128]);
+///
+/// effect.dispose();
+/// assert!(buffers.borrow().is_empty());
+/// ```
+pub fn on_cleanup(f: impl FnOnce() + 'static) {
+    if let Some(scope) = current() {
+        scope.cleanups.borrow_mut().push(Box::new(f));
+    } else if let Some(effect) = effect_peak().and_then(|e| e.effect.upgrade()) {
+        effect.on_cleanup(Box::new(f));
+    }
+}
+
+/// Returns the scope owning nodes created now, if they are created by the closure it
+/// runs rather than by an effect or a memo.
+fn current() -> Option<Rc<Scope>> {
+    let (scope, depths) = SCOPE_STACK.with_borrow(|stack| {
+        let frame = stack.last()?;
+        Some((frame.scope.clone(), (frame.effect_depth, frame.memo_depth)))
+    })?;
+    if depths != (effect_depth(), memo_stack::depth()) {
+        return None;
+    }
+    scope.upgrade()
+}
+
+/// Makes the current scope, if any, own a signal or a memo.
+pub(crate) fn adopt(node: Rc<dyn Any>) {
+    if let Some(scope) = current() {
+        scope.nodes.borrow_mut().push(node);
+    }
+}
+
+/// Makes the current scope, if any, own a node, and returns it.
+pub(crate) fn owned<N: 'static>(node: Rc<N>) -> Rc<N> {
+    adopt(node.clone());
+    node
+}
+
+/// Makes the current scope, if any, own `effect`.
+pub(crate) fn adopt_effect(effect: &Rc<Effect>) {
+    if let Some(scope) = current() {
+        scope.effects.borrow_mut().push(effect.clone());
+    }
+}
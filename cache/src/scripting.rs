@@ -0,0 +1,161 @@
+//! A bridge exposing named signals to [rhai](https://rhai.rs) scripts.
+//!
+//! Enabled with the `scripting` feature. Signals registered under a name in
+//! [`ScriptSignals`] become reachable from scripts through two functions:
+//!
+//! - `get(name)` returns the current value, tracking it as a dependency
+//!   when called from a script function running as an effect.
+//! - `set(name, value)` sets the value and returns `true` if it changed. Without the
+//!   `nightly` feature, only signals created with
+//!   [`Signal::new_with_eq`](crate::Signal::new_with_eq) compare the values: `set` returns
+//!   `true` for any other signal.
+//!
+//! Script-defined functions are turned into effects with [`effect`]: they run
+//! immediately and again whenever a signal they read through `get` changes.
+//!
+//! # SAFETY
+//!
+//! Like the rest of the crate, the bridge is **not thread-safe** and must be used
+//! from a single thread.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::rc::Rc;
+//! use reactive_cache::{prelude::*, scripting::{self, ScriptSignals}};
+//!
+//! let hp = Signal::new(100_i64);
+//! let warning = Signal::new(false);
+//!
+//! let mut signals = ScriptSignals::new();
+//! signals.insert("player.hp", hp.clone());
+//! signals.insert("ui.low_hp_warning", warning.clone());
+//!
+//! let mut engine = rhai::Engine::new();
+//! scripting::register(&mut engine, signals);
+//!
+//! let engine = Rc::new(engine);
+//! let ast = Rc::new(
+//!     engine
+//!         .compile(
+//!             r#"
+//!             fn update_warning() {
+//!                 set("ui.low_hp_warning", get("player.hp") < 20);
+//!             }
+//!             "#,
+//!         )
+//!         .unwrap(),
+//! );
+//!
+//! let effect = scripting::effect(&engine, &ast, "update_warning");
+//! assert!(!*warning.get());
+//!
+//! engine.run(r#"set("player.hp", 10)"#).unwrap();
+//! assert_eq!(*hp.get(), 10);
+//! assert!(*warning.get());
+//! ```
+
+use std::{collections::HashMap, rc::Rc};
+
+use rhai::{AST, Dynamic, Engine, EvalAltResult, Scope};
+
+use crate::{Effect, Signal};
+
+/// Type-erased access to a signal whose values convert to and from [`Dynamic`].
+trait ScriptSignal {
+    fn get(&self) -> Dynamic;
+    fn set(&self, name: &str, value: Dynamic) -> Result<bool, Box<EvalAltResult>>;
+}
+
+impl<T: Clone + 'static> ScriptSignal for Signal<T> {
+    fn get(&self) -> Dynamic {
+        Dynamic::from(Signal::get(self).clone())
+    }
+
+    fn set(&self, name: &str, value: Dynamic) -> Result<bool, Box<EvalAltResult>> {
+        let type_name = value.type_name();
+        let value = value.try_cast::<T>().ok_or_else(|| {
+            format!("Signal `{name}` cannot be set to a value of type `{type_name}`.")
+        })?;
+        Ok(self.set_value(value))
+    }
+}
+
+/// A set of signals exposed to scripts under string names.
+#[derive(Default)]
+pub struct ScriptSignals {
+    signals: HashMap<String, Rc<dyn ScriptSignal>>,
+}
+
+impl ScriptSignals {
+    /// Creates an empty set of named signals.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exposes `signal` to scripts under `name`, replacing any signal previously
+    /// registered under the same name.
+    pub fn insert<T: Clone + 'static>(&mut self, name: impl Into<String>, signal: Rc<Signal<T>>) {
+        self.signals.insert(name.into(), signal);
+    }
+
+    fn find(&self, name: &str) -> Result<&Rc<dyn ScriptSignal>, Box<EvalAltResult>> {
+        self.signals
+            .get(name)
+            .ok_or_else(|| format!("No signal is registered as `{name}`.").into())
+    }
+}
+
+/// Registers the `get(name)` and `set(name, value)` script functions on `engine`,
+/// resolving names against `signals`.
+pub fn register(engine: &mut Engine, signals: ScriptSignals) {
+    let signals = Rc::new(signals);
+
+    engine.register_fn("get", {
+        let signals = signals.clone();
+        move |name: &str| -> Result<Dynamic, Box<EvalAltResult>> { Ok(signals.find(name)?.get()) }
+    });
+
+    engine.register_fn(
+        "set",
+        move |name: &str, value: Dynamic| -> Result<bool, Box<EvalAltResult>> {
+            signals.find(name)?.set(name, value)
+        },
+    );
+}
+
+/// Creates an effect running the script function `fn_name` defined in `ast`.
+///
+/// The function takes no arguments. It runs immediately and again whenever a
+/// signal it read through `get` changes. Errors raised by the script are reported
+/// like a panic of the effect: to its [`ErrorBoundary`](crate::boundary::ErrorBoundary), or else
+/// to the panic handler of the `panic-handler` feature. Without either, they
+/// unwind through the code that changed the signal.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use reactive_cache::{boundary::ErrorBoundary, prelude::*, scripting::{self, ScriptSignals}};
+///
+/// let mut engine = rhai::Engine::new();
+/// scripting::register(&mut engine, ScriptSignals::new());
+/// let engine = Rc::new(engine);
+/// let ast = Rc::new(engine.compile(r#"fn broken() { get("missing") }"#).unwrap());
+///
+/// let boundary = ErrorBoundary::new(|_| {});
+/// # let hook = std::panic::take_hook();
+/// # std::panic::set_hook(Box::new(|_| {}));
+/// let _effect = boundary.scope(|| scripting::effect(&engine, &ast, "broken"));
+/// # std::panic::set_hook(hook);
+/// assert!(boundary.error().unwrap().message.contains("missing"));
+/// ```
+pub fn effect(engine: &Rc<Engine>, ast: &Rc<AST>, fn_name: &str) -> Rc<Effect> {
+    let (engine, ast, fn_name) = (engine.clone(), ast.clone(), fn_name.to_string());
+
+    Effect::new(move || {
+        if let Err(err) = engine.call_fn::<Dynamic>(&mut Scope::new(), &ast, &fn_name, ()) {
+            panic!("Script effect `{fn_name}` failed: {err}");
+        }
+    })
+}
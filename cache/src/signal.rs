@@ -1,9 +1,15 @@
 use std::{
-    cell::{Ref, RefCell},
-    rc::{Rc, Weak},
+    cell::{Ref, RefCell, RefMut},
+    ops::{Add, Sub},
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
 };
 
-use crate::{Effect, IMemo, IObservable, effect_stack::EffectStackEntry};
+use crate::{
+    Durability, EffectOptions, Memo, NodeId, Observable, ReactiveError, Tracker,
+    combinators::rate_limited, inspect::DebugValue, runtime,
+};
 
 /// A reactive signal that holds a value, tracks dependencies, and triggers effects.
 ///
@@ -19,7 +25,9 @@ use crate::{Effect, IMemo, IObservable, effect_stack::EffectStackEntry};
 ///
 /// # Type Parameters
 ///
-/// - `T`: The type of the value stored in the signal. Must implement `Eq`.
+/// - `T`: The type of the value stored in the signal. [`set`](SignalSetter::set) requires
+///   and compares `PartialEq` values; other values are set with [`Signal::set_always`].
+///   With the `nightly` feature, `set` accepts any value and compares those implementing `Eq`.
 ///
 /// # Memory Management Note
 ///
@@ -71,58 +79,111 @@ pub struct Signal<T> {
     /// Current value of the signal.
     value: RefCell<T>,
 
-    /// Memoized computations that depend on this signal.
-    /// Weak references are used to avoid memory leaks.
-    dependents: RefCell<Vec<Weak<dyn IMemo>>>,
+    /// Tells whether two values are equal, in place of `==`, see [`Signal::new_with_eq`].
+    eq: Option<Comparator<T>>,
 
-    /// Effects that depend on this signal.
-    /// Weak references prevent retaining dropped effects.
-    effects: RefCell<Vec<Weak<Effect>>>,
+    /// Memoized computations and effects that depend on this signal.
+    /// Weak references are used to avoid memory leaks.
+    tracker: Tracker,
 }
 
+type Comparator<T> = Box<dyn Fn(&T, &T) -> bool>;
+
+/// Creates a `Signal` holding `T::default()`.
+///
+/// Since `Rc<T>` implements `Default` whenever `T` does,
+/// `Rc::<Signal<T>>::default()` works as well.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use reactive_cache::prelude::*;
+///
+/// let signal: Rc<Signal<i32>> = Default::default();
+/// assert_eq!(*signal.get(), 0);
+/// ```
 impl<T: Default> Default for Signal<T> {
+    #[track_caller]
     fn default() -> Self {
+        Self::from(T::default())
+    }
+}
+
+/// Creates a `Signal` holding the given value.
+///
+/// Combined with `From<T> for Rc<T>`, a shared signal can be built with `.into()`.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use reactive_cache::prelude::*;
+///
+/// let signal: Rc<Signal<String>> = Signal::from("Alice".to_string()).into();
+/// assert_eq!(*signal.get(), "Alice");
+/// ```
+impl<T> From<T> for Signal<T> {
+    #[track_caller]
+    fn from(value: T) -> Self {
+        runtime::increment(&runtime::SIGNALS);
         Self {
-            value: Default::default(),
-            dependents: Default::default(),
-            effects: Default::default(),
+            value: value.into(),
+            eq: None,
+            tracker: Tracker::new(),
         }
     }
 }
 
+/// Collects an iterator into a `Signal` holding any collection type.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use reactive_cache::prelude::*;
+///
+/// let signal: Signal<Vec<i32>> = (1..=3).collect();
+/// assert_eq!(*signal.get(), [1, 2, 3]);
+///
+/// let signal: Rc<Signal<String>> = Rc::new("abc".chars().rev().collect());
+/// assert_eq!(*signal.get(), "cba");
+/// ```
+impl<A, T: FromIterator<A>> FromIterator<A> for Signal<T> {
+    #[track_caller]
+    fn from_iter<I: IntoIterator<Item = A>>(iter: I) -> Self {
+        Self::from(T::from_iter(iter))
+    }
+}
+
 impl<T> Signal<T> {
-    /// Re-runs all dependent effects that are still alive.
-    ///
-    /// This is triggered after the signal's value has changed.  
-    /// Dead effects (already dropped) are cleaned up automatically.
-    fn flush_effects(&self) {
-        // When triggering an Effect, dependencies are not collected for that Effect.
-        self.effects.borrow_mut().retain(|w| {
-            if let Some(e) = w.upgrade() {
-                crate::effect::run_untracked(&e);
-                true
-            } else {
-                false
-            }
-        });
+    /// Moves the signal into an `Rc`, owned by the current [`Scope`](crate::Scope) if any.
+    fn into_owned(self) -> Rc<Self>
+    where
+        T: 'static,
+    {
+        crate::scope::owned(Rc::new(self))
     }
 
     /// Called after the value is updated.  
     /// Triggers all dependent effects.
     #[allow(non_snake_case)]
     fn OnPropertyChanged(&self) {
-        self.flush_effects()
+        self.notify_changed()
     }
 
     /// Called before the value is updated.  
     /// Invalidates all memoized computations depending on this signal.
     #[allow(non_snake_case)]
     fn OnPropertyChanging(&self) {
-        self.invalidate()
+        self.notify_changing()
     }
 
     /// Creates a new `Signal` with the given initial value.
     ///
+    /// A signal created while a [`Scope`](crate::Scope) runs is owned by it, as a
+    /// `Rc<dyn Any>`, so `T` must be `'static`.
+    ///
     /// # Examples
     ///
     /// Basic usage:
@@ -159,18 +220,63 @@ impl<T> Signal<T> {
     /// assert_eq!(*vm.counter.get(), 1);
     /// assert_eq!(*vm.name.get(), "Bob");
     /// ```
-    pub fn new(value: T) -> Rc<Self> {
-        Signal {
-            value: value.into(),
-            dependents: vec![].into(),
-            effects: vec![].into(),
-        }
-        .into()
+    #[track_caller]
+    pub fn new(value: T) -> Rc<Self>
+    where
+        T: 'static,
+    {
+        Signal::from(value).into_owned()
+    }
+
+    /// Creates a new `Signal` with the given initial value, whose [`set`](SignalSetter::set)
+    /// compares values with `eq` instead of `==`.
+    ///
+    /// Setting a value `eq` deems equal to the current one keeps the current value and
+    /// notifies nothing. This allows comparing floats approximately, comparing structs by
+    /// a key field, or comparing types that are only `PartialEq`.
+    ///
+    /// Like [`Signal::new`], this requires `T: 'static`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let temperature = Signal::new_with_eq(20.0_f64, |a, b| (a - b).abs() < 0.1);
+    /// assert!(!temperature.set(20.05));
+    /// assert_eq!(*temperature.get(), 20.0);
+    ///
+    /// assert!(temperature.set(21.0));
+    /// assert_eq!(*temperature.get(), 21.0);
+    /// ```
+    #[track_caller]
+    pub fn new_with_eq(value: T, eq: impl Fn(&T, &T) -> bool + 'static) -> Rc<Self>
+    where
+        T: 'static,
+    {
+        let mut signal = Signal::from(value);
+        signal.eq = Some(Box::new(eq));
+        signal.into_owned()
+    }
+
+    /// Returns the unique id of this signal.
+    pub fn id(&self) -> NodeId {
+        self.tracker.id()
+    }
+
+    /// Sets how often this signal is expected to change. See [`Durability`].
+    pub fn set_durability(&self, durability: Durability) {
+        self.tracker.set_durability(durability)
     }
 
     /// Gets a reference to the current value, tracking dependencies
     /// and effects if inside a reactive context.
     ///
+    /// The signal cannot be set while the returned `Ref` is alive.
+    /// Prefer [`Signal::with`], which scopes the borrow to a closure. `Copy` values are
+    /// better held by a [`CopySignal`](crate::CopySignal), which stores them in a `Cell`
+    /// and returns them by value without borrowing.
+    ///
     /// # Examples
     ///
     /// ```
@@ -179,67 +285,446 @@ impl<T> Signal<T> {
     /// let signal = Signal::new(42);
     /// assert_eq!(*signal.get(), 42);
     /// ```
+    ///
+    /// Setting the signal while the `Ref` is held panics:
+    ///
+    /// ```should_panic
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let signal = Signal::new(vec![1]);
+    /// let value = signal.get();
+    /// signal.set(vec![2]); // Panics: `value` still borrows the signal.
+    /// ```
     pub fn get(&self) -> Ref<'_, T> {
-        self.dependency_collection();
-
-        // Track effects in the call stack
-        if let Some(EffectStackEntry {
-            effect: e,
-            collecting,
-        }) = crate::effect_stack::effect_peak()
-            && *collecting
-            && !self.effects.borrow().iter().any(|w| Weak::ptr_eq(w, e))
-        {
-            self.effects.borrow_mut().push(e.clone());
+        self.track();
+
+        self.value.borrow()
+    }
+
+    /// Gets a reference to the current value like [`Signal::get`], or returns an error
+    /// instead of panicking if the value is being updated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{ReactiveError, prelude::*};
+    ///
+    /// let signal = Signal::new(vec![1]);
+    /// assert_eq!(*signal.try_get().unwrap(), [1]);
+    ///
+    /// signal.update(|_| {
+    ///     assert_eq!(signal.try_get().err(), Some(ReactiveError::Borrowed(signal.id())));
+    /// });
+    /// ```
+    pub fn try_get(&self) -> Result<Ref<'_, T>, ReactiveError> {
+        self.track();
+
+        self.value
+            .try_borrow()
+            .map_err(|_| ReactiveError::Borrowed(self.id()))
+    }
+
+    /// Sets the value like [`SignalSetter::set`], or returns an error instead of
+    /// panicking if the value is still borrowed, for example by a `Ref` returned from
+    /// [`Signal::get`].
+    ///
+    /// Nothing is notified when an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{ReactiveError, prelude::*};
+    ///
+    /// let signal = Signal::new(1);
+    /// let value = signal.get();
+    /// assert_eq!(signal.try_set(2), Err(ReactiveError::Borrowed(signal.id())));
+    ///
+    /// drop(value);
+    /// assert_eq!(signal.try_set(2), Ok(true));
+    /// ```
+    pub fn try_set(&self, value: T) -> Result<bool, ReactiveError>
+    where
+        Self: SignalSetter<T>,
+    {
+        if self.value.try_borrow_mut().is_err() {
+            return Err(ReactiveError::Borrowed(self.id()));
         }
+        Ok(self.set(value))
+    }
 
+    /// Gets a reference to the current value, without tracking.
+    ///
+    /// No memo or effect is registered as a dependent, so the caller is not re-run when
+    /// the value changes. Like with [`Signal::get`], the signal cannot be set while the
+    /// returned `Ref` is alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let count = Signal::new(0);
+    /// let verbose = Signal::new(false);
+    /// let logged = Rc::new(Cell::new(0));
+    ///
+    /// let effect = Effect::new({
+    ///     let (count, verbose, logged) = (count.clone(), verbose.clone(), logged.clone());
+    ///     move || {
+    ///         let _ = count.get();
+    ///         if *verbose.get_untracked() {
+    ///             logged.set(logged.get() + 1);
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// // The effect does not depend on the flag.
+    /// verbose.set(true);
+    /// assert_eq!(logged.get(), 0);
+    /// count.set(1);
+    /// assert_eq!(logged.get(), 1);
+    /// ```
+    pub fn get_untracked(&self) -> Ref<'_, T> {
         self.value.borrow()
     }
-}
 
-pub trait SignalSetter<T> {
-    fn set(&self, value: T) -> bool;
-}
+    /// Calls `f` with a reference to the current value, tracking dependencies
+    /// and effects if inside a reactive context.
+    ///
+    /// Unlike [`Signal::get`], the borrow ends when `f` returns,
+    /// so it cannot accidentally outlive a later `set()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let names = Signal::new(vec!["Alice".to_string()]);
+    /// let len = names.with(|names| names.len());
+    ///
+    /// names.set(vec![]);
+    /// assert_eq!(len, 1);
+    /// assert!(names.with(Vec::is_empty));
+    /// ```
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.track();
 
-impl<T> SignalSetter<T> for Signal<T> {
-    /// Sets the value of the signal.
+        f(&self.value.borrow())
+    }
+
+    /// Calls `f` with a reference to the current value, without tracking.
     ///
-    /// For generic types `T` that do not support comparison, they are treated as
-    /// always changing, so the value is always set and `true` is always returned.
-    /// All dependent memos are invalidated and dependent effects were triggered.
+    /// No memo or effect is registered as a dependent, so the caller is
+    /// not re-run when the value changes.
     ///
     /// # Examples
     ///
     /// ```
+    /// use std::{cell::Cell, rc::Rc};
     /// use reactive_cache::prelude::*;
     ///
-    /// #[derive(Debug)]
-    /// struct Num(i32);
+    /// let signal = Signal::new(1);
+    /// let runs = Rc::new(Cell::new(0));
     ///
-    /// let signal = Signal::new(Num(5));
-    /// assert_eq!(signal.set(Num(10)), true);
-    /// assert_eq!(signal.get().0, 10);
+    /// let effect = Effect::new({
+    ///     let (signal, runs) = (signal.clone(), runs.clone());
+    ///     move || {
+    ///         runs.set(runs.get() + 1);
+    ///         signal.with_untracked(|v| assert!(*v > 0));
+    ///     }
+    /// });
     ///
-    /// // Setting to the same value always return true and trigger all effects.
-    /// assert_eq!(signal.set(Num(10)), true);
+    /// signal.set(2);
+    /// assert_eq!(runs.get(), 1);
     /// ```
-    default fn set(&self, value: T) -> bool {
+    pub fn with_untracked<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.value.borrow())
+    }
+
+    /// Mutates the value in place with `f`, then notifies dependents.
+    ///
+    /// Unlike [`SignalSetter::set`], the value is not cloned nor compared: memos are
+    /// invalidated and effects triggered even if `f` left it unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let items = Signal::new(vec![1, 2]);
+    /// let total = Memo::new({
+    ///     let items = items.clone();
+    ///     move || items.with(|items| items.iter().sum::<i32>())
+    /// });
+    /// assert_eq!(total.get(), 3);
+    ///
+    /// items.update(|items| items.push(3));
+    /// assert_eq!(total.get(), 6);
+    /// ```
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        self.update_returning(f)
+    }
+
+    /// Mutates the value in place with `f`, then notifies dependents, like
+    /// [`Signal::update`], and returns the result of `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let queue = Signal::new(vec!["build", "test"]);
+    /// assert_eq!(queue.update_returning(|queue| queue.remove(0)), "build");
+    /// assert_eq!(*queue.get(), ["test"]);
+    /// ```
+    pub fn update_returning<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        self.OnPropertyChanging();
+
+        let result = {
+            let mut slot = self.borrow_mut();
+            let old = runtime::recording().then(|| slot.debug_string());
+            let result = f(&mut slot);
+            if let Some(old) = old {
+                runtime::record_change(&self.tracker, old, slot.debug_string());
+            }
+            result
+        };
+
+        self.OnPropertyChanged();
+
+        result
+    }
+
+    /// Sets the value of the signal without comparing it with the current one, so that
+    /// dependent memos are always invalidated and dependent effects triggered.
+    ///
+    /// Useful for values whose equality does not tell whether they changed, such as
+    /// floats that are `NaN` or handles with interior mutability.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let tick = Signal::new(0);
+    /// let runs = Rc::new(Cell::new(0));
+    /// let effect = Effect::new({
+    ///     let (tick, runs) = (tick.clone(), runs.clone());
+    ///     move || {
+    ///         let _ = tick.get();
+    ///         runs.set(runs.get() + 1);
+    ///     }
+    /// });
+    ///
+    /// assert!(!tick.set(0));
+    /// assert_eq!(runs.get(), 1);
+    /// tick.set_always(0);
+    /// assert_eq!(runs.get(), 2);
+    /// ```
+    pub fn set_always(&self, value: T) {
         self.OnPropertyChanging();
 
-        *self.value.borrow_mut() = value;
+        self.replace(value);
+
+        self.OnPropertyChanged();
+    }
+
+    /// Notifies dependents that the value changed, without replacing it.
+    ///
+    /// Call it after mutating state the signal refers to but does not own, for example
+    /// through a handle with interior mutability.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::RefCell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let buffer = Rc::new(RefCell::new(String::new()));
+    /// let text = Signal::new(buffer.clone());
+    /// let len = Memo::new({
+    ///     let text = text.clone();
+    ///     move || text.get().borrow().len()
+    /// });
+    /// assert_eq!(len.get(), 0);
+    ///
+    /// buffer.borrow_mut().push_str("hello");
+    /// text.mark_changed();
+    /// assert_eq!(len.get(), 5);
+    /// ```
+    pub fn mark_changed(&self) {
+        self.OnPropertyChanging();
+        self.OnPropertyChanged();
+    }
+
+    /// Replaces the stored value.
+    fn replace(&self, value: T) {
+        let mut slot = self.borrow_mut();
+        if runtime::recording() {
+            runtime::record_change(&self.tracker, slot.debug_string(), value.debug_string());
+        }
+        *slot = value;
+    }
+
+    /// Borrows the stored value for writing, with a helpful message if it is still borrowed.
+    fn borrow_mut(&self) -> RefMut<'_, T> {
+        let Ok(slot) = self.value.try_borrow_mut() else {
+            panic!(
+                "Signal {} created at {} was set while its value is still borrowed, \
+                 for example by a `Ref` returned from `get()`. \
+                 Drop the `Ref` before setting the signal, or read it with `Signal::with`.",
+                self.id(),
+                self.tracker.location()
+            );
+        };
+        slot
+    }
+
+    /// Returns a detached clone of the current value.
+    ///
+    /// The value is read without tracking: no memo or effect is registered
+    /// as a dependent. The result is a plain copy suitable for handing to other
+    /// threads, serializers or undo stacks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let signal = Signal::new(vec![1, 2]);
+    /// let runs = Rc::new(Cell::new(0));
+    ///
+    /// let effect = Effect::new({
+    ///     let (signal, runs) = (signal.clone(), runs.clone());
+    ///     move || {
+    ///         runs.set(runs.get() + 1);
+    ///         let _ = signal.clone_detached();
+    ///     }
+    /// });
+    ///
+    /// let snapshot: Vec<i32> = signal.clone_detached();
+    /// signal.set(vec![3]);
+    ///
+    /// assert_eq!(snapshot, [1, 2]);
+    /// assert_eq!(runs.get(), 1);
+    /// ```
+    pub fn clone_detached(&self) -> T
+    where
+        T: Clone,
+    {
+        self.value.borrow().clone()
+    }
+}
+
+pub trait SignalSetter<T> {
+    fn set(&self, value: T) -> bool;
+}
+
+impl<T> Signal<T> {
+    /// Sets the value and notifies dependents, unless the comparator of the signal, or
+    /// `eq` for signals without one, tells that it did not change.
+    fn set_unless(&self, value: T, eq: impl FnOnce(&T, &T) -> bool) -> bool {
+        let unchanged = match &self.eq {
+            Some(same) => same(&self.value.borrow(), &value),
+            None => eq(&self.value.borrow(), &value),
+        };
+        if unchanged {
+            return false;
+        }
+
+        self.OnPropertyChanging();
+
+        self.replace(value);
 
         self.OnPropertyChanged();
 
         true
     }
+
+    /// Sets the value from code generic over `T`, like [`SignalSetter::set`].
+    ///
+    /// Without the `nightly` feature, whether `T` can be compared is unknown here, so the
+    /// value is only compared with the comparator of the signal, if any.
+    pub(crate) fn set_value(&self, value: T) -> bool {
+        #[cfg(feature = "nightly")]
+        return SignalSetter::set(self, value);
+        #[cfg(not(feature = "nightly"))]
+        return self.set_unless(value, |_, _| false);
+    }
 }
 
+#[cfg(feature = "nightly")]
+impl<T> SignalSetter<T> for Signal<T> {
+    specializable! {
+        /// Sets the value of the signal.
+        ///
+        /// For generic types `T` that do not support comparison, they are treated as
+        /// always changing, so the value is always set and `true` is always returned,
+        /// unless the signal was created by [`Signal::new_with_eq`].
+        /// All dependent memos are invalidated and dependent effects were triggered.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use reactive_cache::prelude::*;
+        ///
+        /// #[derive(Debug)]
+        /// struct Num(i32);
+        ///
+        /// let signal = Signal::new(Num(5));
+        /// assert_eq!(signal.set(Num(10)), true);
+        /// assert_eq!(signal.get().0, 10);
+        ///
+        /// // Setting to the same value always return true and trigger all effects.
+        /// assert_eq!(signal.set(Num(10)), true);
+        /// ```
+        fn set(&self, value: T) -> bool {
+            self.set_unless(value, |_, _| false)
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
 impl<T: Eq> SignalSetter<T> for Signal<T> {
     /// Sets the value of the signal.
     ///
     /// Returns `true` if the value changed, all dependent memos are
     /// invalidated and dependent effects were triggered.
     ///
+    /// Values are compared with `==`, or with the comparator of a signal created by
+    /// [`Signal::new_with_eq`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let signal = Signal::new(5);
+    /// assert_eq!(signal.set(10), true);
+    /// assert_eq!(*signal.get(), 10);
+    ///
+    /// // Setting to the same value returns false
+    /// assert_eq!(signal.set(10), false);
+    /// ```
+    fn set(&self, value: T) -> bool {
+        self.set_unless(value, T::eq)
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T: PartialEq> SignalSetter<T> for Signal<T> {
+    /// Sets the value of the signal.
+    ///
+    /// Returns `true` if the value changed, all dependent memos are
+    /// invalidated and dependent effects were triggered.
+    ///
+    /// Values are compared with `==`, or with the comparator of a signal created by
+    /// [`Signal::new_with_eq`]. Without the `nightly` feature, only values implementing
+    /// `PartialEq` can be set this way; other values are set with [`Signal::set_always`]
+    /// or [`Signal::update`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -253,22 +738,523 @@ impl<T: Eq> SignalSetter<T> for Signal<T> {
     /// assert_eq!(signal.set(10), false);
     /// ```
     fn set(&self, value: T) -> bool {
-        if *self.value.borrow() == value {
+        self.set_unless(value, T::eq)
+    }
+}
+
+impl<T: Clone> Signal<Rc<T>> {
+    /// Updates a shared value in place using copy-on-write semantics.
+    ///
+    /// The closure receives `&mut T` obtained through [`Rc::make_mut`], so readers
+    /// that still hold the previous `Rc<T>` keep observing the old snapshot.
+    ///
+    /// If nothing else holds the value, it is mutated in place without being cloned,
+    /// and dependents are always notified, like [`Signal::update`]: no copy of the old
+    /// value is left to compare the mutated one with, and keeping one would cost the clone
+    /// this method avoids. Otherwise the mutated copy is written through
+    /// [`SignalSetter::set`], which compares it with the old value like any other value
+    /// set, and notifies nothing if it is unchanged.
+    ///
+    /// Returns `true` if dependents were notified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let signal = Signal::new(Rc::new(vec![1, 2, 3]));
+    /// let snapshot = Rc::clone(&signal.get());
+    ///
+    /// assert!(signal.update_make_mut(|v| v.push(4)));
+    /// assert_eq!(**signal.get(), [1, 2, 3, 4]);
+    ///
+    /// // The previously obtained `Rc` still points to the old value.
+    /// assert_eq!(*snapshot, [1, 2, 3]);
+    ///
+    /// // While the value is shared, a no-op mutation is detected as unchanged.
+    /// let snapshot = Rc::clone(&signal.get());
+    /// assert!(!signal.update_make_mut(|_| {}));
+    /// ```
+    pub fn update_make_mut(&self, f: impl FnOnce(&mut T)) -> bool
+    where
+        Self: SignalSetter<Rc<T>>,
+    {
+        // Cloning the `Rc` to compare it would make `make_mut` always copy the value.
+        if Rc::strong_count(&self.value.borrow()) == 1 {
+            self.update(|rc| f(Rc::make_mut(rc)));
+            return true;
+        }
+
+        let mut rc = Rc::clone(&self.value.borrow());
+        f(Rc::make_mut(&mut rc));
+        self.set(rc)
+    }
+}
+
+/// Creates a `Signal<Rc<str>>` from a string slice.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use reactive_cache::prelude::*;
+///
+/// let name: Signal<Rc<str>> = "Alice".into();
+/// assert_eq!(&*name.get_str(), "Alice");
+/// ```
+impl From<&str> for Signal<Rc<str>> {
+    #[track_caller]
+    fn from(value: &str) -> Self {
+        Self::from(Rc::<str>::from(value))
+    }
+}
+
+/// Creates a `Signal<Arc<[u8]>>` from a byte slice.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use reactive_cache::prelude::*;
+///
+/// let payload: Signal<Arc<[u8]>> = b"abc".as_slice().into();
+/// assert_eq!(&*payload.get_bytes(), b"abc");
+/// ```
+impl From<&[u8]> for Signal<Arc<[u8]>> {
+    #[track_caller]
+    fn from(value: &[u8]) -> Self {
+        Self::from(Arc::<[u8]>::from(value))
+    }
+}
+
+/// Helpers for shared, immutable text.
+///
+/// Storing text as `Rc<str>` makes every read a reference count increment
+/// instead of a `String` clone. Values are compared by content, so setting
+/// equal text does not notify dependents.
+impl Signal<Rc<str>> {
+    /// Creates a new `Signal` holding a copy of `value`.
+    #[track_caller]
+    pub fn new_str(value: &str) -> Rc<Self> {
+        Signal::from(value).into_owned()
+    }
+
+    /// Returns a shared handle to the current text, tracking dependencies
+    /// and effects if inside a reactive context.
+    ///
+    /// Unlike [`Signal::get`], the result does not borrow the signal.
+    pub fn get_str(&self) -> Rc<str> {
+        self.with(Rc::clone)
+    }
+
+    /// Sets the text, allocating only if it differs from the current one.
+    ///
+    /// Returns `true` if the text changed and dependents were notified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let title = Signal::new_str("Draft");
+    /// let before = title.get_str();
+    ///
+    /// assert!(!title.set_str("Draft"));
+    /// assert!(std::rc::Rc::ptr_eq(&before, &title.get_str()));
+    ///
+    /// assert!(title.set_str("Final"));
+    /// assert_eq!(&*title.get_str(), "Final");
+    /// ```
+    pub fn set_str(&self, value: &str) -> bool {
+        if &**self.value.borrow() == value {
             return false;
         }
+        self.set(value.into())
+    }
+}
 
-        self.OnPropertyChanging();
+/// Helpers for shared, immutable binary payloads.
+///
+/// Storing bytes as `Arc<[u8]>` makes every read a reference count increment
+/// and lets the payload be handed to other threads. Values are compared by
+/// content, so setting equal bytes does not notify dependents.
+impl Signal<Arc<[u8]>> {
+    /// Creates a new `Signal` holding a copy of `value`.
+    #[track_caller]
+    pub fn new_bytes(value: &[u8]) -> Rc<Self> {
+        Signal::from(value).into_owned()
+    }
 
-        *self.value.borrow_mut() = value;
+    /// Returns a shared handle to the current bytes, tracking dependencies
+    /// and effects if inside a reactive context.
+    ///
+    /// Unlike [`Signal::get`], the result does not borrow the signal.
+    pub fn get_bytes(&self) -> Arc<[u8]> {
+        self.with(Arc::clone)
+    }
 
-        self.OnPropertyChanged();
+    /// Sets the bytes, allocating only if they differ from the current ones.
+    ///
+    /// Returns `true` if the bytes changed and dependents were notified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let frame = Signal::new_bytes(&[1, 2, 3]);
+    /// assert!(!frame.set_bytes(&[1, 2, 3]));
+    /// assert!(frame.set_bytes(&[4]));
+    /// assert_eq!(&*frame.get_bytes(), [4]);
+    /// ```
+    pub fn set_bytes(&self, value: &[u8]) -> bool {
+        if &**self.value.borrow() == value {
+            return false;
+        }
+        self.set(value.into())
+    }
+}
 
-        true
+/// Rate-limited views of a signal.
+///
+/// The returned memos follow the signal through deferred effects,
+/// so they only pick up new values during [`flush`](crate::flush).
+impl<T: Clone + 'static> Signal<T> {
+    /// Returns a memo following this signal once it has stopped changing for `delay`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let query = Signal::new("".to_string());
+    /// let settled = query.debounced(Duration::from_millis(20));
+    ///
+    /// query.set("r".into());
+    /// query.set("rust".into());
+    /// reactive_cache::flush();
+    /// assert_eq!(settled.get(), "");
+    ///
+    /// std::thread::sleep(Duration::from_millis(30));
+    /// reactive_cache::flush();
+    /// assert_eq!(settled.get(), "rust");
+    /// ```
+    pub fn debounced(self: &Rc<Self>, delay: Duration) -> Rc<Memo<T>> {
+        rate_limited(self.clone(), EffectOptions::new().debounce(delay))
+    }
+
+    /// Returns a memo following this signal, updated at most once per `interval`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let position = Signal::new(0);
+    /// let sampled = position.throttled(Duration::from_millis(20));
+    ///
+    /// std::thread::sleep(Duration::from_millis(30));
+    /// position.set(1);
+    /// reactive_cache::flush();
+    /// assert_eq!(sampled.get(), 1);
+    ///
+    /// // Too soon after the previous update.
+    /// position.set(2);
+    /// reactive_cache::flush();
+    /// assert_eq!(sampled.get(), 1);
+    ///
+    /// std::thread::sleep(Duration::from_millis(30));
+    /// reactive_cache::flush();
+    /// assert_eq!(sampled.get(), 2);
+    /// ```
+    pub fn throttled(self: &Rc<Self>, interval: Duration) -> Rc<Memo<T>> {
+        rate_limited(self.clone(), EffectOptions::new().throttle(interval))
+    }
+}
+
+/// Read-modify-write helpers for numeric signals.
+///
+/// Each helper reads the value, computes the new one and writes it back through
+/// [`SignalSetter::set`], notifying dependents at most once.
+/// They return `true` if the value changed.
+impl<T: Copy + Add<Output = T>> Signal<T> {
+    /// Adds `n` to the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let total = Signal::new(1.5);
+    /// total.add(2.0);
+    /// assert_eq!(*total.get(), 3.5);
+    /// ```
+    pub fn add(&self, n: T) -> bool
+    where
+        Self: SignalSetter<T>,
+    {
+        let value = *self.value.borrow();
+        self.set(value + n)
     }
 }
 
-impl<T> IObservable for Signal<T> {
-    fn dependents(&self) -> &RefCell<Vec<Weak<dyn IMemo>>> {
-        &self.dependents
+impl<T: Copy + Sub<Output = T>> Signal<T> {
+    /// Subtracts `n` from the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let hp = Signal::new(100);
+    /// assert!(hp.sub(30));
+    /// assert!(!hp.sub(0));
+    /// assert_eq!(*hp.get(), 70);
+    /// ```
+    pub fn sub(&self, n: T) -> bool
+    where
+        Self: SignalSetter<T>,
+    {
+        let value = *self.value.borrow();
+        self.set(value - n)
+    }
+}
+
+impl<T: Integer> Signal<T> {
+    /// Adds one to the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::Cell, rc::Rc};
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let clicks = Signal::new(0_u32);
+    /// let runs = Rc::new(Cell::new(0));
+    /// let effect = Effect::new({
+    ///     let (clicks, runs) = (clicks.clone(), runs.clone());
+    ///     move || {
+    ///         let _ = *clicks.get();
+    ///         runs.set(runs.get() + 1);
+    ///     }
+    /// });
+    ///
+    /// clicks.increment();
+    /// clicks.increment();
+    /// assert_eq!(*clicks.get(), 2);
+    /// assert_eq!(runs.get(), 3);
+    /// ```
+    pub fn increment(&self) -> bool {
+        self.add(T::ONE)
+    }
+
+    /// Subtracts one from the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let lives = Signal::new(3_i8);
+    /// lives.decrement();
+    /// assert_eq!(*lives.get(), 2);
+    /// ```
+    pub fn decrement(&self) -> bool {
+        self.sub(T::ONE)
+    }
+
+    /// Adds `n` to the value, saturating at the numeric bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let volume = Signal::new(250_u8);
+    /// assert!(volume.saturating_add(10));
+    /// assert!(!volume.saturating_add(10));
+    /// assert_eq!(*volume.get(), 255);
+    /// ```
+    pub fn saturating_add(&self, n: T) -> bool {
+        let value = *self.value.borrow();
+        self.set(value.saturating_add(n))
+    }
+
+    /// Subtracts `n` from the value, saturating at the numeric bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let stock = Signal::new(2_u32);
+    /// stock.saturating_sub(5);
+    /// assert_eq!(*stock.get(), 0);
+    /// ```
+    pub fn saturating_sub(&self, n: T) -> bool {
+        let value = *self.value.borrow();
+        self.set(value.saturating_sub(n))
+    }
+
+    /// Adds `n` to the value, wrapping around at the numeric bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let frame = Signal::new(u8::MAX);
+    /// frame.wrapping_add(1);
+    /// assert_eq!(*frame.get(), 0);
+    /// ```
+    pub fn wrapping_add(&self, n: T) -> bool {
+        let value = *self.value.borrow();
+        self.set(value.wrapping_add(n))
+    }
+
+    /// Subtracts `n` from the value, wrapping around at the numeric bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let cursor = Signal::new(0_u16);
+    /// cursor.wrapping_sub(1);
+    /// assert_eq!(*cursor.get(), u16::MAX);
+    /// ```
+    pub fn wrapping_sub(&self, n: T) -> bool {
+        let value = *self.value.borrow();
+        self.set(value.wrapping_sub(n))
+    }
+}
+
+impl<T: PartialEq> Signal<T> {
+    /// Sets the value only if the current value equals `expected`.
+    ///
+    /// Returns `Ok` with the result of [`set`](SignalSetter::set) if the value was
+    /// written, or `Err` with the rejected `new` value if the current value differs.
+    /// Values are compared with `==`, or with the comparator of a signal created by
+    /// [`Signal::new_with_eq`]. The comparison does not register the caller as a
+    /// dependent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// #[derive(Debug, PartialEq, Eq)]
+    /// enum State {
+    ///     Idle,
+    ///     Loading,
+    ///     Done,
+    /// }
+    ///
+    /// let state = Signal::new(State::Idle);
+    /// assert_eq!(state.compare_set(&State::Idle, State::Loading), Ok(true));
+    ///
+    /// // A stale transition is rejected instead of clobbering the current state.
+    /// assert_eq!(state.compare_set(&State::Idle, State::Done), Err(State::Done));
+    /// assert_eq!(*state.get(), State::Loading);
+    /// ```
+    pub fn compare_set(&self, expected: &T, new: T) -> Result<bool, T> {
+        let current = self.value.borrow();
+        let matches = match &self.eq {
+            Some(eq) => eq(&current, expected),
+            None => *current == *expected,
+        };
+        drop(current);
+        if !matches {
+            return Err(new);
+        }
+        Ok(self.set(new))
+    }
+}
+
+impl Signal<bool> {
+    /// Inverts the value, notifying dependents once.
+    ///
+    /// Returns the new value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::prelude::*;
+    ///
+    /// let visible = Signal::new(false);
+    /// assert!(visible.toggle());
+    /// assert!(*visible.get());
+    /// ```
+    pub fn toggle(&self) -> bool {
+        let value = !*self.value.borrow();
+        self.set(value);
+        value
+    }
+}
+
+/// Integer types supported by the numeric helpers of [`Signal`].
+///
+/// This trait is sealed and implemented for all primitive integer types.
+pub trait Integer: Copy + Eq + Add<Output = Self> + Sub<Output = Self> + sealed::Sealed {
+    #[doc(hidden)]
+    const ONE: Self;
+    #[doc(hidden)]
+    fn saturating_add(self, n: Self) -> Self;
+    #[doc(hidden)]
+    fn saturating_sub(self, n: Self) -> Self;
+    #[doc(hidden)]
+    fn wrapping_add(self, n: Self) -> Self;
+    #[doc(hidden)]
+    fn wrapping_sub(self, n: Self) -> Self;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_integer {
+    ($($t:ty)*) => {$(
+        impl sealed::Sealed for $t {}
+
+        impl Integer for $t {
+            const ONE: Self = 1;
+
+            fn saturating_add(self, n: Self) -> Self {
+                <$t>::saturating_add(self, n)
+            }
+
+            fn saturating_sub(self, n: Self) -> Self {
+                <$t>::saturating_sub(self, n)
+            }
+
+            fn wrapping_add(self, n: Self) -> Self {
+                <$t>::wrapping_add(self, n)
+            }
+
+            fn wrapping_sub(self, n: Self) -> Self {
+                <$t>::wrapping_sub(self, n)
+            }
+        }
+    )*};
+}
+
+impl_integer!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+impl<T> Drop for Signal<T> {
+    fn drop(&mut self) {
+        runtime::decrement(&runtime::SIGNALS, 1);
+    }
+}
+
+impl<T> Observable for Signal<T> {
+    fn tracker(&self) -> &Tracker {
+        &self.tracker
+    }
+
+    fn debug_value(&self) -> Option<String> {
+        self.value.borrow().debug_string()
     }
 }
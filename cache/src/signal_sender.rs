@@ -0,0 +1,95 @@
+//! Setting signals from other threads.
+//!
+//! Signals belong to the thread, and the runtime, they were created in. A worker thread
+//! can still change one through the [`SignalSender`] returned by [`Signal::sender`]: the
+//! values it sends are queued, and applied on the thread owning the signal by
+//! [`drain_remote_updates`](crate::drain_remote_updates), which triggers effects like
+//! setting the signal there would.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{cell::RefCell, rc::Rc, thread};
+//! use reactive_cache::prelude::*;
+//!
+//! let status = Signal::new(String::from("idle"));
+//! let seen = Rc::new(RefCell::new(Vec::new()));
+//! let effect = Effect::new({
+//!     let (status, seen) = (status.clone(), seen.clone());
+//!     move || seen.borrow_mut().push(status.get().clone())
+//! });
+//!
+//! let sender = status.sender();
+//! thread::spawn(move || {
+//!     sender.send("loading".into()).unwrap();
+//!     sender.send("done".into()).unwrap();
+//! })
+//! .join()
+//! .unwrap();
+//!
+//! // Nothing changes until the owning thread applies the values.
+//! assert_eq!(*status.get(), "idle");
+//!
+//! assert_eq!(reactive_cache::drain_remote_updates(), 2);
+//! assert_eq!(*status.get(), "done");
+//! assert_eq!(*seen.borrow(), ["idle", "done"]);
+//! ```
+
+use std::{
+    rc::Rc,
+    sync::mpsc::{self, SendError},
+};
+
+use crate::{Signal, scheduler};
+
+impl<T: 'static> Signal<T> {
+    /// Returns a handle setting this signal from other threads.
+    /// See the [module docs](crate::signal_sender).
+    ///
+    /// Each call returns a handle with a queue of its own; clones of a handle share it.
+    /// The signal is only kept alive by its own owners: once it is dropped, sending
+    /// fails after the next drain.
+    pub fn sender(self: &Rc<Self>) -> SignalSender<T> {
+        let (sender, receiver) = mpsc::channel();
+        let signal = Rc::downgrade(self);
+        scheduler::register_remote(Box::new(move || {
+            let signal = signal.upgrade()?;
+            let mut applied = 0;
+            loop {
+                match receiver.try_recv() {
+                    Ok(value) => {
+                        signal.set_value(value);
+                        applied += 1;
+                    }
+                    Err(mpsc::TryRecvError::Empty) => return Some(applied),
+                    // The task is dropped by the next drain, after reporting these values.
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        return (applied > 0).then_some(applied);
+                    }
+                }
+            }
+        }));
+        SignalSender(sender)
+    }
+}
+
+/// Sends values to a [`Signal`] from any thread, returned by [`Signal::sender`].
+///
+/// It is `Send` when `T` is. Clones send to the same signal.
+pub struct SignalSender<T>(mpsc::Sender<T>);
+
+impl<T> SignalSender<T> {
+    /// Queues `value` to be set on the signal by the next call to
+    /// [`drain_remote_updates`](crate::drain_remote_updates) on its thread.
+    ///
+    /// Returns the value back if the signal was dropped before the last drain.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.0.send(value)
+    }
+}
+
+impl<T> Clone for SignalSender<T> {
+    fn clone(&self) -> Self {
+        SignalSender(self.0.clone())
+    }
+}
@@ -0,0 +1,417 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    cmp::Ordering,
+    rc::{Rc, Weak},
+};
+
+use crate::{Effect, NodeId, Observable, Signal, Tracker};
+
+/// A change applied to a [`SignalVec`], passed to views maintaining derived lists.
+#[derive(Debug, PartialEq, Eq)]
+pub enum VecDiff<'a, T> {
+    /// A value was appended.
+    Push(&'a T),
+    /// A value was inserted at an index.
+    Insert(usize, &'a T),
+    /// The value at an index was removed.
+    Remove(usize),
+    /// The value at an index was replaced.
+    Set(usize, &'a T),
+    /// All values were removed.
+    Clear,
+    /// The whole content was replaced.
+    Replace(&'a [T]),
+}
+
+type Listener<T> = dyn Fn(&VecDiff<'_, T>);
+
+/// A reactive list that reports fine-grained changes.
+///
+/// Like a `Signal<Vec<T>>`, reading a `SignalVec` inside a memo or an effect
+/// subscribes to it, and every mutation invalidates those memos and re-runs those effects.
+/// In addition, each mutation is described by a [`VecDiff`], which lets derived views
+/// such as [`SignalVec::filtered`] and [`SignalVec::sorted_by`] update incrementally
+/// instead of being recomputed from scratch.
+///
+/// # Examples
+///
+/// ```
+/// use reactive_cache::{SignalVec, prelude::*};
+///
+/// let todos = SignalVec::new(vec!["write docs"]);
+/// let count = Memo::new({
+///     let todos = todos.clone();
+///     move || todos.len()
+/// });
+///
+/// todos.push("review");
+/// assert_eq!(count.get(), 2);
+/// assert_eq!(todos.remove(0), "write docs");
+/// assert_eq!(todos.to_vec(), ["review"]);
+/// ```
+pub struct SignalVec<T> {
+    items: RefCell<Vec<T>>,
+    tracker: Tracker,
+    listeners: RefCell<Vec<Weak<Listener<T>>>>,
+
+    /// Subscriptions keeping a derived view up to date, owned by the view.
+    upstream: RefCell<Vec<Rc<dyn Any>>>,
+}
+
+impl<T: 'static> SignalVec<T> {
+    /// Creates a new `SignalVec` holding `items`.
+    #[track_caller]
+    pub fn new(items: Vec<T>) -> Rc<Self> {
+        crate::scope::owned(Rc::new(SignalVec {
+            items: RefCell::new(items),
+            tracker: Tracker::new(),
+            listeners: Default::default(),
+            upstream: Default::default(),
+        }))
+    }
+
+    /// Returns the unique id of this list.
+    pub fn id(&self) -> NodeId {
+        self.tracker.id()
+    }
+
+    /// Calls `f` with the current items, tracking dependencies
+    /// and effects if inside a reactive context.
+    pub fn with<R>(&self, f: impl FnOnce(&[T]) -> R) -> R {
+        self.track();
+        f(&self.items.borrow())
+    }
+
+    /// Returns the number of items, tracking dependencies
+    /// and effects if inside a reactive context.
+    pub fn len(&self) -> usize {
+        self.with(<[T]>::len)
+    }
+
+    /// Returns `true` if the list is empty, tracking dependencies
+    /// and effects if inside a reactive context.
+    pub fn is_empty(&self) -> bool {
+        self.with(<[T]>::is_empty)
+    }
+
+    /// Returns a copy of the items, tracking dependencies
+    /// and effects if inside a reactive context.
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.with(<[T]>::to_vec)
+    }
+
+    /// Appends `value`.
+    pub fn push(&self, value: T) {
+        self.mutate(
+            |items| items.push(value),
+            |items| VecDiff::Push(items.last().unwrap()),
+        );
+    }
+
+    /// Inserts `value` at `index`, shifting later items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&self, index: usize, value: T) {
+        self.mutate(
+            |items| items.insert(index, value),
+            |items| VecDiff::Insert(index, &items[index]),
+        );
+    }
+
+    /// Removes and returns the item at `index`, shifting later items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&self, index: usize) -> T {
+        self.mutate(|items| items.remove(index), |_| VecDiff::Remove(index))
+    }
+
+    /// Replaces the item at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&self, index: usize, value: T) {
+        self.mutate(
+            |items| items[index] = value,
+            |items| VecDiff::Set(index, &items[index]),
+        );
+    }
+
+    /// Removes all items.
+    pub fn clear(&self) {
+        self.mutate(Vec::clear, |_| VecDiff::Clear);
+    }
+
+    /// Replaces all items.
+    pub fn replace(&self, items: Vec<T>) {
+        self.mutate(|old| *old = items, |items| VecDiff::Replace(items));
+    }
+
+    /// Applies `change`, then reports the diff built by `diff` to the views and
+    /// notifies the dependents.
+    fn mutate<R>(
+        &self,
+        change: impl FnOnce(&mut Vec<T>) -> R,
+        diff: impl for<'a> FnOnce(&'a [T]) -> VecDiff<'a, T>,
+    ) -> R {
+        self.notify_changing();
+
+        let result = change(&mut self.items.borrow_mut());
+
+        let listeners: Vec<_> = {
+            let mut listeners = self.listeners.borrow_mut();
+            listeners.retain(|l| l.strong_count() > 0);
+            listeners.iter().filter_map(Weak::upgrade).collect()
+        };
+        if !listeners.is_empty() {
+            let items = self.items.borrow();
+            let diff = diff(&items);
+            for listener in listeners {
+                listener(&diff);
+            }
+        }
+
+        self.notify_changed();
+
+        result
+    }
+
+    /// Calls `listener` with every subsequent change, for as long as the returned
+    /// handle is alive.
+    fn subscribe(&self, listener: impl Fn(&VecDiff<'_, T>) + 'static) -> Rc<Listener<T>> {
+        let listener: Rc<Listener<T>> = Rc::new(listener);
+        self.listeners.borrow_mut().push(Rc::downgrade(&listener));
+        listener
+    }
+
+    /// Returns a view holding the items for which the predicate held by `pred` returns `true`,
+    /// in their original order.
+    ///
+    /// Changes of the source are applied incrementally: only the changed items are
+    /// tested again. Changing `pred` re-filters the whole list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{SignalVec, prelude::*};
+    ///
+    /// let numbers = SignalVec::new(vec![1, 2, 3, 4]);
+    /// let pred = Signal::new((|n: &i32| n % 2 == 0) as fn(&i32) -> bool);
+    /// let view = numbers.filtered(pred.clone());
+    /// assert_eq!(view.to_vec(), [2, 4]);
+    ///
+    /// numbers.push(6);
+    /// numbers.remove(1);
+    /// assert_eq!(view.to_vec(), [4, 6]);
+    ///
+    /// pred.set(|n| *n > 3);
+    /// assert_eq!(view.to_vec(), [4, 6]);
+    /// numbers.set(0, 5);
+    /// assert_eq!(view.to_vec(), [5, 4, 6]);
+    /// ```
+    pub fn filtered<F>(self: &Rc<Self>, pred: Rc<Signal<F>>) -> Rc<SignalVec<T>>
+    where
+        T: Clone,
+        F: Fn(&T) -> bool + 'static,
+    {
+        let output = SignalVec::new(Vec::new());
+        let out = Rc::downgrade(&output);
+
+        // Whether each source item passes the predicate.
+        let included = Rc::new(RefCell::new(Vec::<bool>::new()));
+        let rank =
+            |included: &[bool], index: usize| included[..index].iter().filter(|i| **i).count();
+
+        let listener = self.subscribe({
+            let (pred, out, included) = (pred.clone(), out.clone(), included.clone());
+            move |diff| {
+                let Some(out) = out.upgrade() else { return };
+                let mut included = included.borrow_mut();
+                pred.with_untracked(|pred| match *diff {
+                    VecDiff::Push(v) => {
+                        let keep = pred(v);
+                        if keep {
+                            out.push(v.clone());
+                        }
+                        included.push(keep);
+                    }
+                    VecDiff::Insert(i, v) => {
+                        let keep = pred(v);
+                        if keep {
+                            out.insert(rank(&included, i), v.clone());
+                        }
+                        included.insert(i, keep);
+                    }
+                    VecDiff::Remove(i) => {
+                        if included.remove(i) {
+                            out.remove(rank(&included, i));
+                        }
+                    }
+                    VecDiff::Set(i, v) => {
+                        let (at, keep) = (rank(&included, i), pred(v));
+                        match (included[i], keep) {
+                            (true, true) => out.set(at, v.clone()),
+                            (true, false) => drop(out.remove(at)),
+                            (false, true) => out.insert(at, v.clone()),
+                            (false, false) => {}
+                        }
+                        included[i] = keep;
+                    }
+                    VecDiff::Clear => {
+                        included.clear();
+                        out.clear();
+                    }
+                    VecDiff::Replace(items) => {
+                        *included = items.iter().map(pred).collect();
+                        out.replace(items.iter().filter(|&v| pred(v)).cloned().collect());
+                    }
+                });
+            }
+        });
+
+        // Re-filter everything whenever the predicate changes, and once now.
+        let effect = Effect::new({
+            let source = Rc::downgrade(self);
+            move || {
+                let pred = pred.get();
+                let (Some(source), Some(out)) = (source.upgrade(), out.upgrade()) else {
+                    return;
+                };
+                // The source is released before `replace` runs the dependents of the view,
+                // which may change it.
+                let kept = {
+                    let items = source.items.borrow();
+                    *included.borrow_mut() = items.iter().map(&*pred).collect();
+                    items.iter().filter(|&v| pred(v)).cloned().collect()
+                };
+                out.replace(kept);
+            }
+        });
+
+        output
+            .upstream
+            .borrow_mut()
+            .extend([Rc::new(listener) as Rc<dyn Any>, effect]);
+        output
+    }
+
+    /// Returns a view holding the items sorted by `cmp`.
+    ///
+    /// The sort is stable, and changes of the source are applied incrementally:
+    /// inserted or replaced items are placed by binary search, without re-sorting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::SignalVec;
+    ///
+    /// let scores = SignalVec::new(vec![("ann", 30), ("bob", 10)]);
+    /// let ranking = scores.sorted_by(|a, b| b.1.cmp(&a.1));
+    /// assert_eq!(ranking.to_vec(), [("ann", 30), ("bob", 10)]);
+    ///
+    /// scores.push(("cid", 20));
+    /// scores.set(0, ("ann", 5));
+    /// assert_eq!(ranking.to_vec(), [("cid", 20), ("bob", 10), ("ann", 5)]);
+    /// ```
+    pub fn sorted_by(
+        self: &Rc<Self>,
+        cmp: impl Fn(&T, &T) -> Ordering + 'static,
+    ) -> Rc<SignalVec<T>>
+    where
+        T: Clone,
+    {
+        // Source indices in output order.
+        let order = Rc::new(RefCell::new(Vec::<usize>::new()));
+
+        let sort = {
+            let order = order.clone();
+            move |items: &[T], out: &SignalVec<T>, cmp: &dyn Fn(&T, &T) -> Ordering| {
+                let mut indices: Vec<usize> = (0..items.len()).collect();
+                indices.sort_by(|a, b| cmp(&items[*a], &items[*b]));
+                out.replace(indices.iter().map(|i| items[*i].clone()).collect());
+                *order.borrow_mut() = indices;
+            }
+        };
+
+        let output = SignalVec::new(Vec::new());
+        sort(&self.items.borrow(), &output, &cmp);
+
+        let out = Rc::downgrade(&output);
+        let listener = self.subscribe(move |diff| {
+            let Some(out) = out.upgrade() else { return };
+
+            // Inserts source item `index` by binary search. Equal items keep source order.
+            let insert = |order: &mut Vec<usize>, index: usize, v: &T| {
+                let (mut lo, mut hi) = (0, order.len());
+                {
+                    let items = out.items.borrow();
+                    while lo < hi {
+                        let mid = (lo + hi) / 2;
+                        let before = match cmp(&items[mid], v) {
+                            Ordering::Less => true,
+                            Ordering::Equal => order[mid] < index,
+                            Ordering::Greater => false,
+                        };
+                        if before {
+                            lo = mid + 1;
+                        } else {
+                            hi = mid;
+                        }
+                    }
+                }
+                order.insert(lo, index);
+                out.insert(lo, v.clone());
+            };
+            let remove = |order: &mut Vec<usize>, index: usize| {
+                let at = order.iter().position(|i| *i == index).unwrap();
+                order.remove(at);
+                out.remove(at);
+            };
+
+            let mut o = order.borrow_mut();
+            match *diff {
+                VecDiff::Push(v) => {
+                    let index = o.len();
+                    insert(&mut o, index, v);
+                }
+                VecDiff::Insert(index, v) => {
+                    o.iter_mut().filter(|i| **i >= index).for_each(|i| *i += 1);
+                    insert(&mut o, index, v);
+                }
+                VecDiff::Remove(index) => {
+                    remove(&mut o, index);
+                    o.iter_mut().filter(|i| **i > index).for_each(|i| *i -= 1);
+                }
+                VecDiff::Set(index, v) => {
+                    remove(&mut o, index);
+                    insert(&mut o, index, v);
+                }
+                VecDiff::Clear => {
+                    o.clear();
+                    out.clear();
+                }
+                VecDiff::Replace(items) => {
+                    drop(o);
+                    sort(items, &out, &cmp);
+                }
+            }
+        });
+
+        output.upstream.borrow_mut().push(Rc::new(listener));
+        output
+    }
+}
+
+impl<T> Observable for SignalVec<T> {
+    fn tracker(&self) -> &Tracker {
+        &self.tracker
+    }
+}
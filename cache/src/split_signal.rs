@@ -0,0 +1,184 @@
+//! Read and write halves of a [`Signal`].
+//!
+//! [`Signal::split`] hands out a [`ReadSignal`], which can only read and track the value,
+//! and a [`WriteSignal`], which can only change it. A view model can keep the write half
+//! private and expose the read half, so that the rest of the code reacts to the state
+//! without being able to modify it.
+//!
+//! A read half can also be obtained from a shared signal with `From`, for example to
+//! return the state of a library as a [`ReadonlySignal`] that its users cannot set.
+//!
+//! # Examples
+//!
+//! ```
+//! use reactive_cache::{ReadSignal, WriteSignal, prelude::*};
+//!
+//! struct Counter {
+//!     count: ReadSignal<i32>,
+//!     set_count: WriteSignal<i32>,
+//! }
+//!
+//! impl Counter {
+//!     fn new() -> Self {
+//!         let (count, set_count) = Signal::new(0).split();
+//!         Counter { count, set_count }
+//!     }
+//!
+//!     fn increment(&self) {
+//!         self.set_count.update(|count| *count += 1);
+//!     }
+//! }
+//!
+//! let counter = Counter::new();
+//! let double = Memo::new({
+//!     let count = counter.count.clone();
+//!     move || *count.get() * 2
+//! });
+//!
+//! counter.increment();
+//! assert_eq!(double.get(), 2);
+//! ```
+
+use std::{cell::Ref, rc::Rc};
+
+use crate::{NodeId, Observable, Signal, SignalSetter, Tracker};
+
+impl<T> Signal<T> {
+    /// Splits this signal into a read half and a write half.
+    /// See the [module docs](crate::split_signal).
+    pub fn split(self: &Rc<Self>) -> (ReadSignal<T>, WriteSignal<T>) {
+        (ReadSignal(self.clone()), WriteSignal(self.clone()))
+    }
+}
+
+/// The read half of a [`Signal`], returned by [`Signal::split`].
+///
+/// Clones share the same signal.
+pub struct ReadSignal<T>(Rc<Signal<T>>);
+
+impl<T> ReadSignal<T> {
+    /// Returns the unique id of the signal.
+    pub fn id(&self) -> NodeId {
+        self.0.id()
+    }
+
+    /// Gets a reference to the current value, tracking dependencies. See [`Signal::get`].
+    pub fn get(&self) -> Ref<'_, T> {
+        self.0.get()
+    }
+
+    /// Calls `f` with a reference to the current value, tracking dependencies.
+    /// See [`Signal::with`].
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.0.with(f)
+    }
+
+    /// Gets a reference to the current value, without tracking.
+    /// See [`Signal::get_untracked`].
+    pub fn get_untracked(&self) -> Ref<'_, T> {
+        self.0.get_untracked()
+    }
+
+    /// Calls `f` with a reference to the current value, without tracking.
+    /// See [`Signal::with_untracked`].
+    pub fn with_untracked<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.0.with_untracked(f)
+    }
+}
+
+impl<T> Clone for ReadSignal<T> {
+    fn clone(&self) -> Self {
+        ReadSignal(self.0.clone())
+    }
+}
+
+impl<T> Observable for ReadSignal<T> {
+    fn tracker(&self) -> &Tracker {
+        self.0.tracker()
+    }
+
+    fn debug_value(&self) -> Option<String> {
+        self.0.debug_value()
+    }
+}
+
+impl<T> From<Rc<Signal<T>>> for ReadSignal<T> {
+    fn from(signal: Rc<Signal<T>>) -> Self {
+        ReadSignal(signal)
+    }
+}
+
+/// A read-only view of a [`Signal`], another name for [`ReadSignal`].
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use reactive_cache::{ReadonlySignal, prelude::*};
+///
+/// pub struct Downloads {
+///     progress: Rc<Signal<u8>>,
+/// }
+///
+/// impl Downloads {
+///     pub fn progress(&self) -> ReadonlySignal<u8> {
+///         self.progress.clone().into()
+///     }
+/// }
+///
+/// let downloads = Downloads { progress: Signal::new(0) };
+/// let progress = downloads.progress();
+/// downloads.progress.set(50);
+/// assert_eq!(*progress.get(), 50);
+/// ```
+pub type ReadonlySignal<T> = ReadSignal<T>;
+
+/// The write half of a [`Signal`], returned by [`Signal::split`].
+///
+/// Clones share the same signal.
+pub struct WriteSignal<T>(Rc<Signal<T>>);
+
+impl<T> WriteSignal<T> {
+    /// Returns the unique id of the signal.
+    pub fn id(&self) -> NodeId {
+        self.0.id()
+    }
+
+    /// Sets the value, returning whether dependents were notified.
+    /// See [`SignalSetter::set`].
+    pub fn set(&self, value: T) -> bool
+    where
+        Signal<T>: SignalSetter<T>,
+    {
+        self.0.set(value)
+    }
+
+    /// Sets the value without comparing it with the current one.
+    /// See [`Signal::set_always`].
+    pub fn set_always(&self, value: T) {
+        self.0.set_always(value)
+    }
+
+    /// Mutates the value in place, then notifies dependents. See [`Signal::update`].
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        self.0.update(f)
+    }
+
+    /// Mutates the value in place, then notifies dependents, and returns the result of `f`.
+    /// See [`Signal::update_returning`].
+    pub fn update_returning<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        self.0.update_returning(f)
+    }
+
+    /// Notifies dependents that the value changed, without replacing it.
+    /// See [`Signal::mark_changed`].
+    pub fn mark_changed(&self) {
+        self.0.mark_changed()
+    }
+}
+
+impl<T> Clone for WriteSignal<T> {
+    fn clone(&self) -> Self {
+        WriteSignal(self.0.clone())
+    }
+}
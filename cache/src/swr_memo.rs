@@ -0,0 +1,148 @@
+//! Memos serving their previous value while it is recomputed.
+//!
+//! A [`SwrMemo`] (stale-while-revalidate) computes its value with a [`Memo`], but reading
+//! it never waits for a recompute. Once a dependency changes, it keeps serving the previous
+//! value right away, and recomputes on the next [`flush`](crate::flush). The memos and
+//! effects reading it are notified when the fresh value lands, if it differs from the
+//! previous one. Interactive applications stay responsive while an expensive value is
+//! brought up to date.
+//!
+//! The first value is computed when the memo is created.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{cell::Cell, rc::Rc};
+//! use reactive_cache::{SwrMemo, prelude::*};
+//!
+//! let query = Signal::new("rust");
+//! let searches = Rc::new(Cell::new(0));
+//! let results = SwrMemo::new({
+//!     let (query, searches) = (query.clone(), searches.clone());
+//!     move || {
+//!         searches.set(searches.get() + 1);
+//!         format!("results for {}", query.get())
+//!     }
+//! });
+//! assert_eq!(results.get(), "results for rust");
+//!
+//! // The previous results are shown until the search ran.
+//! query.set("reactive");
+//! assert!(results.is_stale());
+//! assert_eq!(results.get(), "results for rust");
+//! assert_eq!(searches.get(), 1);
+//!
+//! reactive_cache::flush();
+//! assert_eq!(results.get(), "results for reactive");
+//! assert_eq!(searches.get(), 2);
+//! ```
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    Effect, EffectOptions, Memo, NodeId, Observable, Tracker,
+    inspect::{self, DebugValue, Event, NodeKind},
+};
+
+/// A memo serving its previous value while it is recomputed. See the [module docs](self).
+pub struct SwrMemo<T> {
+    memo: Rc<Memo<T>>,
+    /// The value served to readers, replaced once a recompute lands.
+    value: RefCell<Rc<T>>,
+    tracker: Tracker,
+    /// Queued on the next flush whenever a dependency of the memo changes.
+    _revalidate: Rc<Effect>,
+}
+
+impl<T: 'static> SwrMemo<T> {
+    /// Creates a memo computing its value with `f`, and computes it.
+    #[track_caller]
+    pub fn new(f: impl Fn() -> T + 'static) -> Rc<Self> {
+        let memo = Memo::new(f);
+        crate::scope::owned(Rc::new_cyclic(|weak: &Weak<SwrMemo<T>>| {
+            let revalidate = Effect::new_with_deps_and_options(
+                {
+                    let weak = weak.clone();
+                    move || {
+                        if let Some(this) = weak.upgrade() {
+                            this.revalidate();
+                        }
+                    }
+                },
+                || {
+                    memo.track();
+                    memo.value();
+                },
+                EffectOptions::new().deferred(),
+            );
+            SwrMemo {
+                value: RefCell::new(fresh(&memo)),
+                memo,
+                tracker: Tracker::new(),
+                _revalidate: revalidate,
+            }
+        }))
+    }
+
+    /// Returns the unique id of this memo.
+    pub fn id(&self) -> NodeId {
+        self.tracker.id()
+    }
+
+    /// Returns the value, which may be outdated, without computing it.
+    ///
+    /// This registers the caller as a dependent, notified once a fresh value lands.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.track();
+        let value = self.value.borrow().clone();
+        (*value).clone()
+    }
+
+    /// Returns whether a dependency may have changed since the value was computed, so that
+    /// a recompute is pending. Nothing is computed to tell.
+    pub fn is_stale(&self) -> bool {
+        self.memo.is_dirty()
+    }
+
+    /// Takes the value of the memo, brought up to date by the effect running this, and
+    /// notifies dependents if it changed.
+    fn revalidate(&self) {
+        let value = fresh(&self.memo);
+        let previous = self.value.replace(value.clone());
+        // The memo keeps its previous value when it recomputes an equal one.
+        if Rc::ptr_eq(&previous, &value) {
+            return;
+        }
+        drop(previous);
+
+        inspect::emit(Event::Recomputed(self.id()));
+        self.notify_changing();
+        self.notify_changed();
+    }
+}
+
+/// Returns the value of `memo`, computing it if necessary, without tracking it.
+fn fresh<T: 'static>(memo: &Memo<T>) -> Rc<T> {
+    crate::effect_stack::effect_suspended(|| memo.value())
+}
+
+impl<T: 'static> Observable for SwrMemo<T> {
+    fn tracker(&self) -> &Tracker {
+        &self.tracker
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Memo
+    }
+
+    fn debug_value(&self) -> Option<String> {
+        let value = self.value.borrow().clone();
+        value.debug_string()
+    }
+}
@@ -0,0 +1,332 @@
+//! Thread-safe signals and memos.
+//!
+//! A [`sync::Signal`](Signal) and a [`sync::Memo`](Memo) behave like their `Rc`
+//! counterparts, but are shared with `Arc` and can be read and set from any thread. A
+//! memo tracks the signals and memos it reads while it computes, on whichever thread
+//! that happens, and is invalidated when one of them is set on any thread. Reading it
+//! recomputes its value only if necessary.
+//!
+//! They form a graph of their own: the memos and effects of the single-threaded runtime
+//! do not track them, and they do not track signals or memos of the runtime.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::thread;
+//! use reactive_cache::{prelude::*, sync};
+//!
+//! let progress = sync::Signal::new(0);
+//! let label = sync::Memo::new({
+//!     let progress = progress.clone();
+//!     move || format!("{}%", *progress.get())
+//! });
+//! assert_eq!(label.get(), "0%");
+//!
+//! let worker = thread::spawn({
+//!     let progress = progress.clone();
+//!     move || {
+//!         for step in 1..=10 {
+//!             progress.set(step * 10);
+//!         }
+//!     }
+//! });
+//! worker.join().unwrap();
+//!
+//! assert_eq!(label.get(), "100%");
+//! ```
+
+use std::{
+    cell::RefCell,
+    sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak},
+};
+
+use crate::SignalSetter;
+
+/// A memo notified when a signal or memo it read changes.
+trait Dependent: Send + Sync {
+    fn invalidate(&self);
+}
+
+thread_local! {
+    /// The memos computing on this thread, innermost last.
+    static COMPUTING: RefCell<Vec<Weak<dyn Dependent>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Pops the memo computing on this thread when its computation returns or unwinds.
+struct Computing;
+
+impl Computing {
+    fn start(dependent: Weak<dyn Dependent>) -> Self {
+        COMPUTING.with_borrow_mut(|stack| stack.push(dependent));
+        Computing
+    }
+}
+
+impl Drop for Computing {
+    fn drop(&mut self) {
+        COMPUTING.with_borrow_mut(Vec::pop);
+    }
+}
+
+/// The memos that read a signal or memo since it last changed.
+#[derive(Default)]
+struct Subscribers(Mutex<Vec<Weak<dyn Dependent>>>);
+
+impl Subscribers {
+    /// Subscribes the memo computing on this thread, if any.
+    fn track(&self) {
+        let Some(dependent) = COMPUTING.with_borrow(|stack| stack.last().cloned()) else {
+            return;
+        };
+        let mut subscribers = lock(&self.0);
+        if !subscribers.iter().any(|s| Weak::ptr_eq(s, &dependent)) {
+            subscribers.push(dependent);
+        }
+    }
+
+    /// Invalidates the subscribed memos, which subscribe again when they recompute.
+    fn notify(&self) {
+        let subscribers = std::mem::take(&mut *lock(&self.0));
+        for subscriber in subscribers {
+            if let Some(subscriber) = subscriber.upgrade() {
+                subscriber.invalidate();
+            }
+        }
+    }
+}
+
+/// Locks `mutex`. A panic while it was locked leaves a value that is still valid, since
+/// values are only ever replaced whole or mutated by the caller's closure.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A thread-safe reactive signal. See the [module docs](self).
+///
+/// Reading it with [`get`](Signal::get) holds a read lock until the guard is dropped:
+/// setting the signal on the same thread in the meantime deadlocks.
+pub struct Signal<T> {
+    value: RwLock<T>,
+    subscribers: Subscribers,
+}
+
+impl<T> Signal<T> {
+    /// Creates a signal holding `value`.
+    pub fn new(value: T) -> Arc<Self> {
+        Arc::new(Signal {
+            value: RwLock::new(value),
+            subscribers: Subscribers::default(),
+        })
+    }
+
+    /// Gets a read guard of the current value.
+    ///
+    /// When called while a memo computes on this thread, the memo becomes a dependent.
+    pub fn get(&self) -> RwLockReadGuard<'_, T> {
+        self.subscribers.track();
+        self.get_untracked()
+    }
+
+    /// Gets a read guard of the current value, without tracking.
+    pub fn get_untracked(&self) -> RwLockReadGuard<'_, T> {
+        self.value.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Calls `f` with a reference to the current value, tracking dependencies like
+    /// [`Signal::get`].
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.get())
+    }
+
+    /// Sets the value without comparing it with the current one, so that dependent memos
+    /// are always invalidated.
+    pub fn set_always(&self, value: T) {
+        *self.write() = value;
+        self.subscribers.notify();
+    }
+
+    /// Mutates the value in place with `f`, then invalidates dependent memos.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::sync;
+    ///
+    /// let items = sync::Signal::new(vec![1, 2]);
+    /// let total = sync::Memo::new({
+    ///     let items = items.clone();
+    ///     move || items.with(|items| items.iter().sum::<i32>())
+    /// });
+    /// assert_eq!(total.get(), 3);
+    ///
+    /// items.update(|items| items.push(3));
+    /// assert_eq!(total.get(), 6);
+    /// ```
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.write());
+        self.subscribers.notify();
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.value.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T> SignalSetter<T> for Signal<T> {
+    specializable! {
+        /// Sets the value of the signal.
+        ///
+        /// Values that cannot be compared are treated as always changing, so `true` is
+        /// always returned.
+        fn set(&self, value: T) -> bool {
+            self.set_always(value);
+
+            true
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T: Eq> SignalSetter<T> for Signal<T> {
+    /// Sets the value of the signal.
+    ///
+    /// Returns `true` if the value changed, and dependent memos were invalidated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{prelude::*, sync};
+    ///
+    /// let signal = sync::Signal::new(5);
+    /// assert!(signal.set(10));
+    /// assert!(!signal.set(10));
+    /// assert_eq!(*signal.get(), 10);
+    /// ```
+    fn set(&self, value: T) -> bool {
+        {
+            let mut slot = self.write();
+            if *slot == value {
+                return false;
+            }
+            *slot = value;
+        }
+        self.subscribers.notify();
+
+        true
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T: PartialEq> SignalSetter<T> for Signal<T> {
+    /// Sets the value of the signal.
+    ///
+    /// Returns `true` if the value changed, and dependent memos were invalidated.
+    ///
+    /// Without the `nightly` feature, only values implementing `PartialEq` can be set
+    /// this way; other values are set with [`Signal::set_always`] or [`Signal::update`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{prelude::*, sync};
+    ///
+    /// let signal = sync::Signal::new(5);
+    /// assert!(signal.set(10));
+    /// assert!(!signal.set(10));
+    /// assert_eq!(*signal.get(), 10);
+    /// ```
+    fn set(&self, value: T) -> bool {
+        {
+            let mut slot = self.write();
+            if *slot == value {
+                return false;
+            }
+            *slot = value;
+        }
+        self.subscribers.notify();
+
+        true
+    }
+}
+
+/// A thread-safe memoized computation. See the [module docs](self).
+///
+/// The value is computed while a lock of the memo is held, so that threads reading a
+/// memo being computed wait for its value rather than computing it again. Setting a
+/// signal the memo reads from within its own computation deadlocks.
+pub struct Memo<T> {
+    f: Box<dyn Fn() -> T + Send + Sync>,
+    value: Mutex<Option<T>>,
+    subscribers: Subscribers,
+    this: Weak<Memo<T>>,
+}
+
+impl<T: Send + Sync + 'static> Memo<T> {
+    /// Creates a memo computing its value with `f`, the first time it is read.
+    pub fn new(f: impl Fn() -> T + Send + Sync + 'static) -> Arc<Self> {
+        Arc::new_cyclic(|this| Memo {
+            f: Box::new(f),
+            value: Mutex::new(None),
+            subscribers: Subscribers::default(),
+            this: this.clone(),
+        })
+    }
+
+    /// Returns the value, recomputing it only if necessary.
+    ///
+    /// When called while another memo computes on this thread, that memo becomes a
+    /// dependent.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.subscribers.track();
+        let mut value = lock(&self.value);
+        value
+            .get_or_insert_with(|| {
+                let _computing = Computing::start(self.this.clone());
+                (self.f)()
+            })
+            .clone()
+    }
+
+    /// Returns whether the value must be recomputed before it is read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use reactive_cache::{prelude::*, sync};
+    ///
+    /// let width = sync::Signal::new(2);
+    /// let area = sync::Memo::new({
+    ///     let width = width.clone();
+    ///     move || *width.get() * 3
+    /// });
+    /// assert!(area.is_dirty());
+    /// assert_eq!(area.get(), 6);
+    /// assert!(!area.is_dirty());
+    ///
+    /// width.set(4);
+    /// assert!(area.is_dirty());
+    /// ```
+    pub fn is_dirty(&self) -> bool {
+        lock(&self.value).is_none()
+    }
+
+    /// Drops the value, so that it is recomputed the next time it is read, and
+    /// invalidates the memos reading it.
+    pub fn invalidate(&self) {
+        Dependent::invalidate(self)
+    }
+}
+
+impl<T: Send + Sync> Dependent for Memo<T> {
+    fn invalidate(&self) {
+        let cached = lock(&self.value).take().is_some();
+        // Dependents of a memo that has no value were notified when it was dropped.
+        if cached {
+            self.subscribers.notify();
+        }
+    }
+}
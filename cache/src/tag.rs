@@ -0,0 +1,158 @@
+//! Invalidation of families of memos by tag.
+
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    Durability, MemoNode,
+    inspect::{self, Event},
+    revision,
+};
+
+thread_local! {
+    static TAGS: RefCell<HashMap<Tag, Vec<Weak<dyn MemoNode>>>> = RefCell::new(HashMap::new());
+}
+
+/// The tagged memos of a runtime that is not entered, see [`Runtime`](crate::Runtime).
+#[derive(Default)]
+pub(crate) struct State(HashMap<Tag, Vec<Weak<dyn MemoNode>>>);
+
+/// Exchanges the tagged memos with those of the runtime being entered or left.
+pub(crate) fn swap(state: &mut State) {
+    TAGS.with_borrow_mut(|tags| std::mem::swap(tags, &mut state.0))
+}
+
+/// A label attached to memos with [`Memo::new_tagged`](crate::Memo::new_tagged),
+/// to invalidate them together with [`invalidate_tag`].
+///
+/// Tags are created from strings. Enums can be used as tags by converting them into one.
+///
+/// # Examples
+///
+/// ```
+/// use reactive_cache::{Memo, Tag, invalidate_tag};
+///
+/// enum Source {
+///     Currencies,
+///     Taxes,
+/// }
+///
+/// impl From<Source> for Tag {
+///     fn from(source: Source) -> Tag {
+///         match source {
+///             Source::Currencies => "currencies".into(),
+///             Source::Taxes => "taxes".into(),
+///         }
+///     }
+/// }
+///
+/// let price = Memo::new_tagged(|| 42, [Source::Currencies, Source::Taxes]);
+/// assert_eq!(price.get(), 42);
+/// assert_eq!(invalidate_tag(Source::Taxes), 1);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tag(Cow<'static, str>);
+
+impl Tag {
+    /// Returns the name of this tag.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&'static str> for Tag {
+    fn from(name: &'static str) -> Self {
+        Tag(Cow::Borrowed(name))
+    }
+}
+
+impl From<String> for Tag {
+    fn from(name: String) -> Self {
+        Tag(Cow::Owned(name))
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Attaches `tag` to `memo`.
+pub(crate) fn register(tag: Tag, memo: Weak<dyn MemoNode>) {
+    TAGS.with_borrow_mut(|tags| {
+        let memos = tags.entry(tag).or_default();
+        // Dropped memos are pruned before the list grows, so that the list of a tag that
+        // is never invalidated only grows with the number of live memos.
+        if memos.len() == memos.capacity() {
+            memos.retain(|m| m.strong_count() > 0);
+        }
+        memos.push(memo)
+    })
+}
+
+/// Marks every live memo tagged with `tag` as dirty, and returns how many there are.
+///
+/// Each of them is recomputed when it is read next, and the effects reading them are
+/// re-run. Memos reading them are only recomputed if their value actually changed.
+///
+/// This is meant for changes the runtime cannot see, such as a currency table reloaded
+/// from disk that a family of pricing memos reads without going through a signal.
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::{invalidate_tag, prelude::*};
+///
+/// // Loaded out of band, so not tracked.
+/// let rate = Rc::new(Cell::new(2));
+/// let price = Signal::new(10);
+/// let total = Memo::new_tagged(
+///     {
+///         let (price, rate) = (price.clone(), rate.clone());
+///         move || *price.get() * rate.get()
+///     },
+///     ["pricing"],
+/// );
+///
+/// assert_eq!(total.get(), 20);
+/// rate.set(3);
+/// assert_eq!(total.get(), 20);
+///
+/// assert_eq!(invalidate_tag("pricing"), 1);
+/// assert_eq!(total.get(), 30);
+/// ```
+pub fn invalidate_tag(tag: impl Into<Tag>) -> usize {
+    let tag = tag.into();
+    let memos: Vec<Rc<dyn MemoNode>> = TAGS.with_borrow_mut(|tags| {
+        let Some(memos) = tags.get_mut(&tag) else {
+            return Vec::new();
+        };
+        memos.retain(|m| m.strong_count() > 0);
+        let live = memos.iter().filter_map(Weak::upgrade).collect();
+        if memos.is_empty() {
+            tags.remove(&tag);
+        }
+        live
+    });
+    if memos.is_empty() {
+        return 0;
+    }
+
+    // Any memo may read a tagged one, so every memo is verified again.
+    revision::bump(Durability::High);
+    for memo in &memos {
+        memo.tracker().mark_dirty();
+        inspect::emit(Event::Invalidated(memo.id()));
+    }
+    for memo in &memos {
+        memo.notify_changed();
+    }
+    memos.len()
+}
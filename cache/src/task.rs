@@ -0,0 +1,259 @@
+//! Groups of asynchronous tasks with a concurrency policy.
+//!
+//! A burst of signal changes re-running an effect that starts a request would start
+//! as many overlapping requests. A [`TaskGroup`] runs the tasks spawned into it
+//! according to a [`Concurrency`] policy instead: only the latest one, one at a time
+//! in order, or up to a number of them in parallel.
+//!
+//! Like [`AsyncMemo`](crate::AsyncMemo), task groups do not depend on any executor:
+//! the application drives the tasks of a group by awaiting [`TaskGroup::idle`], or
+//! by calling [`TaskGroup::poll`] from its event loop.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    future::Future,
+    mem,
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+};
+
+use crate::{Effect, async_memo::Wakers};
+
+/// How the tasks of a [`TaskGroup`] run relative to each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Concurrency {
+    /// Only the latest task runs: spawning a task cancels the running one by dropping it.
+    ///
+    /// Suited to requests whose result is superseded by the next one, like search
+    /// suggestions for the current query.
+    Latest,
+    /// Tasks run one at a time, in the order they were spawned.
+    ///
+    /// Suited to writes that must reach a server in order.
+    Queue,
+    /// Up to the given number of tasks run at once. The others wait, and start in the
+    /// order they were spawned.
+    Parallel(usize),
+}
+
+struct Task {
+    id: u64,
+    future: Pin<Box<dyn Future<Output = ()>>>,
+}
+
+/// Asynchronous tasks running under a [`Concurrency`] policy. See the [module docs](self).
+///
+/// # Examples
+///
+/// ```
+/// use std::{cell::RefCell, pin::pin, rc::Rc, task::{Context, Poll, Waker}};
+/// use reactive_cache::{prelude::*, task::{Concurrency, TaskGroup}};
+///
+/// fn block_on<F: Future>(future: F) -> F::Output {
+///     let mut future = pin!(future);
+///     let mut cx = Context::from_waker(Waker::noop());
+///     loop {
+///         if let Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+///             return value;
+///         }
+///     }
+/// }
+///
+/// // Completes on its second poll, like a request in flight.
+/// async fn request() {
+///     let mut sent = false;
+///     std::future::poll_fn(|_| {
+///         if sent {
+///             Poll::Ready(())
+///         } else {
+///             sent = true;
+///             Poll::Pending
+///         }
+///     })
+///     .await
+/// }
+///
+/// let query = Signal::new("r".to_string());
+/// let suggestions = Rc::new(RefCell::new(Vec::new()));
+///
+/// let group = TaskGroup::new(Concurrency::Latest);
+/// let search = group.effect({
+///     let (query, suggestions) = (query.clone(), suggestions.clone());
+///     move || {
+///         let (query, suggestions) = (query.get().clone(), suggestions.clone());
+///         async move {
+///             request().await;
+///             suggestions.borrow_mut().push(query);
+///         }
+///     }
+/// });
+///
+/// query.set("ru".to_string());
+/// query.set("rust".to_string());
+/// assert_eq!(group.active(), 1);
+///
+/// block_on(group.idle());
+/// assert_eq!(*suggestions.borrow(), ["rust"]);
+/// ```
+pub struct TaskGroup {
+    concurrency: Concurrency,
+    /// Started tasks, except those being polled.
+    running: RefCell<Vec<Task>>,
+    /// Tasks waiting for a running one to complete.
+    queued: RefCell<VecDeque<Task>>,
+    /// The number of started tasks that did not complete, including those being polled.
+    active: Cell<usize>,
+    /// Tasks with a lower id were cancelled, even while being polled.
+    cancelled_below: Cell<u64>,
+    next_id: Cell<u64>,
+    wakers: Arc<Wakers>,
+}
+
+impl TaskGroup {
+    /// Creates an empty group running its tasks according to `concurrency`.
+    pub fn new(concurrency: Concurrency) -> Rc<Self> {
+        Rc::new(TaskGroup {
+            concurrency,
+            running: Default::default(),
+            queued: Default::default(),
+            active: Cell::new(0),
+            cancelled_below: Cell::new(0),
+            next_id: Cell::new(0),
+            wakers: Default::default(),
+        })
+    }
+
+    /// Returns the concurrency policy of this group.
+    pub fn concurrency(&self) -> Concurrency {
+        self.concurrency
+    }
+
+    /// Returns the number of started tasks that did not complete yet.
+    pub fn active(&self) -> usize {
+        self.active.get()
+    }
+
+    /// Returns the number of tasks waiting to start.
+    pub fn queued(&self) -> usize {
+        self.queued.borrow().len()
+    }
+
+    /// Adds a task to the group, starting it when the policy allows.
+    ///
+    /// A task starts running on the next poll of the group.
+    pub fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        let task = Task {
+            id,
+            future: Box::pin(future),
+        };
+
+        if self.concurrency == Concurrency::Latest {
+            self.cancelled_below.set(id);
+            // Dropped after the borrows end, in case a task spawns another one on drop.
+            let cancelled = mem::take(&mut *self.running.borrow_mut());
+            self.active.set(self.active.get() - cancelled.len());
+            drop(cancelled);
+        }
+
+        if self.active.get() < self.limit() {
+            self.active.set(self.active.get() + 1);
+            self.running.borrow_mut().push(task);
+        } else {
+            self.queued.borrow_mut().push_back(task);
+        }
+        self.wakers.wake_by_ref();
+    }
+
+    /// Creates an effect spawning the future returned by `f` into this group whenever
+    /// the effect re-runs.
+    ///
+    /// The dependencies of the effect are the signals and memos read by `f` itself,
+    /// before it returns the future. Reads inside the future are not tracked.
+    #[track_caller]
+    pub fn effect<F>(self: &Rc<Self>, f: impl Fn() -> F + 'static) -> Rc<Effect>
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        let group = Rc::downgrade(self);
+        Effect::new(move || {
+            let future = f();
+            if let Some(group) = group.upgrade() {
+                group.spawn(future);
+            }
+        })
+    }
+
+    /// Polls the running tasks, starting queued ones as running ones complete.
+    ///
+    /// Returns `Poll::Ready` once no task is running or queued. Otherwise the waker of
+    /// `cx` is woken when a task makes progress or is spawned.
+    pub fn poll(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.wakers.register(cx.waker());
+        let waker = Waker::from(self.wakers.clone());
+
+        loop {
+            // Tasks are taken out while polled, as they may spawn into this group.
+            let tasks = mem::take(&mut *self.running.borrow_mut());
+            let mut pending = Vec::with_capacity(tasks.len());
+            for mut task in tasks {
+                if task.id < self.cancelled_below.get() {
+                    self.active.set(self.active.get() - 1);
+                    continue;
+                }
+                match task.future.as_mut().poll(&mut Context::from_waker(&waker)) {
+                    Poll::Ready(()) => self.active.set(self.active.get() - 1),
+                    Poll::Pending => pending.push(task),
+                }
+            }
+
+            let cancelled_below = self.cancelled_below.get();
+            let (pending, cancelled): (Vec<_>, Vec<_>) =
+                pending.into_iter().partition(|t| t.id >= cancelled_below);
+            self.active.set(self.active.get() - cancelled.len());
+            drop(cancelled);
+            self.running.borrow_mut().splice(0..0, pending);
+
+            if !self.start_queued() {
+                break;
+            }
+        }
+
+        if self.active.get() == 0 && self.queued.borrow().is_empty() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Returns a future driving the tasks of this group until none is running or queued.
+    pub fn idle(&self) -> impl Future<Output = ()> + '_ {
+        std::future::poll_fn(move |cx| self.poll(cx))
+    }
+
+    fn limit(&self) -> usize {
+        match self.concurrency {
+            Concurrency::Latest => usize::MAX,
+            Concurrency::Queue => 1,
+            Concurrency::Parallel(n) => n.max(1),
+        }
+    }
+
+    /// Starts queued tasks while the policy allows, returning whether any started.
+    fn start_queued(&self) -> bool {
+        let mut started = false;
+        while self.active.get() < self.limit() {
+            let Some(task) = self.queued.borrow_mut().pop_front() else {
+                break;
+            };
+            self.active.set(self.active.get() + 1);
+            self.running.borrow_mut().push(task);
+            started = true;
+        }
+        started
+    }
+}
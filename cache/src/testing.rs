@@ -0,0 +1,67 @@
+//! Utilities for stress-testing the runtime and reactive abstractions built on it.
+//!
+//! Enabled with the `testing` feature.
+
+use std::rc::Rc;
+
+use crate::{Memo, Signal};
+
+pub mod graphgen;
+#[cfg(feature = "proptest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+pub mod proptest;
+
+/// How a memo of a generated graph computes its value from the nodes it reads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Formula {
+    /// The nodes read, as indices into the graph.
+    pub inputs: Vec<usize>,
+    /// Whether the first input selects which half of the other inputs is read.
+    pub conditional: bool,
+    /// A constant distinguishing memos reading the same nodes.
+    pub salt: i64,
+}
+
+impl Formula {
+    /// Computes the value of the memo, reading the value of its `n`th input with `read(n)`.
+    pub fn eval(&self, mut read: impl FnMut(usize) -> i64) -> i64 {
+        let (sum, read_inputs) = if self.conditional && !self.inputs.is_empty() {
+            let condition = read(0);
+            let rest = 1..self.inputs.len();
+            let half = rest.start + rest.len().div_ceil(2);
+            let branch = if condition % 2 == 0 {
+                rest.start..half
+            } else {
+                half..rest.end
+            };
+            (condition, branch)
+        } else {
+            (0, 0..self.inputs.len())
+        };
+
+        read_inputs
+            .fold(sum, |sum, n| sum.wrapping_add(read(n)))
+            .wrapping_mul(3)
+            .wrapping_add(self.salt)
+    }
+}
+
+#[derive(Clone)]
+enum Node {
+    Signal(Rc<Signal<i64>>),
+    Memo(Rc<Memo<i64>>),
+}
+
+impl Node {
+    fn get(&self) -> i64 {
+        match self {
+            Node::Signal(s) => *s.get(),
+            Node::Memo(m) => m.get(),
+        }
+    }
+
+    /// Creates a memo computing `formula` from `inputs`, the nodes at its input indices.
+    fn memo(formula: Formula, inputs: Vec<Node>) -> Rc<Memo<i64>> {
+        Memo::new(move || formula.eval(|n| inputs[n].get()))
+    }
+}
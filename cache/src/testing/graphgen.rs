@@ -0,0 +1,315 @@
+//! Random signal-memo-effect graphs, driven by random updates and checked against an oracle.
+//!
+//! A [`Graph`] is generated from a [`GraphConfig`]: a layer of signals, followed by layers
+//! of memos reading nodes of the previous layer, and effects reading random memos. Every
+//! memo computes a value from the values it reads, some of them reading different nodes
+//! depending on a condition. [`Graph::run`] then sets random signals, reads random memos
+//! and checks every value read, and every value last seen by an effect, against the values
+//! recomputed from scratch from the signals.
+//!
+//! Generation and updates are deterministic for a given [`GraphConfig::seed`], so a failing
+//! sequence can be replayed. Timing [`Graph::run`] over growing configurations measures how
+//! the runtime scales.
+//!
+//! Effects collect their dependencies on their first run only (see [`Effect::new`]).
+//! An effect reading a memo whose condition later selects other signals is not re-run
+//! when those change, which [`Graph::check`] reports as an [`Inconsistency`].
+//!
+//! # Examples
+//!
+//! ```
+//! use reactive_cache::testing::graphgen::{Graph, GraphConfig};
+//!
+//! let config = GraphConfig {
+//!     depth: 6,
+//!     fan_out: 3,
+//!     conditional: 0.3,
+//!     ..GraphConfig::default()
+//! };
+//!
+//! for seed in 0..10 {
+//!     let mut graph = Graph::new(&GraphConfig { seed, ..config.clone() });
+//!     graph.run(200).unwrap();
+//! }
+//! ```
+
+use std::{cell::Cell, error::Error, fmt, rc::Rc};
+
+use super::{Formula, Node};
+use crate::{Effect, Memo, NodeId, Signal, SignalSetter};
+
+/// The parameters of a generated [`Graph`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphConfig {
+    /// The number of signals.
+    pub signals: usize,
+    /// The number of memo layers above the signals.
+    pub depth: usize,
+    /// The number of memos in each layer.
+    pub width: usize,
+    /// How many memos of the next layer read each node.
+    pub fan_out: usize,
+    /// The fraction of memos reading different nodes depending on a condition, from 0 to 1.
+    pub conditional: f64,
+    /// The number of effects, each reading one memo.
+    pub effects: usize,
+    /// The fraction of memos read after each update, from 0 to 1.
+    ///
+    /// Memos left unread are only brought up to date later, exercising lazy recomputation.
+    pub reads: f64,
+    /// The seed of the generation and of the updates.
+    pub seed: u64,
+}
+
+impl Default for GraphConfig {
+    fn default() -> Self {
+        GraphConfig {
+            signals: 8,
+            depth: 4,
+            width: 8,
+            fan_out: 2,
+            conditional: 0.25,
+            effects: 0,
+            reads: 0.5,
+            seed: 0,
+        }
+    }
+}
+
+/// A value read from a [`Graph`] that differs from the value recomputed from scratch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Inconsistency {
+    /// The number of updates applied before the value was read.
+    pub step: usize,
+    /// The memo read, or the memo read by the effect.
+    pub node: NodeId,
+    /// Whether the value was last seen by an effect rather than read from the memo.
+    pub effect: bool,
+    /// The value recomputed from scratch.
+    pub expected: i64,
+    /// The value read.
+    pub actual: i64,
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = if self.effect {
+            "an effect of memo"
+        } else {
+            "memo"
+        };
+        write!(
+            f,
+            "after {} updates, {} {} saw {} instead of {}",
+            self.step, source, self.node, self.actual, self.expected
+        )
+    }
+}
+
+impl Error for Inconsistency {}
+
+/// A xorshift generator, so that graphs only depend on the seed.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // Xorshift never leaves zero.
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self, p: f64) -> bool {
+        ((self.next() >> 11) as f64 / (1_u64 << 53) as f64) < p
+    }
+}
+
+struct Observer {
+    memo: usize,
+    seen: Rc<Cell<i64>>,
+    _effect: Rc<Effect>,
+}
+
+/// A generated graph of signals, memos and effects. See the [module docs](self).
+pub struct Graph {
+    signals: Vec<Rc<Signal<i64>>>,
+    memos: Vec<Rc<Memo<i64>>>,
+    formulas: Vec<Formula>,
+    observers: Vec<Observer>,
+    reads: f64,
+    rng: Rng,
+    step: usize,
+}
+
+impl Graph {
+    /// Generates a graph.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `signals` or `width` is zero while memos or effects are requested.
+    pub fn new(config: &GraphConfig) -> Self {
+        let mut rng = Rng::new(config.seed);
+        let signals: Vec<_> = (0..config.signals)
+            .map(|_| Signal::new((rng.next() % 100) as i64))
+            .collect();
+        let mut nodes: Vec<Node> = signals.iter().cloned().map(Node::Signal).collect();
+
+        let mut formulas = vec![];
+        let mut memos = vec![];
+        let mut layer = 0..nodes.len();
+        for _ in 0..config.depth {
+            let mut inputs = vec![vec![]; config.width];
+            for node in layer.clone() {
+                for _ in 0..config.fan_out {
+                    let reader = &mut inputs[rng.below(config.width)];
+                    if !reader.contains(&node) {
+                        reader.push(node);
+                    }
+                }
+            }
+
+            let start = nodes.len();
+            for mut inputs in inputs {
+                if inputs.is_empty() {
+                    inputs.push(layer.start + rng.below(layer.len()));
+                }
+                let formula = Formula {
+                    conditional: rng.chance(config.conditional),
+                    salt: formulas.len() as i64,
+                    inputs,
+                };
+
+                let inputs = formula.inputs.iter().map(|&i| nodes[i].clone()).collect();
+                let memo = Node::memo(formula.clone(), inputs);
+                formulas.push(formula);
+                memos.push(memo.clone());
+                nodes.push(Node::Memo(memo));
+            }
+            layer = start..nodes.len();
+        }
+
+        let observers = (0..config.effects)
+            .map(|_| {
+                let memo = rng.below(memos.len());
+                let seen = Rc::new(Cell::new(0));
+                let effect = Effect::new({
+                    let (m, seen) = (memos[memo].clone(), seen.clone());
+                    move || seen.set(m.get())
+                });
+                Observer {
+                    memo,
+                    seen,
+                    _effect: effect,
+                }
+            })
+            .collect();
+
+        Graph {
+            signals,
+            memos,
+            formulas,
+            observers,
+            reads: config.reads,
+            rng,
+            step: 0,
+        }
+    }
+
+    /// Returns the signals, in creation order.
+    pub fn signals(&self) -> &[Rc<Signal<i64>>] {
+        &self.signals
+    }
+
+    /// Returns the memos, layer by layer.
+    pub fn memos(&self) -> &[Rc<Memo<i64>>] {
+        &self.memos
+    }
+
+    /// Recomputes the value of every memo from scratch, from the current values of the signals.
+    pub fn expected(&self) -> Vec<i64> {
+        let mut values: Vec<i64> = self
+            .signals
+            .iter()
+            .map(|s| s.with_untracked(|v| *v))
+            .collect();
+        for formula in &self.formulas {
+            let value = formula.eval(|n| values[formula.inputs[n]]);
+            values.push(value);
+        }
+        values.split_off(self.signals.len())
+    }
+
+    /// Sets a random signal to a random value, then reads random memos and checks them.
+    pub fn step(&mut self) -> Result<(), Inconsistency> {
+        if !self.signals.is_empty() {
+            let signal = self.rng.below(self.signals.len());
+            let value = (self.rng.next() % 100) as i64;
+            self.signals[signal].set(value);
+        }
+        self.step += 1;
+
+        let expected = self.expected();
+        for (i, memo) in self.memos.iter().enumerate() {
+            if self.rng.chance(self.reads) {
+                self.check_memo(memo, expected[i])?;
+            }
+        }
+        self.check_effects(&expected)
+    }
+
+    /// Applies `steps` random updates, checking the graph after each one and at the end.
+    pub fn run(&mut self, steps: usize) -> Result<(), Inconsistency> {
+        for _ in 0..steps {
+            self.step()?;
+        }
+        self.check()
+    }
+
+    /// Checks every memo and every effect against the values recomputed from scratch.
+    pub fn check(&self) -> Result<(), Inconsistency> {
+        let expected = self.expected();
+        for (memo, &expected) in self.memos.iter().zip(&expected) {
+            self.check_memo(memo, expected)?;
+        }
+        self.check_effects(&expected)
+    }
+
+    fn check_memo(&self, memo: &Rc<Memo<i64>>, expected: i64) -> Result<(), Inconsistency> {
+        let actual = memo.get();
+        if actual == expected {
+            return Ok(());
+        }
+        Err(Inconsistency {
+            step: self.step,
+            node: memo.id(),
+            effect: false,
+            expected,
+            actual,
+        })
+    }
+
+    fn check_effects(&self, expected: &[i64]) -> Result<(), Inconsistency> {
+        for observer in &self.observers {
+            let (actual, expected) = (observer.seen.get(), expected[observer.memo]);
+            if actual != expected {
+                return Err(Inconsistency {
+                    step: self.step,
+                    node: self.memos[observer.memo].id(),
+                    effect: true,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        Ok(())
+    }
+}
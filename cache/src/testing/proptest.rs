@@ -0,0 +1,313 @@
+//! Property testing of reactive abstractions against reference semantics, with [`proptest`].
+//!
+//! [`Op`] generates random operations on a graph of signals and memos: creating nodes,
+//! setting signals, reading memos and dropping nodes. [`check`] applies a sequence of them
+//! both to a [`System`] under test and to an [`Oracle`] recomputing every value from
+//! scratch, and reports the first read where they disagree.
+//!
+//! [`Nodes`] implements [`System`] with the [`Signal`]s and [`Memo`](crate::Memo)s of this crate.
+//! Implementing [`System`] for another abstraction, such as a wrapper around them or a
+//! custom [`Observable`](crate::Observable), checks it against the same semantics.
+//!
+//! Enabled with the `proptest` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest::{collection::vec, prelude::*, test_runner::TestRunner};
+//! use reactive_cache::testing::proptest::{Nodes, Op, check};
+//!
+//! let mut runner = TestRunner::default();
+//! runner
+//!     .run(&vec(any::<Op>(), 0..64), |ops| {
+//!         check(&mut Nodes::default(), &ops)?;
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+
+use std::{error::Error, fmt};
+
+use ::proptest::{collection::vec, prelude::*, sample::Index};
+
+use super::{Formula, Node};
+use crate::{Signal, SignalSetter};
+
+/// An operation on a graph of signals and memos.
+///
+/// Nodes are picked among the live nodes with an [`Index`], so that any sequence of
+/// operations is valid. Operations picking a node when there is none are skipped.
+#[derive(Clone, Debug)]
+pub enum Op {
+    /// Creates a signal with an initial value.
+    CreateSignal(i64),
+    /// Creates a memo reading live nodes, computing a [`Formula`].
+    CreateMemo {
+        inputs: Vec<Index>,
+        conditional: bool,
+    },
+    /// Sets a live signal.
+    SetSignal { signal: Index, value: i64 },
+    /// Reads a live memo and checks its value.
+    ReadMemo(Index),
+    /// Drops a live node. Memos reading it keep using it.
+    DropNode(Index),
+}
+
+impl Arbitrary for Op {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Op>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        // Values are kept small, so that memos often recompute to an equal value.
+        let value = 0..10_i64;
+        prop_oneof![
+            2 => value.clone().prop_map(Op::CreateSignal),
+            3 => (vec(any::<Index>(), 0..4), any::<bool>())
+                .prop_map(|(inputs, conditional)| Op::CreateMemo { inputs, conditional }),
+            4 => (any::<Index>(), value)
+                .prop_map(|(signal, value)| Op::SetSignal { signal, value }),
+            4 => any::<Index>().prop_map(Op::ReadMemo),
+            1 => any::<Index>().prop_map(Op::DropNode),
+        ]
+        .boxed()
+    }
+}
+
+/// A reactive abstraction checked by [`check`].
+///
+/// Nodes are numbered in creation order from zero, signals and memos alike. A dropped
+/// node is never set or read again, but the memos created before it was dropped may
+/// still read it.
+pub trait System {
+    /// Creates a signal.
+    fn create_signal(&mut self, value: i64);
+
+    /// Creates a memo computing `formula` from the nodes numbered by its inputs.
+    fn create_memo(&mut self, formula: &Formula);
+
+    /// Sets the value of a signal.
+    fn set_signal(&mut self, node: usize, value: i64);
+
+    /// Returns the value of a memo.
+    fn read_memo(&mut self, node: usize) -> i64;
+
+    /// Drops a node.
+    fn drop_node(&mut self, node: usize);
+}
+
+/// A [`System`] made of the [`Signal`]s and [`Memo`](crate::Memo)s of this crate.
+#[derive(Default)]
+pub struct Nodes {
+    nodes: Vec<Option<Node>>,
+}
+
+impl Nodes {
+    fn node(&self, node: usize) -> &Node {
+        self.nodes[node].as_ref().expect("node was dropped")
+    }
+}
+
+impl System for Nodes {
+    fn create_signal(&mut self, value: i64) {
+        self.nodes.push(Some(Node::Signal(Signal::new(value))));
+    }
+
+    fn create_memo(&mut self, formula: &Formula) {
+        let inputs = formula
+            .inputs
+            .iter()
+            .map(|&i| self.node(i).clone())
+            .collect();
+        let memo = Node::memo(formula.clone(), inputs);
+        self.nodes.push(Some(Node::Memo(memo)));
+    }
+
+    fn set_signal(&mut self, node: usize, value: i64) {
+        let Node::Signal(signal) = self.node(node) else {
+            panic!("node {node} is not a signal");
+        };
+        signal.set(value);
+    }
+
+    fn read_memo(&mut self, node: usize) -> i64 {
+        self.node(node).get()
+    }
+
+    fn drop_node(&mut self, node: usize) {
+        self.nodes[node] = None;
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Reference {
+    Signal(i64),
+    Memo(Formula),
+}
+
+/// The reference semantics: every memo is recomputed from scratch whenever it is read.
+#[derive(Clone, Debug, Default)]
+pub struct Oracle {
+    nodes: Vec<Reference>,
+    live: Vec<bool>,
+}
+
+impl Oracle {
+    /// Creates a signal, returning its number.
+    pub fn create_signal(&mut self, value: i64) -> usize {
+        self.push(Reference::Signal(value))
+    }
+
+    /// Creates a memo, returning its number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an input is not the number of an existing node.
+    pub fn create_memo(&mut self, formula: Formula) -> usize {
+        assert!(formula.inputs.iter().all(|&i| i < self.nodes.len()));
+        self.push(Reference::Memo(formula))
+    }
+
+    /// Sets the value of a signal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is not a signal.
+    pub fn set_signal(&mut self, node: usize, value: i64) {
+        let Reference::Signal(v) = &mut self.nodes[node] else {
+            panic!("node {node} is not a signal");
+        };
+        *v = value;
+    }
+
+    /// Drops a node, which is no longer picked by [`Op`]s.
+    pub fn drop_node(&mut self, node: usize) {
+        self.live[node] = false;
+    }
+
+    /// Returns whether a node has not been dropped.
+    pub fn is_live(&self, node: usize) -> bool {
+        self.live[node]
+    }
+
+    /// Returns whether a node is a signal.
+    pub fn is_signal(&self, node: usize) -> bool {
+        matches!(self.nodes[node], Reference::Signal(_))
+    }
+
+    /// Recomputes the values of every node from scratch, in creation order.
+    pub fn values(&self) -> Vec<i64> {
+        let mut values = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let value = match node {
+                Reference::Signal(value) => *value,
+                Reference::Memo(formula) => formula.eval(|n| values[formula.inputs[n]]),
+            };
+            values.push(value);
+        }
+        values
+    }
+
+    fn push(&mut self, node: Reference) -> usize {
+        self.nodes.push(node);
+        self.live.push(true);
+        self.nodes.len() - 1
+    }
+
+    /// Picks a live node, among signals, memos or both.
+    fn pick(&self, index: &Index, signal: Option<bool>) -> Option<usize> {
+        let nodes: Vec<_> = (0..self.nodes.len())
+            .filter(|&n| self.live[n] && signal.is_none_or(|s| s == self.is_signal(n)))
+            .collect();
+        (!nodes.is_empty()).then(|| nodes[index.index(nodes.len())])
+    }
+}
+
+/// A memo of a [`System`] whose value differs from the value recomputed by the [`Oracle`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The number of operations applied before the memo was read.
+    pub step: usize,
+    /// The number of the memo.
+    pub node: usize,
+    /// The value recomputed from scratch.
+    pub expected: i64,
+    /// The value read.
+    pub actual: i64,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "after {} operations, memo {} read {} instead of {}",
+            self.step, self.node, self.actual, self.expected
+        )
+    }
+}
+
+impl Error for Divergence {}
+
+/// Applies `ops` to `system` and to an [`Oracle`], checking every memo read, then every
+/// live memo at the end.
+pub fn check(system: &mut impl System, ops: &[Op]) -> Result<(), Divergence> {
+    let mut oracle = Oracle::default();
+    let read = |system: &mut dyn System, oracle: &Oracle, step, node| {
+        let (expected, actual) = (oracle.values()[node], system.read_memo(node));
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(Divergence {
+                step,
+                node,
+                expected,
+                actual,
+            })
+        }
+    };
+
+    for (step, op) in ops.iter().enumerate() {
+        match op {
+            Op::CreateSignal(value) => {
+                oracle.create_signal(*value);
+                system.create_signal(*value);
+            }
+            Op::CreateMemo {
+                inputs,
+                conditional,
+            } => {
+                let formula = Formula {
+                    inputs: inputs.iter().filter_map(|i| oracle.pick(i, None)).collect(),
+                    conditional: *conditional,
+                    salt: step as i64,
+                };
+                system.create_memo(&formula);
+                oracle.create_memo(formula);
+            }
+            Op::SetSignal { signal, value } => {
+                if let Some(node) = oracle.pick(signal, Some(true)) {
+                    oracle.set_signal(node, *value);
+                    system.set_signal(node, *value);
+                }
+            }
+            Op::ReadMemo(memo) => {
+                if let Some(node) = oracle.pick(memo, Some(false)) {
+                    read(system, &oracle, step, node)?;
+                }
+            }
+            Op::DropNode(node) => {
+                if let Some(node) = oracle.pick(node, None) {
+                    oracle.drop_node(node);
+                    system.drop_node(node);
+                }
+            }
+        }
+    }
+
+    for node in 0..oracle.nodes.len() {
+        if oracle.is_live(node) && !oracle.is_signal(node) {
+            read(system, &oracle, ops.len(), node)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+//! Redrawing terminal UIs only when their reactive state changed.
+//!
+//! Enabled with the `tui` feature. A [`View`] keeps a [`DirtyFlag`] per widget, set by an
+//! effect whenever the signals and memos read by that widget change. Its
+//! [`needs_redraw`](View::needs_redraw) memo tells whether any flag is set, and
+//! [`View::draw`], called from the event loop between ratatui draw calls, only renders
+//! a frame when it is.
+//!
+//! # Examples
+//!
+//! ```
+//! use ratatui::{Terminal, backend::TestBackend, widgets::Paragraph};
+//! use reactive_cache::{prelude::*, tui::View};
+//!
+//! let mut terminal = Terminal::new(TestBackend::new(20, 1)).unwrap();
+//! let count = Signal::new(0);
+//!
+//! let view = View::new();
+//! view.watch({
+//!     let count = count.clone();
+//!     move || {
+//!         let _ = count.get();
+//!     }
+//! });
+//! let render = |frame: &mut ratatui::Frame| {
+//!     frame.render_widget(Paragraph::new(format!("count: {}", count.get())), frame.area());
+//! };
+//!
+//! // The first frame is always drawn.
+//! assert!(view.draw(&mut terminal, render).unwrap());
+//! assert!(!view.draw(&mut terminal, render).unwrap());
+//!
+//! count.set(1);
+//! assert!(view.draw(&mut terminal, render).unwrap());
+//! ```
+
+use std::rc::Rc;
+
+use ratatui::{Frame, Terminal, backend::Backend};
+
+use crate::{Effect, Memo, Signal, SignalSetter};
+
+/// Whether the state read by a widget changed since it was last drawn.
+///
+/// Clones share the same flag.
+#[derive(Clone)]
+pub struct DirtyFlag {
+    dirty: Rc<Signal<bool>>,
+    _effect: Rc<Effect>,
+}
+
+impl DirtyFlag {
+    /// Creates a flag set whenever the signals and memos read by `state` change.
+    ///
+    /// `state` runs once now, to collect those dependencies. The flag starts set,
+    /// since the widget was never drawn.
+    #[track_caller]
+    pub fn new(state: impl Fn() + 'static) -> Self {
+        let dirty = Signal::new(true);
+        let effect = Effect::new({
+            let dirty = dirty.clone();
+            move || {
+                state();
+                dirty.set(true);
+            }
+        });
+        DirtyFlag {
+            dirty,
+            _effect: effect,
+        }
+    }
+
+    /// Returns whether the widget needs to be drawn again.
+    ///
+    /// Memos and effects reading the flag are notified when it changes.
+    pub fn is_dirty(&self) -> bool {
+        *self.dirty.get()
+    }
+
+    /// Sets the flag, for example after the terminal was resized.
+    pub fn mark(&self) {
+        self.dirty.set(true);
+    }
+
+    /// Clears the flag, once the widget was drawn.
+    pub fn clear(&self) {
+        self.dirty.set(false);
+    }
+}
+
+/// The dirty flags of the widgets of a terminal UI. See the [module docs](self).
+pub struct View {
+    flags: Rc<Signal<Vec<DirtyFlag>>>,
+    needs_redraw: Rc<Memo<bool>>,
+}
+
+impl View {
+    /// Creates a view without widgets, which needs no redraw until one is watched.
+    pub fn new() -> Self {
+        let flags: Rc<Signal<Vec<DirtyFlag>>> = Signal::new(Vec::new());
+        let needs_redraw = Memo::new({
+            let flags = flags.clone();
+            move || flags.get().iter().any(DirtyFlag::is_dirty)
+        });
+        View {
+            flags,
+            needs_redraw,
+        }
+    }
+
+    /// Adds a widget whose state is read by `state`, and returns its flag.
+    ///
+    /// See [`DirtyFlag::new`]. The flag lives as long as the view.
+    #[track_caller]
+    pub fn watch(&self, state: impl Fn() + 'static) -> DirtyFlag {
+        let flag = DirtyFlag::new(state);
+        let mut flags = self.flags.get().clone();
+        flags.push(flag.clone());
+        self.flags.set_always(flags);
+        flag
+    }
+
+    /// Returns the memo telling whether any widget needs to be drawn again.
+    pub fn needs_redraw(&self) -> &Rc<Memo<bool>> {
+        &self.needs_redraw
+    }
+
+    /// Sets the flag of every widget, for example after the terminal was resized.
+    pub fn mark_all(&self) {
+        for flag in self.flags.get().iter() {
+            flag.mark();
+        }
+    }
+
+    /// Runs the deferred effects, then draws a frame with `render` if any widget needs
+    /// it, and clears every flag.
+    ///
+    /// Call it on every iteration of the event loop, in place of [`Terminal::draw`].
+    /// Returns whether a frame was drawn.
+    pub fn draw<B: Backend>(
+        &self,
+        terminal: &mut Terminal<B>,
+        render: impl FnOnce(&mut Frame),
+    ) -> Result<bool, B::Error> {
+        crate::flush();
+        if !self.needs_redraw.get() {
+            return Ok(false);
+        }
+
+        terminal.draw(render)?;
+        for flag in self.flags.get().iter() {
+            flag.clear();
+        }
+        Ok(true)
+    }
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,170 @@
+//! Signals fed by DOM events and browser state.
+//!
+//! Enabled with the `wasm` feature. The helpers register an event listener that sets
+//! a signal, and remove the listener when the signal is dropped, so the lifetime of the
+//! subscription is the lifetime of the signal. Listeners only hold a weak reference to
+//! their signal, so they never keep it alive.
+//!
+//! - [`signal_from_event`] holds the latest event fired on a target.
+//! - [`signal_from_event_with`] holds a value computed from the latest event.
+//! - [`media_query_signal`] holds whether a CSS media query matches.
+//!
+//! [`persist_local`] keeps a signal in `localStorage`, so web app state survives reloads.
+//!
+//! The helpers only work in a browser: elsewhere, web-sys functions panic.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use reactive_cache::{prelude::*, wasm};
+//!
+//! let window = web_sys::window().unwrap();
+//! let width = wasm::signal_from_event_with(
+//!     &window,
+//!     "resize",
+//!     window.inner_width().unwrap().as_f64().unwrap(),
+//!     {
+//!         let window = window.clone();
+//!         move |_| window.inner_width().unwrap().as_f64().unwrap()
+//!     },
+//! )
+//! .unwrap();
+//! let dark = wasm::media_query_signal("(prefers-color-scheme: dark)").unwrap();
+//!
+//! let layout = Memo::new({
+//!     let (width, dark) = (width.clone(), dark.clone());
+//!     move || {
+//!         let columns = (*width.get() / 300.0).max(1.0) as u32;
+//!         let theme = if *dark.get() { "dark" } else { "light" };
+//!         format!("{columns} columns, {theme} theme")
+//!     }
+//! });
+//! ```
+
+use std::rc::Rc;
+
+use serde::{Serialize, de::DeserializeOwned};
+use wasm_bindgen::{JsCast, JsValue, closure::Closure};
+use web_sys::{Event, EventTarget, Storage};
+
+use crate::{Effect, Observable, Signal, SignalSetter};
+
+/// Creates a signal holding the latest `event` fired on `target`, or `None` before the first one.
+///
+/// Returns the error thrown by `addEventListener`, if any.
+pub fn signal_from_event(
+    target: &EventTarget,
+    event: &str,
+) -> Result<Rc<Signal<Option<Event>>>, JsValue> {
+    signal_from_event_with(target, event, None, |e| Some(e.clone()))
+}
+
+/// Creates a signal holding `initial`, then `map` of each `event` fired on `target`.
+///
+/// Returns the error thrown by `addEventListener`, if any.
+pub fn signal_from_event_with<T: 'static>(
+    target: &EventTarget,
+    event: &str,
+    initial: T,
+    map: impl Fn(&Event) -> T + 'static,
+) -> Result<Rc<Signal<T>>, JsValue> {
+    let signal = Signal::new(initial);
+    listen(&signal, target, event, move |signal, e| {
+        signal.set_value(map(e));
+    })?;
+    Ok(signal)
+}
+
+/// Creates a signal holding whether the CSS media `query` matches, such as
+/// `"(prefers-color-scheme: dark)"` or `"(max-width: 600px)"`.
+///
+/// Returns an error if there is no window or the query is invalid.
+pub fn media_query_signal(query: &str) -> Result<Rc<Signal<bool>>, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let list = window
+        .match_media(query)?
+        .ok_or_else(|| JsValue::from_str("invalid media query"))?;
+
+    let signal = Signal::new(list.matches());
+    listen(&signal, &list, "change", {
+        let list = list.clone();
+        move |signal, _| {
+            signal.set(list.matches());
+        }
+    })?;
+    Ok(signal)
+}
+
+/// Hydrates `signal` from the JSON stored under `key` in `localStorage`, then stores
+/// every new value of the signal there.
+///
+/// The signal keeps its value if nothing is stored under `key` yet, or if the stored
+/// JSON does not deserialize into `T`, for example after the type changed. Values are
+/// stored until the returned effect is dropped; failures to store them, such as an
+/// exceeded quota, are ignored.
+///
+/// Returns an error if there is no window or `localStorage` is unavailable.
+///
+/// # Examples
+///
+/// ```no_run
+/// use reactive_cache::{prelude::*, wasm};
+///
+/// let todos = Signal::new(Vec::<String>::new());
+/// let persistence = wasm::persist_local("todos", &todos).unwrap();
+///
+/// // Stored in `localStorage` and restored on the next visit.
+/// todos.set(vec!["water the plants".to_string()]);
+/// ```
+pub fn persist_local<T>(key: &str, signal: &Rc<Signal<T>>) -> Result<Rc<Effect>, JsValue>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    let storage = local_storage()?;
+    if let Some(value) = storage
+        .get_item(key)?
+        .and_then(|json| serde_json::from_str(&json).ok())
+    {
+        signal.set_value(value);
+    }
+
+    let (key, signal) = (key.to_owned(), Rc::downgrade(signal));
+    Ok(Effect::new(move || {
+        let Some(signal) = signal.upgrade() else {
+            return;
+        };
+        if let Ok(json) = serde_json::to_string(&*signal.get()) {
+            let _ = storage.set_item(&key, &json);
+        }
+    }))
+}
+
+fn local_storage() -> Result<Storage, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    window
+        .local_storage()?
+        .ok_or_else(|| JsValue::from_str("localStorage is unavailable"))
+}
+
+/// Calls `on_event` with `signal` on every `event` fired on `target`, until `signal` is dropped.
+fn listen<T: 'static>(
+    signal: &Rc<Signal<T>>,
+    target: &EventTarget,
+    event: &str,
+    on_event: impl Fn(&Signal<T>, &Event) + 'static,
+) -> Result<(), JsValue> {
+    let weak = Rc::downgrade(signal);
+    let listener = Closure::<dyn Fn(Event)>::new(move |e: Event| {
+        if let Some(signal) = weak.upgrade() {
+            on_event(&signal, &e);
+        }
+    });
+    target.add_event_listener_with_callback(event, listener.as_ref().unchecked_ref())?;
+
+    let (target, event) = (target.clone(), event.to_owned());
+    signal.on_drop(move || {
+        let _ =
+            target.remove_event_listener_with_callback(&event, listener.as_ref().unchecked_ref());
+    });
+    Ok(())
+}
@@ -1,6 +1,11 @@
+use std::collections::{HashMap, VecDeque};
+
 use proc_macro::TokenStream;
-use quote::{format_ident, quote};
-use syn::{Ident, ItemFn, ItemStatic, ReturnType, parse_macro_input};
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    Data, DeriveInput, Expr, ExprCall, File, FnArg, Ident, Index, Item, ItemFn, ItemStatic, Pat,
+    Path, ReturnType, parse_macro_input, visit::Visit,
+};
 
 /// Wraps a `static mut` variable as a reactive global signal.
 ///
@@ -8,10 +13,14 @@ use syn::{Ident, ItemFn, ItemStatic, ReturnType, parse_macro_input};
 /// and generates a **function with the same name as the variable** that returns a
 /// `&'static Rc<Signal<T>>`. You can then call `.get()` to read the value or `.set(value)` to update it.
 ///
+/// Like the reactive runtime, the signal is local to each thread: every thread calling the
+/// function gets a signal of its own, created on its first call and never dropped.
+///
 /// # Requirements
 ///
 /// - Supports only `static mut` variables.
-/// - Type `T` must implement `Eq`.
+/// - Without the `nightly` feature of `reactive_cache`, `.set(value)` requires `T` to
+///   implement `PartialEq`; other values are set with `.set_always(value)`.
 ///
 /// # Examples
 ///
@@ -33,11 +42,6 @@ use syn::{Ident, ItemFn, ItemStatic, ReturnType, parse_macro_input};
 /// assert_eq!(*B().get(), "world");
 /// ```
 ///
-/// # SAFETY
-///
-/// This macro wraps `static mut` variables internally, so it **is not thread-safe**.
-/// It should be used only in single-threaded contexts.
-///
 /// # Warning
 ///
 /// **Do not set any signal that is part of the same effect chain.**
@@ -61,21 +65,23 @@ pub fn signal(input: TokenStream) -> TokenStream {
     let ty = &item.ty;
     let expr = &item.expr;
 
-    let lazy_ty = quote! { reactive_cache::Lazy<std::rc::Rc<reactive_cache::Signal<#ty>>> };
-    let expr = quote! { reactive_cache::Lazy::new(|| reactive_cache::Signal::new(#expr)) };
+    let rc_ty = quote! { std::rc::Rc<reactive_cache::Signal<#ty>> };
+    let expr = leaked(quote! { reactive_cache::Signal::new(#expr) });
 
     let expanded = quote! {
         #[allow(non_snake_case)]
-        #vis fn #ident() -> &'static std::rc::Rc<reactive_cache::Signal<#ty>> {
-            static mut #ident: #lazy_ty = #expr;
-            unsafe { &*#ident }
+        #vis fn #ident() -> &'static #rc_ty {
+            thread_local! {
+                static #ident: &'static #rc_ty = #expr;
+            }
+            #ident.with(|signal| *signal)
         }
     };
 
     expanded.into()
 }
 
-/// Turns a zero-argument function into a memoized, reactive computation.
+/// Turns a function into a memoized, reactive computation.
 ///
 /// The `#[memo]` attribute macro transforms a function into a static
 /// `reactive_cache::Memo`, which:
@@ -83,10 +89,20 @@ pub fn signal(input: TokenStream) -> TokenStream {
 /// 2. Caches the result for future calls.
 /// 3. Automatically tracks reactive dependencies if used inside `Signal` or other reactive contexts.
 ///
+/// An `async fn` is backed by a `reactive_cache::AsyncMemo` instead: the first call
+/// awaits the computation and caches its output, later calls return the cached value
+/// until one of the signals or memos it read changes.
+///
+/// A function with parameters is backed by a `reactive_cache::KeyedMemo` keyed on its
+/// arguments: each distinct set of arguments gets a memo of its own, and those of the
+/// least recently used arguments are dropped.
+///
 /// # Requirements
 ///
-/// - The function must have **no parameters**.
 /// - The function must return a value (`-> T`), which must implement `Clone`.
+/// - Parameters must be plain identifiers, whose types implement `Clone`, `Hash` and `Eq`
+///   and are `'static`.
+/// - An `async fn` must have **no parameters**.
 ///
 /// # Examples
 ///
@@ -119,11 +135,55 @@ pub fn signal(input: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
-/// # SAFETY
+/// Functions with parameters cache a value per set of arguments:
 ///
-/// This macro uses a `static mut` internally, so it **is not thread-safe**.
-/// It is intended for single-threaded usage only. Accessing the memo from
-/// multiple threads concurrently can cause undefined behavior.
+/// ```rust
+/// use reactive_cache::prelude::*;
+/// use reactive_macros::{memo, signal};
+///
+/// signal!(static mut TAX_RATE: u32 = 10;);
+///
+/// #[memo]
+/// pub fn price_with_tax(price: u32, quantity: u32) -> u32 {
+///     price * quantity * (100 + *TAX_RATE().get()) / 100
+/// }
+///
+/// fn main() {
+///     assert_eq!(price_with_tax(100, 2), 220);
+///     assert_eq!(price_with_tax(50, 1), 55);
+///
+///     TAX_RATE().set(20);
+///     assert_eq!(price_with_tax(100, 2), 240);
+/// }
+/// ```
+///
+/// Asynchronous memos work with any executor:
+///
+/// ```rust
+/// use std::{pin::pin, task::{Context, Poll, Waker}};
+/// use reactive_cache::prelude::*;
+/// use reactive_macros::{memo, signal};
+///
+/// signal!(static mut USER_ID: u32 = 1;);
+///
+/// #[memo]
+/// pub async fn load_profile() -> String {
+///     format!("user #{}", *USER_ID().get())
+/// }
+///
+/// fn main() {
+///     let mut cx = Context::from_waker(Waker::noop());
+///     assert_eq!(pin!(load_profile()).poll(&mut cx), Poll::Ready("user #1".to_string()));
+///
+///     USER_ID().set(2);
+///     assert_eq!(pin!(load_profile()).poll(&mut cx), Poll::Ready("user #2".to_string()));
+/// }
+/// ```
+///
+/// # Threads
+///
+/// Like the reactive runtime, the memo is local to each thread: every thread calling the
+/// function computes and caches its value separately.
 #[proc_macro_attribute]
 pub fn memo(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let func = parse_macro_input!(item as ItemFn);
@@ -142,29 +202,274 @@ pub fn memo(_attr: TokenStream, item: TokenStream) -> TokenStream {
         }
     };
 
-    if !sig.inputs.is_empty() {
+    if sig.asyncness.is_some() && !sig.inputs.is_empty() {
         return syn::Error::new_spanned(
             &sig.inputs,
-            "The memo macro can only be used with `get` function without any parameters.",
+            "The memo macro can only be used with `async` functions without any parameters.",
         )
         .to_compile_error()
         .into();
     }
 
     let ident = format_ident!("{}", ident.to_string().to_uppercase());
-    let ty = quote! { reactive_cache::Lazy<std::rc::Rc<reactive_cache::Memo<#output_ty>>> };
-    let expr = quote! { reactive_cache::Lazy::new(|| reactive_cache::Memo::new(|| #block)) };
 
-    let expanded = quote! {
-        #vis #sig {
-            static mut #ident: #ty = #expr;
-            unsafe { #ident.get() }
+    let expanded = if !sig.inputs.is_empty() {
+        let mut sig = sig.clone();
+        let mut params = vec![];
+        for input in sig.inputs.iter_mut() {
+            let FnArg::Typed(input) = input else {
+                return syn::Error::new_spanned(
+                    input,
+                    "The memo macro cannot be used with methods.",
+                )
+                .to_compile_error()
+                .into();
+            };
+            let Pat::Ident(pat) = &mut *input.pat else {
+                return syn::Error::new_spanned(
+                    &input.pat,
+                    "The parameters of a memo must be plain identifiers.",
+                )
+                .to_compile_error()
+                .into();
+            };
+            // The arguments are only moved into the key, which the computation clones.
+            let binding = pat.clone();
+            pat.mutability = None;
+            params.push((binding, pat.ident.clone(), input.ty.clone()));
+        }
+
+        let bindings = params.iter().map(|(binding, _, _)| binding);
+        let args = params.iter().map(|(_, arg, _)| arg);
+        let tys: Vec<_> = params.iter().map(|(_, _, ty)| ty).collect();
+        let index = (0..params.len()).map(Index::from);
+
+        let key_ty = quote! { (#(#tys,)*) };
+        let ty = quote! { std::rc::Rc<reactive_cache::KeyedMemo<#key_ty, #output_ty>> };
+        let expr = leaked(quote! {
+            reactive_cache::KeyedMemo::new(|key: &#key_ty| {
+                #(let #bindings: #tys = std::clone::Clone::clone(&key.#index);)*
+                #block
+            })
+        });
+
+        quote! {
+            #vis #sig {
+                thread_local! {
+                    static #ident: &'static #ty = #expr;
+                }
+                #ident.with(|memo| *memo).get((#(#args,)*))
+            }
+        }
+    } else if sig.asyncness.is_some() {
+        let ty = quote! { std::rc::Rc<reactive_cache::AsyncMemo<#output_ty>> };
+        let expr = leaked(quote! { reactive_cache::AsyncMemo::new(|| async #block) });
+
+        quote! {
+            #vis #sig {
+                thread_local! {
+                    static #ident: &'static #ty = #expr;
+                }
+                #ident.with(|memo| *memo).get().await
+            }
+        }
+    } else {
+        let ty = quote! { std::rc::Rc<reactive_cache::Memo<#output_ty>> };
+        let expr = leaked(quote! { reactive_cache::Memo::new(|| #block) });
+
+        quote! {
+            #vis #sig {
+                thread_local! {
+                    static #ident: &'static #ty = #expr;
+                }
+                #ident.with(|memo| *memo).get()
+            }
         }
     };
 
     expanded.into()
 }
 
+/// Leaks the node created by `expr`, so that the thread-local holding it is never
+/// dropped, like a `static`.
+fn leaked(expr: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! { std::boxed::Box::leak(std::boxed::Box::new(#expr)) }
+}
+
+/// Checks at compile time that the `#[memo]` functions it wraps do not call each other in a cycle.
+///
+/// A memo that reads itself, directly or through other memos, panics with
+/// "Memo dependency cycle detected" when it is first computed. Wrapping the definitions
+/// of related functions in `check_graph!` reports such cycles as a compile error instead,
+/// pointing at the call that closes the cycle and naming every function in it.
+///
+/// Every call between the wrapped functions is considered, including calls to functions
+/// without `#[memo]` in between, and calls inside closures that may never run.
+/// Only cycles going through at least one `#[memo]` function are reported.
+/// The wrapped items are emitted unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use reactive_macros::{check_graph, memo, signal};
+///
+/// signal!(static mut PRICE: u32 = 10;);
+///
+/// check_graph! {
+///     #[memo]
+///     pub fn total() -> u32 {
+///         subtotal() + shipping()
+///     }
+///
+///     #[memo]
+///     pub fn subtotal() -> u32 {
+///         *PRICE().get() * 2
+///     }
+///
+///     fn shipping() -> u32 {
+///         if subtotal() > 50 { 0 } else { 5 }
+///     }
+/// }
+///
+/// assert_eq!(total(), 25);
+/// ```
+///
+/// A cycle fails to compile:
+///
+/// ```compile_fail
+/// use reactive_macros::{check_graph, memo};
+///
+/// check_graph! {
+///     #[memo]
+///     pub fn ping() -> i32 {
+///         pong() + 1
+///     }
+///
+///     #[memo]
+///     pub fn pong() -> i32 {
+///         ping() + 1 // error: memo dependency cycle: `ping` -> `pong` -> `ping`
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn check_graph(input: TokenStream) -> TokenStream {
+    let file = parse_macro_input!(input as File);
+
+    let functions: Vec<&ItemFn> = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Fn(f) => Some(f),
+            _ => None,
+        })
+        .collect();
+    let index: HashMap<String, usize> = functions
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (f.sig.ident.to_string(), i))
+        .collect();
+
+    let calls: Vec<Vec<(usize, &Path)>> = functions
+        .iter()
+        .map(|f| {
+            let mut visitor = Calls {
+                index: &index,
+                calls: vec![],
+            };
+            visitor.visit_block(&f.block);
+            visitor.calls
+        })
+        .collect();
+    let memos: Vec<bool> = functions
+        .iter()
+        .map(|f| f.attrs.iter().any(|a| a.path().is_ident("memo")))
+        .collect();
+
+    for memo in (0..functions.len()).filter(|&i| memos[i]) {
+        if let Some((cycle, call)) = find_cycle(memo, &calls) {
+            let chain = cycle
+                .iter()
+                .map(|&i| format!("`{}`", functions[i].sig.ident))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return syn::Error::new_spanned(call, format!("memo dependency cycle: {chain}"))
+                .to_compile_error()
+                .into();
+        }
+    }
+
+    file.items
+        .iter()
+        .map(ToTokens::to_token_stream)
+        .collect::<proc_macro2::TokenStream>()
+        .into()
+}
+
+/// Collects the calls to the functions wrapped by `check_graph!`.
+struct Calls<'a, 'ast> {
+    index: &'a HashMap<String, usize>,
+    calls: Vec<(usize, &'ast Path)>,
+}
+
+impl<'ast> Visit<'ast> for Calls<'_, 'ast> {
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(callee) = &*call.func
+            && let Some(i) = local_function(&callee.path).and_then(|name| self.index.get(&name))
+        {
+            self.calls.push((*i, &callee.path));
+        }
+        syn::visit::visit_expr_call(self, call);
+    }
+}
+
+/// Returns the name of the function called by `path`, if it may be defined in the same module.
+fn local_function(path: &Path) -> Option<String> {
+    let last = path.segments.last()?;
+    let local = path.leading_colon.is_none()
+        && path
+            .segments
+            .iter()
+            .rev()
+            .skip(1)
+            .all(|s| s.ident == "self")
+        && last.arguments.is_none();
+    local.then(|| last.ident.to_string())
+}
+
+/// Searches for the shortest chain of calls leading from `memo` back to itself.
+///
+/// Returns the functions in the cycle, starting and ending with `memo`, and the call closing it.
+fn find_cycle<'ast>(
+    memo: usize,
+    calls: &[Vec<(usize, &'ast Path)>],
+) -> Option<(Vec<usize>, &'ast Path)> {
+    // The caller and call through which each function was first reached.
+    let mut reached: Vec<Option<(usize, &Path)>> = vec![None; calls.len()];
+    let mut queue = VecDeque::from([memo]);
+
+    while let Some(caller) = queue.pop_front() {
+        for &(callee, call) in &calls[caller] {
+            if reached[callee].is_some() {
+                continue;
+            }
+            reached[callee] = Some((caller, call));
+            if callee == memo {
+                let mut cycle = vec![memo];
+                let mut node = caller;
+                while node != memo {
+                    cycle.push(node);
+                    node = reached[node].unwrap().0;
+                }
+                cycle.push(memo);
+                cycle.reverse();
+                return Some((cycle, call));
+            }
+            queue.push_back(callee);
+        }
+    }
+    None
+}
+
 /// Evaluates a zero-argument function and optionally reports when the value changes.
 ///
 /// The `#[evaluate(print_fn)]` attribute macro transforms a function into a reactive
@@ -255,3 +560,149 @@ pub fn evaluate(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     expanded.into()
 }
+
+/// Derives per-variant selectors for an enum held in a `reactive_cache::Signal`.
+///
+/// For an enum `AppMode`, the `#[derive(SignalVariants)]` macro generates:
+/// 1. A struct `AppModeSelectors` with one `is_<variant>()` method per variant,
+///    each returning an `Rc<Memo<bool>>`.
+/// 2. An associated function `AppMode::selectors(&Rc<Signal<AppMode>>) -> AppModeSelectors`.
+///
+/// Each selector only changes when the source enters or leaves its variant
+/// (equality cutoff), so an effect reading `is_editing()` does not re-run when the
+/// source switches between two other variants.
+///
+/// # Requirements
+///
+/// - Supports only enums without generic parameters.
+/// - The returned selectors struct must be kept alive for the selectors to keep updating.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::{cell::Cell, rc::Rc};
+/// use reactive_cache::prelude::*;
+/// use reactive_macros::SignalVariants;
+///
+/// #[derive(SignalVariants, PartialEq, Eq)]
+/// enum AppMode {
+///     Viewing,
+///     Editing { id: u32 },
+///     Closed,
+/// }
+///
+/// let mode = Signal::new(AppMode::Viewing);
+/// let selectors = AppMode::selectors(&mode);
+///
+/// let runs = Rc::new(Cell::new(0));
+/// let effect = Effect::new({
+///     let (is_editing, runs) = (selectors.is_editing(), runs.clone());
+///     move || {
+///         let _ = is_editing.get();
+///         runs.set(runs.get() + 1);
+///     }
+/// });
+/// assert_eq!(runs.get(), 1);
+///
+/// // Switching between other variants does not re-run the effect.
+/// mode.set(AppMode::Closed);
+/// assert_eq!(runs.get(), 1);
+///
+/// mode.set(AppMode::Editing { id: 7 });
+/// assert_eq!(runs.get(), 2);
+/// assert!(selectors.is_editing().get());
+/// assert!(!selectors.is_viewing().get());
+/// ```
+///
+/// # SAFETY
+///
+/// The generated selectors rely on `reactive_cache::Effect`, which is **not thread-safe**.
+/// Only use in single-threaded contexts.
+#[proc_macro_derive(SignalVariants)]
+pub fn signal_variants(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let Data::Enum(data) = &input.data else {
+        return syn::Error::new_spanned(
+            &input.ident,
+            "SignalVariants can only be derived for enums.",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    if !input.generics.params.is_empty() {
+        return syn::Error::new_spanned(
+            &input.generics,
+            "SignalVariants cannot be derived for generic enums.",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let vis = &input.vis;
+    let ident = &input.ident;
+    let selectors = format_ident!("{}Selectors", ident);
+
+    let variants: Vec<_> = data.variants.iter().map(|v| &v.ident).collect();
+    let methods: Vec<_> = variants
+        .iter()
+        .map(|v| format_ident!("is_{}", to_snake_case(&v.to_string())))
+        .collect();
+
+    let expanded = quote! {
+        #vis struct #selectors {
+            #( #methods: std::rc::Rc<reactive_cache::Memo<bool>>, )*
+            _effect: std::rc::Rc<reactive_cache::Effect>,
+        }
+
+        impl #selectors {
+            #(
+                #vis fn #methods(&self) -> std::rc::Rc<reactive_cache::Memo<bool>> {
+                    std::rc::Rc::clone(&self.#methods)
+                }
+            )*
+        }
+
+        impl #ident {
+            #vis fn selectors(
+                signal: &std::rc::Rc<reactive_cache::Signal<#ident>>,
+            ) -> #selectors {
+                use reactive_cache::SignalSetter as _;
+
+                #( let #methods = reactive_cache::Signal::new(false); )*
+
+                let _effect = reactive_cache::Effect::new({
+                    let signal = std::rc::Rc::clone(signal);
+                    #( let #methods = std::rc::Rc::clone(&#methods); )*
+                    move || {
+                        let value = signal.get();
+                        #( #methods.set(matches!(*value, #ident::#variants { .. })); )*
+                    }
+                });
+
+                #selectors {
+                    #( #methods: reactive_cache::Memo::new(move || *#methods.get()), )*
+                    _effect,
+                }
+            }
+        }
+    };
+
+    expanded.into()
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.char_indices() {
+        if c.is_uppercase() {
+            if i != 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
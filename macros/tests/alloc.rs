@@ -0,0 +1,85 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    rc::Rc,
+};
+
+use reactive_cache::{prelude::*, runtime};
+
+/// Counts the allocations made by each thread, so that other tests do not interfere.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn steady_state_updates_do_not_allocate() {
+    runtime::set_recorder_capacity(0);
+
+    let a = Signal::new(0);
+    let b = Signal::new(0);
+    let sum = Memo::new({
+        let (a, b) = (a.clone(), b.clone());
+        move || *a.get() + *b.get()
+    });
+    // Recomputed to an equal value whenever only the low bits of `sum` change.
+    let tens = Memo::new({
+        let sum = sum.clone();
+        move || sum.get() / 10
+    });
+    let seen = Rc::new(Cell::new(0));
+    let effect = Effect::new({
+        let (tens, seen) = (tens.clone(), seen.clone());
+        move || seen.set(tens.get())
+    });
+    let nested = Effect::new({
+        let (a, b) = (a.clone(), b.clone());
+        // Setting a signal while effects run nests the propagation.
+        move || {
+            b.set(*a.get() * 2);
+        }
+    });
+
+    // Grows the buffers reused by later updates.
+    a.set(1);
+
+    let allocated = allocations(|| {
+        for i in 0..100 {
+            a.set(i);
+            // Unchanged.
+            a.set(i);
+            assert_eq!(sum.get(), i * 3);
+            assert_eq!(tens.get(), i * 3 / 10);
+            assert_eq!(seen.get(), i * 3 / 10);
+        }
+    });
+    assert_eq!(allocated, 0);
+
+    drop((effect, nested));
+}
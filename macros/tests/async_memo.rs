@@ -0,0 +1,69 @@
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use reactive_cache::prelude::*;
+use reactive_macros::{memo, signal};
+
+thread_local! {
+    static LOADS: Cell<u32> = const { Cell::new(0) };
+}
+
+signal!(
+    static mut ID: u32 = 1;
+);
+signal!(
+    static mut SUFFIX: &'static str = "!";
+);
+
+/// Returns `Pending` on its first poll, like a request in flight.
+async fn yield_once() {
+    let mut yielded = false;
+    std::future::poll_fn(|cx| {
+        if std::mem::replace(&mut yielded, true) {
+            Poll::Ready(())
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+#[memo]
+pub async fn load_name() -> String {
+    LOADS.set(LOADS.get() + 1);
+    let id = *ID().get();
+    yield_once().await;
+    // Signals read after an await are tracked as well.
+    format!("user #{id}{}", *SUFFIX().get())
+}
+
+#[test]
+fn async_memo_shares_and_caches_computations() {
+    let mut cx = Context::from_waker(Waker::noop());
+
+    // Both calls await the same computation, which either of them drives.
+    let mut first = pin!(load_name());
+    let second = pin!(load_name());
+    assert!(first.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(second.poll(&mut cx), Poll::Ready("user #1!".to_string()));
+    assert_eq!(first.poll(&mut cx), Poll::Ready("user #1!".to_string()));
+    assert_eq!(LOADS.get(), 1);
+
+    // The cached value is returned without awaiting.
+    assert_eq!(
+        pin!(load_name()).poll(&mut cx),
+        Poll::Ready("user #1!".to_string())
+    );
+    assert_eq!(LOADS.get(), 1);
+
+    SUFFIX().set("?");
+    let mut third = pin!(load_name());
+    assert!(third.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(third.poll(&mut cx), Poll::Ready("user #1?".to_string()));
+    assert_eq!(LOADS.get(), 2);
+}
@@ -28,3 +28,26 @@ fn basic_memoization_works() {
     let s2 = get_text();
     assert_eq!(s1, s2);
 }
+
+static mut FIBONACCI_CALLS: u32 = 0;
+
+#[memo]
+pub fn fibonacci(n: u64) -> u64 {
+    unsafe { FIBONACCI_CALLS += 1 };
+
+    if n < 2 {
+        n
+    } else {
+        fibonacci(n - 1) + fibonacci(n - 2)
+    }
+}
+
+#[test]
+fn memo_with_parameters_caches_per_argument() {
+    assert_eq!(fibonacci(40), 102334155);
+    // Every argument is computed once, and recursive calls reuse their values.
+    assert_eq!(unsafe { FIBONACCI_CALLS }, 41);
+
+    assert_eq!(fibonacci(30), 832040);
+    assert_eq!(unsafe { FIBONACCI_CALLS }, 41);
+}
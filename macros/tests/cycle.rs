@@ -0,0 +1,40 @@
+use reactive_cache::memo::set_max_depth;
+use reactive_macros::memo;
+
+#[memo]
+pub fn ping() -> i32 {
+    pong() + 1
+}
+
+#[memo]
+pub fn pong() -> i32 {
+    ping() + 1
+}
+
+#[memo]
+pub fn leaf() -> i32 {
+    1
+}
+
+#[test]
+#[should_panic(expected = "Memo dependency cycle detected")]
+fn memo_cycle_test() {
+    ping();
+}
+
+#[test]
+fn memo_cycle_reports_creation_sites() {
+    let panic = std::panic::catch_unwind(ping).unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.contains(concat!(file!(), ":4:")), "{message}");
+    assert!(message.contains(concat!(file!(), ":9:")), "{message}");
+}
+
+#[test]
+fn memo_cycle_unwinds_the_memo_stack() {
+    std::panic::catch_unwind(ping).unwrap_err();
+
+    // `ping` and `pong` were popped: a single memo fits in a depth of one.
+    set_max_depth(1);
+    assert_eq!(leaf(), 1);
+}
@@ -162,6 +162,7 @@ fn signal_set_unchanged_test() {
 }
 
 #[test]
+#[cfg_attr(not(feature = "nightly"), ignore = "early cutoff needs nightly")]
 fn signal_set_value_test() {
     SOURCE_A_CALLED.set(false);
     SOURCE_B_CALLED.set(false);
@@ -198,17 +199,18 @@ fn signal_set_value_test() {
     assert!(!DERIVED_C_CALLED.get());
     assert!(!DERIVED_D_CALLED.get());
     assert!(!DERIVED_E_CALLED.get());
+    // `derived_c` is recomputed, but gives the same value, so `derived_d` is not.
     let d2 = derived_d();
     assert!(SOURCE_A_CALLED.get());
     assert!(SOURCE_B_CALLED.get());
     assert!(DERIVED_C_CALLED.get());
-    assert!(DERIVED_D_CALLED.get());
+    assert!(!DERIVED_D_CALLED.get());
     assert!(!DERIVED_E_CALLED.get());
     let c2 = derived_c();
     assert!(SOURCE_A_CALLED.get());
     assert!(SOURCE_B_CALLED.get());
     assert!(DERIVED_C_CALLED.get());
-    assert!(DERIVED_D_CALLED.get());
+    assert!(!DERIVED_D_CALLED.get());
     assert!(!DERIVED_E_CALLED.get());
 
     assert_eq!(c2, c1);
@@ -2,8 +2,8 @@
 
 use std::{cell::Cell, rc::Rc};
 
-use reactive_cache::effect;
 use reactive_cache::prelude::*;
+use reactive_cache::{Scope, effect, on_cleanup};
 use reactive_macros::{memo, signal};
 
 static mut SOURCE_A_CALLED: i32 = 0;
@@ -88,7 +88,7 @@ fn complex_dependency_effect_test() {
     assert_eq!(unsafe { EFFECT_F_CALLED }, 0);
 
     A().set(0);
-    let _ = Rc::into_raw(effect!(effect_e));
+    effect!(effect_e).keep_alive();
 
     unsafe { SOURCE_A_CALLED = 0 };
     unsafe { SOURCE_B_CALLED = 0 };
@@ -107,7 +107,7 @@ fn complex_dependency_effect_test() {
     assert_eq!(unsafe { EFFECT_F_CALLED }, 0);
 
     A().set(0);
-    let _ = Rc::into_raw(effect!(|| { effect_f() }));
+    effect!(|| { effect_f() }).keep_alive();
 
     unsafe { SOURCE_A_CALLED = 0 };
     unsafe { SOURCE_B_CALLED = 0 };
@@ -132,13 +132,15 @@ signal!(
 
 #[test]
 fn loop_effect_test() {
-    let _ = Rc::into_raw(effect!(|| {
+    effect!(|| {
         LOOP_A().get();
-    }));
+    })
+    .keep_alive();
 
-    let _ = Rc::into_raw(effect!(|| {
+    effect!(|| {
         LOOP_A().set(20);
-    }));
+    })
+    .keep_alive();
 }
 
 signal!(
@@ -154,7 +156,7 @@ fn switch_effect_test() {
     let b_rst = Rc::new(Cell::new(0));
 
     let b_rst_clone = b_rst.clone();
-    let _ = Rc::into_raw(effect!(
+    effect!(
         move || {
             match *SWITCH_A().get() {
                 true => {}
@@ -167,7 +169,8 @@ fn switch_effect_test() {
             SWITCH_A().get();
             SWITCH_B().get();
         }
-    ));
+    )
+    .keep_alive();
 
     assert_eq!(b_rst.get(), 0);
 
@@ -177,3 +180,305 @@ fn switch_effect_test() {
     SWITCH_B().set(20);
     assert_eq!(b_rst.get(), 20); // SWITCH_B is reactive because it is included in the deps closure of `effect!`
 }
+
+#[test]
+fn boundary_catches_memo_panic_test() {
+    use reactive_cache::boundary::ErrorBoundary;
+
+    let divisor = Signal::new(1);
+    let quotient = Memo::new({
+        let divisor = divisor.clone();
+        move || 100 / *divisor.get()
+    });
+    let halved = Memo::new({
+        let quotient = quotient.clone();
+        move || quotient.get() / 2
+    });
+
+    let seen = Rc::new(Cell::new(0));
+    let boundary = ErrorBoundary::new(|_| {});
+    let _effect = boundary.scope(|| {
+        Effect::new({
+            let (halved, seen) = (halved.clone(), seen.clone());
+            move || seen.set(halved.get())
+        })
+    });
+    assert_eq!(seen.get(), 50);
+
+    divisor.set(0);
+    assert!(boundary.error().is_some());
+    assert_eq!(seen.get(), 50);
+
+    // The memos that were computing when the panic unwound can be computed again.
+    divisor.set(5);
+    assert_eq!(seen.get(), 10);
+    assert_eq!(halved.get(), 10);
+}
+
+#[test]
+fn effect_keeps_tracking_after_a_snapshot_panicked_test() {
+    let trigger = Signal::new(0);
+    let failing = Memo::new(|| -> i32 { panic!("failed to compute") });
+
+    let runs = Rc::new(Cell::new(0));
+    let _effect = Effect::new({
+        let (trigger, runs) = (trigger.clone(), runs.clone());
+        move || {
+            let snapshot =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| failing.snapshot_value()));
+            assert!(snapshot.is_err());
+            trigger.get();
+            runs.set(runs.get() + 1);
+        }
+    });
+    assert_eq!(runs.get(), 1);
+
+    trigger.set(1);
+    assert_eq!(runs.get(), 2);
+}
+
+#[test]
+fn boundary_stops_owning_effects_once_its_closure_panicked_test() {
+    use reactive_cache::boundary::ErrorBoundary;
+
+    let boundary = ErrorBoundary::new(|_| {});
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        boundary.scope(|| panic!("failed to build the view"))
+    }));
+    assert!(result.is_err());
+
+    // The panic of an effect created afterwards is not caught by the boundary.
+    let result = std::panic::catch_unwind(|| Effect::new(|| -> () { panic!("failed to render") }));
+    assert!(result.is_err());
+    assert!(boundary.error().is_none());
+}
+
+#[test]
+#[cfg_attr(not(feature = "nightly"), ignore = "early cutoff needs nightly")]
+fn effect_skips_run_when_memo_value_unchanged_test() {
+    let count = Signal::new(1);
+    let is_even = Memo::new({
+        let count = count.clone();
+        move || *count.get() % 2 == 0
+    });
+
+    let runs = Rc::new(Cell::new(0));
+    let _effect = Effect::new({
+        let (is_even, runs) = (is_even.clone(), runs.clone());
+        move || {
+            let _ = is_even.get();
+            runs.set(runs.get() + 1);
+        }
+    });
+    assert_eq!(runs.get(), 1);
+
+    // The memo recomputes the same value, so the effect does not run.
+    count.set(3);
+    assert_eq!(runs.get(), 1);
+
+    count.set(4);
+    assert_eq!(runs.get(), 2);
+    assert!(is_even.get());
+}
+
+#[test]
+fn dynamic_effect_follows_taken_branch_test() {
+    let use_celsius = Signal::new(true);
+    let celsius = Signal::new(20);
+    let fahrenheit = Signal::new(68);
+    let label = Memo::new({
+        let celsius = celsius.clone();
+        move || format!("{}°C", celsius.get())
+    });
+
+    let shown = Rc::new(std::cell::RefCell::new(Vec::new()));
+    let _effect = effect!(dynamic; {
+        let (use_celsius, label, fahrenheit, shown) =
+            (use_celsius.clone(), label.clone(), fahrenheit.clone(), shown.clone());
+        move || {
+            let text = if *use_celsius.get() {
+                label.get()
+            } else {
+                format!("{}°F", fahrenheit.get())
+            };
+            shown.borrow_mut().push(text);
+        }
+    });
+
+    use_celsius.set(false);
+    fahrenheit.set(77);
+    // The Celsius branch is no longer taken.
+    celsius.set(25);
+    assert_eq!(*shown.borrow(), ["20°C", "68°F", "77°F"]);
+
+    use_celsius.set(true);
+    celsius.set(30);
+    assert_eq!(*shown.borrow(), ["20°C", "68°F", "77°F", "25°C", "30°C"]);
+}
+
+#[test]
+fn effect_cleanup_runs_before_rerun_and_on_dispose_test() {
+    let interval = Signal::new(100);
+    let log = Rc::new(std::cell::RefCell::new(Vec::new()));
+    let effect = Effect::new({
+        let (interval, log) = (interval.clone(), log.clone());
+        move || {
+            let interval = *interval.get();
+            log.borrow_mut().push(format!("start {interval}"));
+            let log = log.clone();
+            (interval > 0).then_some(move || log.borrow_mut().push(format!("stop {interval}")))
+        }
+    });
+
+    interval.set(0);
+    interval.set(50);
+    assert!(effect.dispose());
+    assert!(!effect.dispose());
+    drop(effect);
+    assert_eq!(
+        *log.borrow(),
+        ["start 100", "stop 100", "start 0", "start 50", "stop 50"]
+    );
+}
+
+#[test]
+fn effect_runs_once_per_propagation_pass_test() {
+    let a = Signal::new(1);
+    let double = Memo::new({
+        let a = a.clone();
+        move || *a.get() * 2
+    });
+    let sum = Memo::new({
+        let (a, double) = (a.clone(), double.clone());
+        move || *a.get() + double.get()
+    });
+
+    let runs = Rc::new(Cell::new(0));
+    let _effect = Effect::new({
+        let (a, double, sum, runs) = (a.clone(), double.clone(), sum.clone(), runs.clone());
+        move || {
+            let _ = *a.get() + double.get() + sum.get();
+            runs.set(runs.get() + 1);
+        }
+    });
+
+    // Reached directly and through two memos, the effect runs once.
+    a.set(2);
+    assert_eq!(runs.get(), 2);
+
+    // A higher-priority effect clamping `a` runs the effect in a nested pass,
+    // which the outer pass does not repeat.
+    let _clamp = Effect::new_with_priority(
+        {
+            let a = a.clone();
+            move || {
+                if *a.get() > 10 {
+                    a.set(10);
+                }
+            }
+        },
+        1,
+    );
+    a.set(20);
+    assert_eq!(runs.get(), 3);
+    assert_eq!(sum.get(), 30);
+}
+
+#[test]
+fn scope_disposes_nested_scopes_and_keeps_effect_children_to_their_parent_test() {
+    let show = Signal::new(true);
+    let count = Signal::new(0);
+    let log = Rc::new(std::cell::RefCell::new(Vec::new()));
+
+    let page = Scope::new();
+    let widget = page.run(|_| {
+        let widget = Scope::new();
+        widget.run(|_| {
+            let (show, count, log) = (show.clone(), count.clone(), log.clone());
+            Effect::new(move || {
+                if *show.get() {
+                    let (count, log) = (count.clone(), log.clone());
+                    Effect::new(move || log.borrow_mut().push(*count.get()));
+                }
+            });
+        });
+        Rc::downgrade(&widget)
+    });
+
+    // The inner effect belongs to the run of its parent, not to the scope.
+    count.set(1);
+    show.set(false);
+    count.set(2);
+    assert_eq!(*log.borrow(), [0, 1]);
+
+    show.set(true);
+    drop(page);
+    assert!(widget.upgrade().is_none());
+    count.set(3);
+    assert_eq!(*log.borrow(), [0, 1, 2]);
+}
+
+#[test]
+fn scope_owns_every_kind_of_node_created_inside_it_test() {
+    let scope = Scope::new();
+    let nodes: Vec<std::rc::Weak<dyn std::any::Any>> = scope.run(|_| {
+        let nodes: Vec<Rc<dyn std::any::Any>> = vec![
+            Signal::new_str("text"),
+            Signal::new_bytes(b"bytes"),
+            reactive_cache::CopySignal::new(1),
+            reactive_cache::SignalVec::new(vec![1, 2]),
+            reactive_cache::SwrMemo::new(|| 1),
+            reactive_cache::KeyedMemo::new(|key: &i32| *key),
+            reactive_cache::AsyncMemo::new(|| async { 1 }),
+            reactive_cache::diff_memo::DiffMemo::new(|| 1, |a: &i32, b: &i32| b - a),
+        ];
+        nodes.iter().map(Rc::downgrade).collect()
+    });
+
+    assert!(nodes.iter().all(|node| node.upgrade().is_some()));
+    drop(scope);
+    assert!(nodes.iter().all(|node| node.upgrade().is_none()));
+}
+
+#[test]
+fn scope_stops_owning_nodes_once_its_closure_panicked_test() {
+    let scope = Scope::new();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        scope.run(|_| panic!("failed to build the view"))
+    }));
+    assert!(result.is_err());
+
+    let signal = Rc::downgrade(&Signal::new(0));
+    assert!(signal.upgrade().is_none());
+}
+
+#[test]
+fn on_cleanup_runs_with_the_effect_or_scope_it_was_registered_in_test() {
+    let log = Rc::new(std::cell::RefCell::new(Vec::new()));
+    let trigger = Signal::new(0);
+
+    let scope = Scope::new();
+    scope.run(|_| {
+        let log = log.clone();
+        on_cleanup({
+            let log = log.clone();
+            move || log.borrow_mut().push("scope".to_string())
+        });
+        let trigger = trigger.clone();
+        Effect::new(move || {
+            let n = *trigger.get();
+            let log = log.clone();
+            on_cleanup(move || log.borrow_mut().push(format!("effect {n}")));
+        });
+    });
+
+    trigger.set(1);
+    assert_eq!(*log.borrow(), ["effect 0"]);
+
+    // Effects are disposed before the cleanups of the scope run.
+    drop(scope);
+    assert_eq!(*log.borrow(), ["effect 0", "effect 1", "scope"]);
+
+    // Outside any effect or scope, the closure is dropped.
+    on_cleanup(|| unreachable!());
+}
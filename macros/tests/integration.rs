@@ -1,9 +1,15 @@
 use std::{
-    cell::Cell,
+    cell::{Cell, RefCell},
     rc::{Rc, Weak},
 };
 
-use reactive_cache::prelude::*;
+use reactive_cache::{
+    Runtime, SwrMemo,
+    inspect::{self, Event},
+    memo::set_cache_budget,
+    prelude::*,
+    runtime,
+};
 use reactive_macros::{memo, signal};
 
 // ----------------------
@@ -188,3 +194,357 @@ fn test_global_and_vm_interaction_with_memory_cleanup() {
     assert_eq!(get_global_number(), 100);
     assert_eq!(get_global_string(), "hello".to_string());
 }
+
+#[test]
+fn test_strict_mode_accepts_tracked_memos() {
+    reactive_cache::memo::set_strict(true);
+
+    let a = Signal::new(1);
+    let double = Memo::new({
+        let a = a.clone();
+        move || *a.get() * 2
+    });
+    let label = Memo::new({
+        let double = double.clone();
+        move || format!("double: {}", double.get())
+    });
+
+    let seen = Rc::new(Cell::new(0));
+    let _effect = Effect::new({
+        let (label, seen) = (label.clone(), seen.clone());
+        move || {
+            let _ = label.get();
+            seen.set(seen.get() + 1);
+        }
+    });
+
+    // Cache hits are recomputed and compared without changing the dependencies.
+    assert_eq!(label.get(), "double: 2");
+    assert_eq!(label.get(), "double: 2");
+    a.set(3);
+    assert_eq!(label.get(), "double: 6");
+    assert_eq!(seen.get(), 2);
+
+    reactive_cache::memo::set_strict(false);
+}
+
+#[test]
+fn test_each_thread_has_its_own_runtime() {
+    let a = Signal::new(1);
+    let before = runtime::stats();
+
+    let other = std::thread::spawn(|| {
+        let b = Signal::new(2);
+        let runs = Rc::new(Cell::new(0));
+        let _effect = Effect::new({
+            let (b, runs) = (b.clone(), runs.clone());
+            move || {
+                let _ = *b.get();
+                runs.set(runs.get() + 1);
+            }
+        });
+        b.set(3);
+        (runtime::stats().signals, runs.get())
+    });
+    assert_eq!(other.join().unwrap(), (1, 2));
+
+    assert_eq!(runtime::stats(), before);
+    assert_eq!(*a.get(), 1);
+}
+
+#[test]
+fn test_list_diff_patches_rebuild_the_new_list() {
+    use reactive_cache::diff_memo::{ListOp, list_diff};
+
+    let cases: [(&[i32], &[i32]); 6] = [
+        (&[], &[1, 2]),
+        (&[1, 2], &[]),
+        (&[1, 2, 3], &[1, 2, 3]),
+        (&[1, 2, 3, 4, 5], &[1, 9, 5]),
+        (&[1, 5], &[1, 2, 3, 4, 5]),
+        (&[1, 2, 3], &[4, 5, 6, 7]),
+    ];
+    for (old, new) in cases {
+        let mut list = old.to_vec();
+        for op in list_diff(old, new) {
+            match op {
+                ListOp::Insert(i, v) => list.insert(i, v),
+                ListOp::Remove(i) => drop(list.remove(i)),
+                ListOp::Update(i, v) => list[i] = v,
+            }
+        }
+        assert_eq!(list, new);
+    }
+}
+
+#[test]
+fn test_invalidate_tag_recomputes_tagged_memos_and_their_readers() {
+    use reactive_cache::invalidate_tag;
+
+    let rate = Rc::new(Cell::new(2));
+    let price = Memo::new_tagged(
+        {
+            let rate = rate.clone();
+            move || 10 * rate.get()
+        },
+        ["pricing"],
+    );
+    let untagged_runs = Rc::new(Cell::new(0));
+    let untagged = Memo::new({
+        let (rate, runs) = (rate.clone(), untagged_runs.clone());
+        move || {
+            runs.set(runs.get() + 1);
+            rate.get()
+        }
+    });
+    let label = Memo::new({
+        let price = price.clone();
+        move || format!("{} EUR", price.get())
+    });
+    let seen = Rc::new(Cell::new(0));
+    let effect = Effect::new({
+        let (price, seen) = (price.clone(), seen.clone());
+        move || seen.set(price.get())
+    });
+
+    assert_eq!(label.get(), "20 EUR");
+    assert_eq!(untagged.get(), 2);
+    rate.set(3);
+    assert_eq!(label.get(), "20 EUR");
+
+    assert_eq!(invalidate_tag("pricing"), 1);
+    assert_eq!(seen.get(), 30);
+    assert_eq!(label.get(), "30 EUR");
+
+    // Untagged memos keep their cached values.
+    assert_eq!(untagged.get(), 2);
+    assert_eq!(untagged_runs.get(), 1);
+
+    drop(price);
+    drop(label);
+    drop(effect);
+    assert_eq!(invalidate_tag("pricing"), 0);
+    assert_eq!(invalidate_tag("unknown"), 0);
+}
+
+#[test]
+fn test_cache_eviction_prefers_cheap_values() {
+    let runs = Rc::new(Cell::new(0));
+    let expensive = Memo::new({
+        let runs = runs.clone();
+        move || {
+            runs.set(runs.get() + 1);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            1
+        }
+    });
+    let cheap: Vec<_> = (0..5).map(|i| Memo::new(move || i)).collect();
+    expensive.set_bounded(true);
+    for memo in &cheap {
+        memo.set_bounded(true);
+    }
+
+    // Evicts every other value first.
+    set_cache_budget(0);
+    assert_eq!(runtime::stats().cache_weight, 0);
+    set_cache_budget(2);
+    expensive.get();
+    for memo in &cheap {
+        memo.get();
+    }
+
+    // The least recently used value survived, as it is the most expensive one.
+    expensive.get();
+    assert_eq!(runs.get(), 1);
+    set_cache_budget(128);
+}
+
+#[test]
+fn test_unbounded_memos_keep_their_values() {
+    let runs = Rc::new(Cell::new(0));
+    let hot = Memo::new({
+        let runs = runs.clone();
+        move || {
+            runs.set(runs.get() + 1);
+            1
+        }
+    });
+    hot.get();
+
+    // Neither other memos nor a smaller budget evict the value.
+    let others: Vec<_> = (0..200).map(|i| Memo::new(move || i)).collect();
+    for memo in &others {
+        memo.get();
+    }
+    set_cache_budget(0);
+    hot.get();
+    assert_eq!(runs.get(), 1);
+    assert!(hot.is_cached());
+    set_cache_budget(128);
+}
+
+#[test]
+fn test_dropped_memo_leaves_no_cache_entry_or_edges() {
+    let source = Signal::new(1);
+    let before = runtime::stats();
+
+    for i in 0..100 {
+        let memo = Memo::new({
+            let source = source.clone();
+            move || *source.get() + i
+        });
+        memo.set_bounded(true);
+        // Memos allocated where a dropped one lived compute their own value.
+        assert_eq!(memo.get(), 1 + i);
+    }
+
+    let stats = runtime::stats();
+    assert_eq!(stats.cache_entries, before.cache_entries);
+    assert_eq!(stats.cache_weight, before.cache_weight);
+    assert_eq!(stats.edges, before.edges);
+}
+
+#[test]
+fn test_memo_stops_depending_on_signals_it_no_longer_reads() {
+    let use_metric = Signal::new(true);
+    let meters = Signal::new(1000);
+    let feet = Signal::new(3281);
+    let invalidations = Rc::new(RefCell::new(Vec::new()));
+    let hook = inspect::add_hook({
+        let invalidations = invalidations.clone();
+        move |e: &Event| {
+            if let Event::Invalidated(id) = e {
+                invalidations.borrow_mut().push(*id);
+            }
+        }
+    });
+    let distance = Memo::new({
+        let (use_metric, meters, feet) = (use_metric.clone(), meters.clone(), feet.clone());
+        move || {
+            if *use_metric.get() {
+                *meters.get()
+            } else {
+                *feet.get()
+            }
+        }
+    });
+
+    let edges = runtime::stats().edges;
+    assert_eq!(distance.get(), 1000);
+    assert_eq!(runtime::stats().edges, edges + 2);
+
+    use_metric.set(false);
+    assert_eq!(distance.get(), 3281);
+    assert_eq!(runtime::stats().edges, edges + 2);
+
+    // Changing the signal the memo no longer reads leaves it alone.
+    invalidations.borrow_mut().clear();
+    meters.set(1609);
+    assert!(invalidations.borrow().is_empty());
+    feet.set(5280);
+    assert_eq!(*invalidations.borrow(), [distance.id()]);
+    assert_eq!(distance.get(), 5280);
+
+    inspect::remove_hook(hook);
+}
+
+#[test]
+#[cfg_attr(not(feature = "nightly"), ignore = "early cutoff needs nightly")]
+fn test_swr_memo_notifies_dependents_once_fresh_value_lands() {
+    let width = Signal::new(2);
+    let parity = SwrMemo::new({
+        let width = width.clone();
+        move || *width.get() % 2
+    });
+    let label = Memo::new({
+        let parity = parity.clone();
+        move || format!("parity {}", parity.get())
+    });
+    let renders = Rc::new(RefCell::new(Vec::new()));
+    let _effect = Effect::new({
+        let (label, renders) = (label.clone(), renders.clone());
+        move || renders.borrow_mut().push(label.get())
+    });
+
+    width.set(3);
+    assert_eq!(label.get(), "parity 0");
+    assert_eq!(*renders.borrow(), ["parity 0"]);
+
+    reactive_cache::flush();
+    assert!(!parity.is_stale());
+    assert_eq!(*renders.borrow(), ["parity 0", "parity 1"]);
+
+    // An equal fresh value notifies no one.
+    width.set(5);
+    reactive_cache::flush();
+    assert_eq!(*renders.borrow(), ["parity 0", "parity 1"]);
+}
+
+#[test]
+fn test_runtimes_keep_their_own_cache() {
+    let before = runtime::stats();
+    let library = Runtime::new();
+
+    let (source, memos) = library.enter(|| {
+        set_cache_budget(1);
+        let source = Signal::new(1);
+        let memos: Vec<_> = (0..3)
+            .map(|i| {
+                let source = source.clone();
+                let memo = Memo::new(move || *source.get() + i);
+                memo.set_bounded(true);
+                memo
+            })
+            .collect();
+        (source, memos)
+    });
+
+    // The budget and the cached values of the library stay in its runtime.
+    library.enter(|| {
+        for memo in &memos {
+            memo.get();
+        }
+        assert_eq!(runtime::stats().cache_entries, 1);
+        assert_eq!(runtime::stats().cache_evictions, 2);
+        source.set(2);
+        drop((source, memos));
+    });
+    assert_eq!(runtime::stats().cache_entries, before.cache_entries);
+    assert_eq!(runtime::stats().cache_evictions, before.cache_evictions);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "Enter the runtime a node was created in")]
+fn test_nodes_are_bound_to_their_runtime() {
+    let library = Runtime::new();
+    let source = library.enter(|| Signal::new(1));
+    source.set(2);
+}
+
+#[test]
+fn test_update_make_mut_does_not_clone_an_unshared_value() {
+    #[derive(PartialEq, Eq)]
+    struct Counted(Vec<i32>);
+
+    thread_local! {
+        static CLONES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            CLONES.set(CLONES.get() + 1);
+            Counted(self.0.clone())
+        }
+    }
+
+    let signal = Signal::new(Rc::new(Counted(vec![1])));
+    assert!(signal.update_make_mut(|v| v.0.push(2)));
+    assert_eq!(CLONES.get(), 0);
+
+    let snapshot = Rc::clone(&signal.get());
+    assert!(signal.update_make_mut(|v| v.0.push(3)));
+    assert_eq!(CLONES.get(), 1);
+    assert_eq!(snapshot.0, [1, 2]);
+    assert_eq!(signal.get().0, [1, 2, 3]);
+}
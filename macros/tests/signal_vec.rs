@@ -0,0 +1,84 @@
+use reactive_cache::{SignalVec, prelude::*};
+
+/// A small deterministic pseudo-random generator, so failures are reproducible.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % bound.max(1)
+    }
+}
+
+#[test]
+fn views_match_recomputation_test() {
+    let source = SignalVec::new(vec![5_u32, 3, 8]);
+    let pred = Signal::new((|n: &u32| n.is_multiple_of(2)) as fn(&u32) -> bool);
+    let filtered = source.filtered(pred.clone());
+    let sorted = source.sorted_by(|a, b| (a / 10).cmp(&(b / 10)));
+    let filtered_sorted = filtered.sorted_by(|a, b| b.cmp(a));
+
+    let mut rng = Lcg(42);
+    for step in 0..500 {
+        let len = source.len();
+        let value = rng.next(100) as u32;
+        match rng.next(7) {
+            0 => source.push(value),
+            1 => source.insert(rng.next(len + 1), value),
+            2 if len > 0 => drop(source.remove(rng.next(len))),
+            3 if len > 0 => source.set(rng.next(len), value),
+            4 if step % 50 == 0 => source.clear(),
+            5 if step % 20 == 0 => source.replace((0..rng.next(8)).map(|i| i as u32 * 7).collect()),
+            6 if step % 30 == 0 => {
+                pred.set(if step % 60 == 0 {
+                    |n| n % 2 == 0
+                } else {
+                    |n| *n > 50
+                });
+            }
+            _ => source.push(value),
+        }
+
+        let items = source.to_vec();
+        let pred = *pred.get();
+
+        let expected: Vec<u32> = items.iter().copied().filter(&pred).collect();
+        assert_eq!(filtered.to_vec(), expected, "filtered view at step {step}");
+
+        let mut expected = items.clone();
+        expected.sort_by_key(|n| n / 10);
+        assert_eq!(sorted.to_vec(), expected, "sorted view at step {step}");
+
+        let mut expected: Vec<u32> = items.iter().copied().filter(&pred).collect();
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(
+            filtered_sorted.to_vec(),
+            expected,
+            "chained view at step {step}"
+        );
+    }
+}
+
+#[test]
+fn filtered_view_dependents_may_change_the_source_test() {
+    let source = SignalVec::new(vec![1, 2]);
+    let pred = Signal::new((|n: &i32| n % 2 == 0) as fn(&i32) -> bool);
+    let view = source.filtered(pred.clone());
+
+    // Completes the source once the view shows two items.
+    let _effect = Effect::new({
+        let (source, view) = (source.clone(), view.clone());
+        move || {
+            if view.len() == 2 && source.len() < 3 {
+                source.push(3);
+            }
+        }
+    });
+
+    pred.set(|_| true);
+    assert_eq!(source.to_vec(), [1, 2, 3]);
+    assert_eq!(view.to_vec(), [1, 2, 3]);
+}
@@ -0,0 +1,103 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use reactive_cache::{
+    prelude::*,
+    task::{Concurrency, TaskGroup},
+};
+
+/// Returns `Pending` on its first poll, like a request in flight.
+async fn yield_once() {
+    let mut yielded = false;
+    std::future::poll_fn(|cx| {
+        if std::mem::replace(&mut yielded, true) {
+            Poll::Ready(())
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+/// Spawns tasks logging when they start and complete, and returns the log.
+fn spawn_logged(group: &TaskGroup, count: usize) -> Rc<RefCell<Vec<String>>> {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    for i in 0..count {
+        let log = log.clone();
+        group.spawn(async move {
+            log.borrow_mut().push(format!("start {i}"));
+            yield_once().await;
+            log.borrow_mut().push(format!("end {i}"));
+        });
+    }
+    log
+}
+
+fn poll(group: &TaskGroup) -> Poll<()> {
+    group.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+#[test]
+fn queued_tasks_run_one_at_a_time_in_order() {
+    let group = TaskGroup::new(Concurrency::Queue);
+    let log = spawn_logged(&group, 3);
+    assert_eq!((group.active(), group.queued()), (1, 2));
+
+    while poll(&group).is_pending() {}
+    assert_eq!(
+        *log.borrow(),
+        ["start 0", "end 0", "start 1", "end 1", "start 2", "end 2"]
+    );
+    assert_eq!((group.active(), group.queued()), (0, 0));
+}
+
+#[test]
+fn parallel_tasks_respect_the_limit() {
+    let group = TaskGroup::new(Concurrency::Parallel(2));
+    let log = spawn_logged(&group, 3);
+    assert_eq!((group.active(), group.queued()), (2, 1));
+
+    assert!(poll(&group).is_pending());
+    assert_eq!(*log.borrow(), ["start 0", "start 1"]);
+
+    // Completing the first two starts the third one.
+    assert!(poll(&group).is_pending());
+    assert_eq!(
+        *log.borrow(),
+        ["start 0", "start 1", "end 0", "end 1", "start 2"]
+    );
+    assert!(poll(&group).is_ready());
+}
+
+#[test]
+fn latest_cancels_the_task_in_flight() {
+    let count = Signal::new(0);
+    let completed = Rc::new(Cell::new(None));
+    let group = TaskGroup::new(Concurrency::Latest);
+    let effect = group.effect({
+        let (count, completed) = (count.clone(), completed.clone());
+        move || {
+            let (count, completed) = (*count.get(), completed.clone());
+            async move {
+                yield_once().await;
+                completed.set(Some(count));
+            }
+        }
+    });
+
+    assert!(poll(&group).is_pending());
+    // Spawned while the first task is in flight.
+    count.set(1);
+    assert_eq!(group.active(), 1);
+    while poll(&group).is_pending() {}
+    assert_eq!(completed.get(), Some(1));
+
+    drop(effect);
+    count.set(2);
+    assert!(poll(&group).is_ready());
+    assert_eq!(completed.get(), Some(1));
+}
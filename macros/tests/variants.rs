@@ -0,0 +1,47 @@
+use std::{cell::Cell, rc::Rc};
+
+use reactive_cache::prelude::*;
+use reactive_macros::SignalVariants;
+
+#[derive(SignalVariants, PartialEq, Eq)]
+pub enum AppMode {
+    Idle,
+    EditingText(String),
+    Saving { progress: u8 },
+}
+
+#[test]
+fn variant_selectors_test() {
+    let mode = Signal::new(AppMode::Idle);
+    let selectors = AppMode::selectors(&mode);
+
+    assert!(selectors.is_idle().get());
+    assert!(!selectors.is_editing_text().get());
+    assert!(!selectors.is_saving().get());
+
+    let editing_runs = Rc::new(Cell::new(0));
+    let _effect = Effect::new({
+        let is_editing_text = selectors.is_editing_text();
+        let editing_runs = editing_runs.clone();
+        move || {
+            let _ = is_editing_text.get();
+            editing_runs.set(editing_runs.get() + 1);
+        }
+    });
+    assert_eq!(editing_runs.get(), 1);
+
+    mode.set(AppMode::Saving { progress: 0 });
+    mode.set(AppMode::Saving { progress: 50 });
+    mode.set(AppMode::Idle);
+    assert_eq!(editing_runs.get(), 1);
+
+    mode.set(AppMode::EditingText("a".to_string()));
+    assert_eq!(editing_runs.get(), 2);
+
+    mode.set(AppMode::EditingText("ab".to_string()));
+    assert_eq!(editing_runs.get(), 2);
+
+    assert!(!selectors.is_idle().get());
+    assert!(selectors.is_editing_text().get());
+    assert!(!selectors.is_saving().get());
+}